@@ -0,0 +1,316 @@
+//! Sprite Animations
+//!
+//! An `Animator` holds frame tables keyed by animation name ("idle", "walk",
+//! "jump", ...) and steps through the frames of whichever one is playing.
+//! The tables are loaded from an Aseprite JSON sheet export, where each
+//! animation is a frame tag, so artists can add or retime animations without
+//! touching the code. Both the "array" and "hash" export layouts are read.
+//!
+//! A `SpriteSheet` pairs the frame tables with the sheet's image. The player
+//! and enemies are drawn from one when it's there and has the animation they
+//! need, and fall back to their built-in drawing otherwise.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+/// Where the player's sprite sheet is looked for
+pub const PLAYER_SPRITES_PATH: &str = "assets/sprites/player.json";
+/// Where the enemies' sprite sheet is looked for, with one tag per enemy kind
+pub const ENEMY_SPRITES_PATH: &str = "assets/sprites/enemies.json";
+
+/// One frame of an animation: where it sits on the sprite sheet and how long it shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationFrame {
+    /// Area of the sprite sheet image holding the frame
+    pub source: Rect,
+    /// Seconds the frame is shown for
+    pub duration: f32,
+}
+
+/// Frame tables for a sprite, and which of them is playing
+#[derive(Debug, Clone, Default)]
+pub struct Animator {
+    tables: HashMap<String, Vec<AnimationFrame>>,
+    /// File name of the sheet's image, as the export gives it
+    image: Option<String>,
+    current: Option<String>,
+    elapsed: f32,
+}
+
+/// Aseprite's rectangle layout
+#[derive(Debug, Deserialize)]
+struct AseRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+/// A frame in an Aseprite sheet
+#[derive(Debug, Deserialize)]
+struct AseFrame {
+    #[serde(default)]
+    filename: String,
+    frame: AseRect,
+    /// Milliseconds
+    duration: f32,
+}
+
+/// Aseprite exports frames either as a list or as a map keyed by file name
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AseFrames {
+    Array(Vec<AseFrame>),
+    Hash(HashMap<String, AseFrame>),
+}
+
+/// An animation tag: a named, inclusive range of frames
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AseTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AseMeta {
+    #[serde(default)]
+    frame_tags: Vec<AseTag>,
+    #[serde(default)]
+    image: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AseSheet {
+    frames: AseFrames,
+    meta: AseMeta,
+}
+
+/// Number at the end of a frame's file name ("mario 12.aseprite" is 12),
+/// used to put hash exports back in frame order
+fn frame_number(filename: &str) -> Option<u32> {
+    let stem = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem);
+    let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    stem[stem.len() - digits..].parse().ok()
+}
+
+impl Animator {
+    /// Build the frame tables from an Aseprite JSON sheet export, one table per frame tag.
+    /// Tags pointing past the last frame are an error.
+    pub fn from_aseprite_json(json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let sheet: AseSheet = serde_json::from_str(json)?;
+        let frames: Vec<AseFrame> = match sheet.frames {
+            AseFrames::Array(frames) => frames,
+            AseFrames::Hash(frames) => {
+                let mut frames: Vec<AseFrame> = frames
+                    .into_iter()
+                    .map(|(filename, frame)| AseFrame { filename, ..frame })
+                    .collect();
+                frames.sort_by_key(|f| (frame_number(&f.filename), f.filename.clone()));
+                frames
+            }
+        };
+        let frames: Vec<AnimationFrame> = frames
+            .iter()
+            .map(|f| AnimationFrame {
+                source: Rect::new(f.frame.x, f.frame.y, f.frame.w, f.frame.h),
+                duration: f.duration / 1000.0,
+            })
+            .collect();
+
+        let mut tables = HashMap::new();
+        for tag in sheet.meta.frame_tags {
+            if tag.from > tag.to || tag.to >= frames.len() {
+                return Err(format!("tag '{}' covers frames {}-{} but the sheet has {}", tag.name, tag.from, tag.to, frames.len()).into());
+            }
+            let mut table = frames[tag.from..=tag.to].to_vec();
+            match tag.direction.as_str() {
+                "reverse" => table.reverse(),
+                // Forwards then back again, without repeating the end frames
+                "pingpong" if table.len() > 2 => {
+                    let back: Vec<AnimationFrame> = table[1..table.len() - 1].iter().rev().copied().collect();
+                    table.extend(back);
+                }
+                _ => {}
+            }
+            tables.insert(tag.name, table);
+        }
+        Ok(Self { tables, image: sheet.meta.image, ..Default::default() })
+    }
+
+    /// Load the frame tables from an Aseprite JSON sheet file
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_aseprite_json(&fs::read_to_string(filepath)?)
+    }
+
+    /// The frames of a named animation, if the sheet has it
+    pub fn frames(&self, name: &str) -> Option<&[AnimationFrame]> {
+        self.tables.get(name).map(Vec::as_slice)
+    }
+
+    /// File name of the sheet's image, if the export names one
+    pub fn image(&self) -> Option<&str> {
+        self.image.as_deref()
+    }
+
+    /// The frame a named animation shows `time` seconds after it starts,
+    /// looping. None if the sheet doesn't have the animation.
+    pub fn frame_at(&self, name: &str, time: f32) -> Option<&AnimationFrame> {
+        let frames = self.frames(name)?;
+        let length: f32 = frames.iter().map(|f| f.duration).sum();
+        let mut time = if length > 0.0 { time % length } else { 0.0 };
+        for frame in frames {
+            if time < frame.duration {
+                return Some(frame);
+            }
+            time -= frame.duration;
+        }
+        frames.last()
+    }
+
+    /// Which of the given animation names the sheet doesn't have
+    pub fn missing_tags<'a>(&self, names: &[&'a str]) -> Vec<&'a str> {
+        names.iter().copied().filter(|name| !self.tables.contains_key(*name)).collect()
+    }
+
+    /// Switch to a named animation, starting it from its first frame. An
+    /// animation the sheet doesn't have leaves the current one playing.
+    /// Returns whether the animation is now playing.
+    pub fn play(&mut self, name: &str) -> bool {
+        if self.current.as_deref() == Some(name) {
+            return true;
+        }
+        if !self.tables.contains_key(name) {
+            return false;
+        }
+        self.current = Some(name.to_owned());
+        self.elapsed = 0.0;
+        true
+    }
+
+    /// Advance the playing animation
+    pub fn update(&mut self, delta_time: f32) {
+        self.elapsed += delta_time;
+    }
+
+    /// The frame to show now, looping the playing animation. None until something plays.
+    pub fn current_frame(&self) -> Option<&AnimationFrame> {
+        self.frame_at(self.current.as_deref()?, self.elapsed)
+    }
+}
+
+/// A sprite sheet image and the animations cut from it
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    pub texture: Texture2D,
+    pub animations: Animator,
+}
+
+impl SpriteSheet {
+    /// Load an Aseprite JSON export and the image it names, which sits next
+    /// to it (or shares its name, as a PNG, if the export doesn't name one).
+    /// Returns `None` if there's no sheet there.
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let path = filepath.as_ref();
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let animations = Animator::from_aseprite_json(&json)?;
+        let image = match animations.image() {
+            Some(image) => path.with_file_name(image),
+            None => path.with_extension("png"),
+        };
+        let texture = Texture2D::from_file_with_format(&fs::read(image)?, None);
+        texture.set_filter(FilterMode::Nearest);
+        Ok(Some(Self { texture, animations }))
+    }
+
+    /// Draw the frame a named animation shows `time` seconds in, stretched
+    /// over `dest` and mirrored if `flip_x`. Draws nothing and returns false
+    /// if the sheet doesn't have the animation, so the caller can fall back.
+    pub fn draw(&self, name: &str, time: f32, dest: Rect, flip_x: bool) -> bool {
+        let Some(frame) = self.animations.frame_at(name, time) else { return false };
+        let params = DrawTextureParams {
+            dest_size: Some(dest.size()),
+            source: Some(frame.source),
+            flip_x,
+            ..Default::default()
+        };
+        draw_texture_ex(&self.texture, dest.x, dest.y, WHITE, params);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_level::AnimationState;
+
+    #[test]
+    fn test_aseprite_tags_become_frame_tables() {
+        let json = r#"{
+            "frames": {
+                "mario 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+                "mario 1.aseprite": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+                "mario 2.aseprite": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "duration": 80 },
+                "mario 10.aseprite": { "frame": { "x": 48, "y": 0, "w": 16, "h": 16 }, "duration": 200 }
+            },
+            "meta": {
+                "frameTags": [
+                    { "name": "idle", "from": 0, "to": 0, "direction": "forward" },
+                    { "name": "walk", "from": 1, "to": 3, "direction": "forward" }
+                ]
+            }
+        }"#;
+        let mut animator = Animator::from_aseprite_json(json).unwrap();
+
+        let walk: Vec<(f32, f32)> = animator.frames("walk").unwrap().iter().map(|f| (f.source.x, f.duration)).collect();
+        assert_eq!(walk, vec![(16.0, 0.1), (32.0, 0.08), (48.0, 0.2)]);
+        assert_eq!(animator.missing_tags(&["idle", "walk", "jump"]), vec!["jump"]);
+
+        // Asking for a missing animation keeps the current one going
+        assert!(animator.play("walk"));
+        assert!(!animator.play("jump"));
+        animator.update(0.15);
+        assert_eq!(animator.current_frame().unwrap().source.x, 32.0);
+    }
+    #[test]
+    fn test_player_states_pick_their_animation_from_the_sheet() {
+        let json = r#"{
+            "frames": [
+                { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 500 },
+                { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+                { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "duration": 100 }
+            ],
+            "meta": {
+                "image": "player.png",
+                "frameTags": [
+                    { "name": "idle", "from": 0, "to": 0 },
+                    { "name": "walk", "from": 1, "to": 2 }
+                ]
+            }
+        }"#;
+        let animations = Animator::from_aseprite_json(json).unwrap();
+        assert_eq!(animations.image(), Some("player.png"));
+
+        let source_x = |state: AnimationState, time: f32| animations.frame_at(state.tag(), time).map(|f| f.source.x);
+        assert_eq!(source_x(AnimationState::Idle, 3.0), Some(0.0));
+        assert_eq!(source_x(AnimationState::Walking, 0.15), Some(32.0));
+        assert_eq!(source_x(AnimationState::Walking, 0.25), Some(16.0));
+        // No jump animation: the player is drawn the built-in way instead
+        assert_eq!(source_x(AnimationState::Jumping, 0.0), None);
+
+        // And without a sheet at all, nothing is loaded
+        assert!(SpriteSheet::load("no/such/sheet.json").unwrap().is_none());
+    }
+}
@@ -0,0 +1,203 @@
+//! Title Menu and Attract Mode
+//!
+//! The game opens on a title menu. Left alone for a few seconds, the menu
+//! plays a recorded demo run of the level behind it, like an arcade cabinet's
+//! attract mode, and any key press goes back to the menu. The demo is an
+//! input script in the same format the headless simulation reads, played back
+//! through `SimpleLevel::step` so it runs the same way every time.
+
+use macroquad::prelude::*;
+
+use crate::frame_limiter::{FrameLimiter, FrameSettings};
+use crate::input::InputState;
+use crate::simple_level::SimpleLevel;
+use crate::simulation::{SIMULATION_DT, parse_script};
+use crate::viewport::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH, Viewport};
+
+/// Seconds the menu sits untouched before the demo starts
+pub const ATTRACT_IDLE_TIME: f32 = 8.0;
+
+/// Recorded inputs for the demo run, one line per simulation step
+pub const DEMO_SCRIPT: &str = "\
+# Stand still a moment, then run right, hopping the gaps and enemies
+30 idle
+90 right
+20 right jump
+60 right
+25 right jump
+40 right
+10 idle
+20 jump
+80 right
+25 right jump
+30 right
+40 left
+20 left jump
+60 right
+25 right jump
+120 right
+";
+
+/// What the title screen is showing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttractState {
+    Menu,
+    Demo,
+}
+
+/// The title menu's idle timer and the demo it plays
+pub struct AttractMode {
+    pub state: AttractState,
+    idle_timer: f32,
+    inputs: Vec<InputState>,
+    demo: Option<SimpleLevel>,
+    demo_step: usize,
+    demo_time: f32,
+}
+
+impl AttractMode {
+    /// Attract mode playing back the given inputs as its demo
+    pub fn new(inputs: Vec<InputState>) -> Self {
+        Self { state: AttractState::Menu, idle_timer: 0.0, inputs, demo: None, demo_step: 0, demo_time: 0.0 }
+    }
+
+    /// Attract mode playing the built-in demo run
+    pub fn with_default_demo() -> Self {
+        Self::new(parse_script(DEMO_SCRIPT).expect("the demo script is valid"))
+    }
+
+    /// The level being played by the demo, while it's running
+    pub fn demo(&self) -> Option<&SimpleLevel> {
+        self.demo.as_ref()
+    }
+
+    /// Count idle time on the menu and start the demo once there's been enough
+    /// of it, or advance the running demo. A key press resets the idle timer on
+    /// the menu and stops the demo.
+    pub fn update(&mut self, key_pressed: bool, delta_time: f32) {
+        match self.state {
+            AttractState::Menu if key_pressed => self.idle_timer = 0.0,
+            AttractState::Menu => {
+                self.idle_timer += delta_time;
+                if self.idle_timer >= ATTRACT_IDLE_TIME {
+                    self.start_demo();
+                }
+            }
+            AttractState::Demo if key_pressed => self.back_to_menu(),
+            AttractState::Demo => self.play_demo(delta_time),
+        }
+    }
+
+    fn start_demo(&mut self) {
+        self.state = AttractState::Demo;
+        self.demo = Some(SimpleLevel::new());
+        self.demo_step = 0;
+        self.demo_time = 0.0;
+    }
+
+    fn back_to_menu(&mut self) {
+        self.state = AttractState::Menu;
+        self.idle_timer = 0.0;
+        self.demo = None;
+    }
+
+    /// Feed the demo its recorded inputs at the rate they were recorded,
+    /// returning to the menu when they run out or the run ends
+    fn play_demo(&mut self, delta_time: f32) {
+        let Some(level) = self.demo.as_mut() else { return };
+        self.demo_time += delta_time;
+        while self.demo_time >= SIMULATION_DT {
+            self.demo_time -= SIMULATION_DT;
+            let Some(input) = self.inputs.get(self.demo_step) else { break };
+            level.step(input, SIMULATION_DT);
+            self.demo_step += 1;
+        }
+        if self.demo_step >= self.inputs.len() || level.game_over || level.game_won {
+            self.back_to_menu();
+        }
+    }
+
+    /// Draw the demo, if it's running, with the title over it
+    pub fn draw(&self, viewport: &Viewport) {
+        let (view_width, view_height) = (VIRTUAL_WIDTH, VIRTUAL_HEIGHT);
+        match &self.demo {
+            Some(level) => {
+                let (zoom, top) = level.camera_zoom();
+                let camera = viewport.begin_zoomed(zoom, top);
+                level.draw_world(&camera);
+                viewport.begin();
+                level.draw_overlay();
+            }
+            None => {
+                viewport.begin();
+                clear_background(Color::new(0.36, 0.58, 0.99, 1.0));
+            }
+        }
+
+        let title = "RUST MARIO";
+        let title_width = measure_text(title, None, 64, 1.0).width;
+        draw_text(title, (view_width - title_width) / 2.0, view_height / 3.0, 64.0, WHITE);
+        let prompt = if self.state == AttractState::Demo { "DEMO - press any key" } else { "Press Enter to play" };
+        let prompt_width = measure_text(prompt, None, 28, 1.0).width;
+        draw_text(prompt, (view_width - prompt_width) / 2.0, view_height / 3.0 + 50.0, 28.0, YELLOW);
+        viewport.end();
+    }
+}
+
+/// Show the title menu, playing the demo while it's idle, until Enter is
+/// pressed on the menu. Returns false if the window was asked to close.
+pub async fn run_title_menu(frame_settings: FrameSettings) -> bool {
+    let mut limiter = FrameLimiter::new(frame_settings.target_fps);
+    let viewport = Viewport::default();
+    let mut attract = AttractMode::with_default_demo();
+
+    loop {
+        limiter.begin_frame();
+        if is_quit_requested() {
+            return false;
+        }
+        let key = get_last_key_pressed();
+        if attract.state == AttractState::Menu {
+            match key {
+                Some(KeyCode::Enter) => return true,
+                Some(KeyCode::Escape) => return false,
+                _ => {}
+            }
+        }
+        attract.update(key.is_some(), get_frame_time());
+        attract.draw(&viewport);
+
+        limiter.end_frame();
+        next_frame().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_menu_plays_the_demo_until_a_key_is_pressed() {
+        let mut attract = AttractMode::with_default_demo();
+
+        // Pressing keys on the menu keeps it from timing out
+        attract.update(false, ATTRACT_IDLE_TIME - 1.0);
+        attract.update(true, 0.5);
+        attract.update(false, ATTRACT_IDLE_TIME - 1.0);
+        assert_eq!(attract.state, AttractState::Menu);
+
+        attract.update(false, 1.0);
+        assert_eq!(attract.state, AttractState::Demo);
+
+        // The recorded inputs drive the demo level
+        let start_x = attract.demo().unwrap().player.x;
+        for _ in 0..120 {
+            attract.update(false, SIMULATION_DT);
+        }
+        assert!(attract.demo().unwrap().player.x > start_x);
+
+        attract.update(true, SIMULATION_DT);
+        assert_eq!(attract.state, AttractState::Menu);
+        assert!(attract.demo().is_none());
+    }
+}
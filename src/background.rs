@@ -0,0 +1,159 @@
+//! Background Decoration
+//!
+//! Non-colliding scenery such as bushes, clouds and hills lives in its own
+//! tile layers in the level data. Layers are drawn behind the platforms,
+//! unless marked as foreground, and can scroll slower than the camera for a
+//! parallax effect. They never take part in collision.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The kinds of decoration a background tile can show
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DecorationKind {
+    Bush,
+    Cloud,
+    Hill,
+}
+
+impl DecorationKind {
+    /// Base width of the decoration before scaling
+    pub fn width(&self) -> f32 {
+        match self {
+            DecorationKind::Bush => 40.0,
+            DecorationKind::Cloud => 60.0,
+            DecorationKind::Hill => 120.0,
+        }
+    }
+}
+
+/// A single decoration placed in a background layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundTile {
+    pub kind: DecorationKind,
+    /// Left edge of the tile in layer coordinates
+    pub x: f32,
+    /// Bottom of the tile (where it sits on the ground, or the cloud's base)
+    pub y: f32,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl BackgroundTile {
+    /// Create a decoration at its natural size
+    pub fn new(kind: DecorationKind, x: f32, y: f32) -> Self {
+        Self { kind, x, y, scale: 1.0 }
+    }
+
+    /// Width of the tile once scaled
+    pub fn width(&self) -> f32 {
+        self.kind.width() * self.scale
+    }
+
+    /// Draw the tile with its left edge at `screen_x`
+    pub fn draw(&self, screen_x: f32) {
+        let w = self.width();
+        let s = self.scale;
+        match self.kind {
+            DecorationKind::Bush => {
+                let color = Color::new(0.1, 0.6, 0.15, 1.0);
+                draw_circle(screen_x + w * 0.25, self.y - 8.0 * s, 10.0 * s, color);
+                draw_circle(screen_x + w * 0.5, self.y - 12.0 * s, 13.0 * s, color);
+                draw_circle(screen_x + w * 0.75, self.y - 8.0 * s, 10.0 * s, color);
+            }
+            DecorationKind::Cloud => {
+                draw_circle(screen_x + w * 0.25, self.y - 10.0 * s, 12.0 * s, WHITE);
+                draw_circle(screen_x + w * 0.5, self.y - 16.0 * s, 16.0 * s, WHITE);
+                draw_circle(screen_x + w * 0.75, self.y - 10.0 * s, 12.0 * s, WHITE);
+                draw_rectangle(screen_x + w * 0.25, self.y - 10.0 * s, w * 0.5, 10.0 * s, WHITE);
+            }
+            DecorationKind::Hill => {
+                let color = Color::new(0.3, 0.65, 0.3, 1.0);
+                draw_triangle(
+                    vec2(screen_x, self.y),
+                    vec2(screen_x + w, self.y),
+                    vec2(screen_x + w / 2.0, self.y - w * 0.5),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// A tile layer of decorations sharing one parallax factor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundLayer {
+    /// How far the layer moves relative to the camera: 1.0 moves with the
+    /// level, smaller values scroll slower and look further away
+    #[serde(default = "default_parallax")]
+    pub parallax: f32,
+    pub tiles: Vec<BackgroundTile>,
+    /// Drawn in front of the level instead of behind it, like a tree crown
+    /// the player walks behind
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub foreground: bool,
+}
+
+fn default_parallax() -> f32 {
+    1.0
+}
+
+impl BackgroundLayer {
+    /// Create a layer with the given parallax factor
+    pub fn new(parallax: f32, tiles: Vec<BackgroundTile>) -> Self {
+        Self { parallax, tiles, foreground: false }
+    }
+
+    /// Create a layer drawn in front of the level
+    pub fn foreground(parallax: f32, tiles: Vec<BackgroundTile>) -> Self {
+        Self { foreground: true, ..Self::new(parallax, tiles) }
+    }
+
+    /// Tiles that are on screen for this camera position, with their screen x
+    pub fn visible_tiles(&self, camera_x: f32, view_width: f32) -> impl Iterator<Item = (f32, &BackgroundTile)> {
+        let offset = -camera_x * self.parallax;
+        self.tiles
+            .iter()
+            .map(move |tile| (tile.x + offset, tile))
+            .filter(move |(screen_x, tile)| *screen_x + tile.width() >= 0.0 && *screen_x <= view_width)
+    }
+
+    /// Draw the layer's visible tiles, returning how many were drawn
+    pub fn draw(&self, camera_x: f32, view_width: f32) -> usize {
+        let mut drawn = 0;
+        for (screen_x, tile) in self.visible_tiles(camera_x, view_width) {
+            tile.draw(screen_x);
+            drawn += 1;
+        }
+        drawn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallax_layer_scrolls_slower_than_camera() {
+        let layer = BackgroundLayer::new(
+            0.5,
+            vec![
+                BackgroundTile::new(DecorationKind::Cloud, 200.0, 80.0),
+                BackgroundTile::new(DecorationKind::Hill, 900.0, 400.0),
+                BackgroundTile::new(DecorationKind::Bush, 2000.0, 400.0),
+            ],
+        );
+
+        let visible: Vec<_> = layer.visible_tiles(0.0, 800.0).collect();
+        assert_eq!(visible.len(), 1);
+
+        // At half speed the camera has to move twice as far to reveal the hill
+        let visible: Vec<_> = layer.visible_tiles(400.0, 800.0).collect();
+        assert_eq!(visible.len(), 2);
+        assert_eq!(visible[1].0, 700.0);
+    }
+}
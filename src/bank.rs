@@ -0,0 +1,71 @@
+//! Coin Bank
+//!
+//! Every coin the player picks up is also paid into a bank that outlives
+//! single levels and sessions. After a game over, coins from the bank can be
+//! spent on a continue instead of starting the level from scratch.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Coins the bank is short of for a purchase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsufficientCoins {
+    pub cost: u32,
+    pub balance: u32,
+}
+
+impl fmt::Display for InsufficientCoins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "that costs {} coins but the bank only has {}", self.cost, self.balance)
+    }
+}
+
+impl std::error::Error for InsufficientCoins {}
+
+/// The player's persistent coin savings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoinBank {
+    coins: u32,
+}
+
+impl CoinBank {
+    /// Coins currently saved
+    pub fn balance(&self) -> u32 {
+        self.coins
+    }
+
+    /// Pay coins into the bank
+    pub fn deposit(&mut self, coins: u32) {
+        self.coins = self.coins.saturating_add(coins);
+    }
+
+    /// Take coins out of the bank, leaving it untouched if it can't cover the cost
+    pub fn spend(&mut self, cost: u32) -> Result<(), InsufficientCoins> {
+        self.coins = self.coins.checked_sub(cost).ok_or(InsufficientCoins { cost, balance: self.coins })?;
+        Ok(())
+    }
+
+    /// Take back coins that were paid in but no longer count, emptying the
+    /// bank at most
+    pub fn withdraw(&mut self, coins: u32) {
+        self.coins = self.coins.saturating_sub(coins);
+    }
+
+    /// Load the bank from a JSON file, starting empty if there isn't one yet
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        match fs::read_to_string(filepath) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Save the bank to a JSON file
+    pub fn save<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(filepath, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
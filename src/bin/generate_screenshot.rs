@@ -1,51 +1,101 @@
 //! Screenshot Generator
-//! 
+//!
 //! This binary generates a screenshot of the Rust Mario game
 //! and saves it to assets/screenshot.png for documentation purposes.
+//!
+//! With `--duration <secs>` it instead lets you play for that long while
+//! recording, then saves the final frame as a screenshot along with a GIF
+//! of the whole session (`--output` and `--gif` change where they go).
 
 use macroquad::prelude::*;
+use rust_mario::frame_limiter::{FrameSettings, game_window_conf};
+use rust_mario::screenshot::{CaptureConfig, GameRecorder};
 use rust_mario::simple_level::SimpleLevel;
 
 /// Window configuration for screenshot generation
 fn window_conf() -> Conf {
-    Conf {
-        window_title: "Rust Mario - Screenshot Generator".to_owned(),
-        window_width: 800,
-        window_height: 600,
-        ..Default::default()
-    }
+    game_window_conf("Rust Mario - Screenshot Generator", &FrameSettings::default())
 }
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    println!("Generating screenshot...");
-    
-    // Create a new game level
-    let game = SimpleLevel::new();
-    
-    // Render a few frames to ensure everything is initialized properly
-    for _ in 0..5 {
-        clear_background(SKYBLUE);
-        game.draw();
-        next_frame().await;
+    let config = match CaptureConfig::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: generate_screenshot [--duration <secs>] [--output <png>] [--gif <gif>] [--region <x,y,w,h>]");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = std::fs::create_dir_all("assets") {
+        eprintln!("Failed to create assets directory: {}", e);
+    }
+
+    let mut game = SimpleLevel::new();
+
+    match config.duration {
+        None => {
+            println!("Generating screenshot...");
+
+            // Render a few frames to ensure everything is initialized properly
+            for _ in 0..5 {
+                clear_background(SKYBLUE);
+                game.draw();
+                next_frame().await;
+            }
+        }
+        Some(duration) => {
+            println!("Recording {} seconds of play - go!", duration);
+            let mut recorder = GameRecorder::new(config.frame_interval_ms);
+            // Closing the window ends the recording early instead of losing it
+            prevent_quit();
+            let capture_interval = config.frame_interval_ms as f64 / 1000.0;
+
+            let start_time = get_time();
+            let mut last_capture_time = start_time - capture_interval;
+            while get_time() - start_time < duration as f64 && !game.should_quit() && !is_quit_requested() {
+                game.update(get_frame_time());
+                game.draw();
+
+                let current_time = get_time();
+                if current_time - last_capture_time >= capture_interval {
+                    if let Err(e) = recorder.capture_frame_at(current_time) {
+                        eprintln!("Failed to capture frame: {}", e);
+                    }
+                    last_capture_time = current_time;
+                }
+
+                next_frame().await;
+            }
+
+            // Draw the final frame again so the screenshot below has it
+            game.draw();
+
+            println!("Recording complete! Captured {} frames", recorder.frame_count());
+            match recorder.save_on_shutdown(&config.gif_path) {
+                Ok(0) => println!("Nothing was recorded"),
+                Ok(_) => println!("Recording saved to {}", config.gif_path),
+                Err(e) => eprintln!("Failed to save recording: {}", e),
+            }
+        }
     }
-    
+
     // Take the screenshot
-    let screenshot_path = "assets/screenshot.png";
-    match game.take_screenshot(screenshot_path) {
+    match game.take_screenshot(&config.screenshot_path, config.region) {
         Ok(()) => {
-            println!("Screenshot successfully saved to {}", screenshot_path);
+            println!("Screenshot successfully saved to {}", config.screenshot_path);
         },
         Err(e) => {
             eprintln!("Failed to take screenshot: {}", e);
             std::process::exit(1);
         }
     }
-    
+
     // Wait a moment to ensure file is written
     for _ in 0..10 {
         next_frame().await;
     }
-    
+
     println!("Screenshot generation complete!");
-}
\ No newline at end of file
+}
@@ -4,39 +4,14 @@
 //! showing improved graphics, animations, enemies, and environmental elements.
 
 use macroquad::prelude::*;
+use rust_mario::frame_limiter::{FrameSettings, game_window_conf};
 use rust_mario::simple_level::SimpleLevel;
 use rust_mario::screenshot::GameRecorder;
+use rust_mario::simulation::{is_recording_frame, parse_script, RECORDING_SCRIPT, RECORDING_STEPS, SIMULATION_DT};
 
 /// Window configuration for recording
 fn window_conf() -> Conf {
-    Conf {
-        window_title: "Rust Mario - 10 Second Recording".to_owned(),
-        window_width: 800,
-        window_height: 600,
-        ..Default::default()
-    }
-}
-
-/// Simulated input for the recording - creates a scripted gameplay sequence
-fn get_simulated_input(time: f32) -> (bool, bool, bool) {
-    match time {
-        // First 2 seconds: move right
-        t if t < 2.0 => (false, true, false),
-        // 2-3 seconds: jump while moving right
-        t if t >= 2.0 && t < 3.0 => (false, true, true),
-        // 3-4 seconds: continue right
-        t if t >= 3.0 && t < 4.0 => (false, true, false),
-        // 4-5 seconds: move left
-        t if t >= 4.0 && t < 5.0 => (true, false, false),
-        // 5-6 seconds: jump left
-        t if t >= 5.0 && t < 6.0 => (true, false, true),
-        // 6-7 seconds: move right again
-        t if t >= 6.0 && t < 7.0 => (false, true, false),
-        // 7-8 seconds: big jump
-        t if t >= 7.0 && t < 8.0 => (false, true, true),
-        // 8-10 seconds: final approach to goal
-        _ => (false, true, false),
-    }
+    game_window_conf("Rust Mario - 10 Second Recording", &FrameSettings::default())
 }
 
 #[macroquad::main(window_conf)]
@@ -46,55 +21,29 @@ async fn main() {
     // Create the game
     let mut game = SimpleLevel::new();
     
-    // Create recorder (capture every 100ms = 10 FPS for reasonable file size)
-    let mut recorder = GameRecorder::new(100);
-    
-    let start_time = get_time();
-    let mut last_capture_time = start_time;
-    let recording_duration = 10.0; // 10 seconds
-    let capture_interval = 0.1; // Capture every 100ms
+    // Create recorder (capture every 100ms = 10 FPS at half resolution for reasonable file size)
+    let mut recorder = GameRecorder::new(100).with_scale(0.5);
     
-    println!("Recording gameplay for {} seconds...", recording_duration);
-    
-    while get_time() - start_time < recording_duration {
-        let current_time = get_time();
-        let elapsed = current_time - start_time;
-        
-        // Simulate input based on elapsed time
-        let (should_move_left, should_move_right, should_jump) = get_simulated_input(elapsed as f32);
-        
-        // We can't directly inject input into macroquad, so we'll modify the player directly
-        // This is a test-specific approach
-        if should_move_left {
-            game.player.velocity_x = -200.0;
-            game.player.facing_right = false;
-        } else if should_move_right {
-            game.player.velocity_x = 200.0;
-            game.player.facing_right = true;
-        } else {
-            game.player.velocity_x = 0.0;
-        }
-        
-        if should_jump && game.player.on_ground {
-            game.player.velocity_y = -300.0;
-            game.player.on_ground = false;
+    // The same scripted inputs and fixed time step as the recording regression test
+    let inputs = parse_script(RECORDING_SCRIPT).expect("the recording script is valid");
+    println!("Recording {} steps of gameplay...", RECORDING_STEPS);
+
+    // Quitting or closing the window stops early, keeping what's been recorded
+    prevent_quit();
+    for (step, input) in inputs.iter().take(RECORDING_STEPS).enumerate() {
+        if game.should_quit() || is_quit_requested() {
+            println!("Stopping the recording early");
+            break;
         }
-        
-        // Update game state
-        game.update(get_frame_time());
-        
-        // Draw the game
+        game.step(input, SIMULATION_DT);
         game.draw();
-        
-        // Capture frame at regular intervals
-        if current_time - last_capture_time >= capture_interval {
-            match recorder.capture_frame() {
-                Ok(()) => {},
-                Err(e) => eprintln!("Failed to capture frame: {}", e),
-            }
-            last_capture_time = current_time;
+
+        if is_recording_frame(step)
+            && let Err(e) = recorder.capture_frame_at(step as f64 * SIMULATION_DT as f64)
+        {
+            eprintln!("Failed to capture frame: {}", e);
         }
-        
+
         next_frame().await;
     }
     
@@ -108,8 +57,9 @@ async fn main() {
     
     // Save the recording as a GIF
     let gif_path = "assets/10_second_recording.gif";
-    match recorder.save_gif(gif_path) {
-        Ok(()) => {
+    match recorder.save_on_shutdown(gif_path) {
+        Ok(0) => println!("Nothing was recorded"),
+        Ok(_) => {
             println!("Recording saved successfully to {}", gif_path);
             println!("You can now view the 10-second gameplay recording!");
         },
@@ -0,0 +1,56 @@
+//! Headless simulation binary
+//!
+//! Runs the level without a window from an input script and prints the final
+//! state as JSON.
+//!
+//! Usage: `simulate [--steps N] [--level LEVEL.json] [SCRIPT]`
+//!
+//! The script is read from stdin when no file (or `-`) is given, and the
+//! number of steps defaults to the length of the script.
+
+use std::io::Read;
+use std::process;
+
+use rust_mario::simple_level::SimpleLevel;
+use rust_mario::simulation::{parse_script, simulate};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("simulate: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut steps = None;
+    let mut level_path = None;
+    let mut script_path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--steps" => steps = Some(args.next().ok_or("--steps needs a value")?.parse::<usize>()?),
+            "--level" => level_path = Some(args.next().ok_or("--level needs a path")?),
+            _ => script_path = Some(arg),
+        }
+    }
+
+    let script = match script_path.as_deref() {
+        None | Some("-") => {
+            let mut script = String::new();
+            std::io::stdin().read_to_string(&mut script)?;
+            script
+        }
+        Some(path) => std::fs::read_to_string(path)?,
+    };
+    let inputs = parse_script(&script)?;
+
+    let mut level = match level_path {
+        Some(path) => SimpleLevel::load(path)?,
+        None => SimpleLevel::new(),
+    };
+
+    let result = simulate(&mut level, &inputs, steps.unwrap_or(inputs.len()));
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
@@ -0,0 +1,280 @@
+//! Blocks
+//!
+//! Solid blocks placed in the level that react to being hit from below.
+//! A brick is bumped upwards by small Mario and shattered by big Mario.
+//! A question block gives out a coin and then becomes used, while a
+//! multi-coin block keeps paying out for every hit within a short window
+//! (up to a cap) before it is used up. Springs launch the player upwards, and
+//! chaining spring bounces back to back launches them a little higher each time.
+//! Hitting a switch block toggles every outline block in its group between
+//! passable and solid.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::simple_level::Platform;
+
+/// Width and height of a block
+pub const BLOCK_SIZE: f32 = 20.0;
+/// How long the bump animation lasts, in seconds
+const BUMP_DURATION: f32 = 0.15;
+/// How far a bumped block rises at the peak of its animation
+const BUMP_HEIGHT: f32 = 5.0;
+/// Most coins a multi-coin block can give out
+pub const MULTI_COIN_CAP: u32 = 10;
+/// Seconds after the first hit during which a multi-coin block keeps paying out
+pub const MULTI_COIN_WINDOW: f32 = 4.0;
+/// Launch speed of an isolated spring bounce
+pub const SPRING_LAUNCH: f32 = 450.0;
+/// Extra launch speed per chained bounce, as a fraction of the base launch
+const SPRING_CHAIN_BONUS: f32 = 0.15;
+/// Fastest launch a bounce chain can build up to
+pub const SPRING_MAX_LAUNCH: f32 = 650.0;
+/// Seconds after a bounce in which landing on a spring continues the chain
+const SPRING_CHAIN_WINDOW: f32 = 2.5;
+
+/// The different kinds of block
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BlockKind {
+    Brick,
+    /// Gives a single coin
+    Question,
+    /// Gives a coin per hit until the cap or time window runs out
+    MultiCoin,
+    /// An emptied block that no longer gives anything
+    Used,
+    /// Launches the player upwards when landed on
+    Spring,
+    /// Toggles the outline blocks of its group when hit from below
+    Switch { group: u32 },
+    /// Passable until its group's switch is toggled on
+    Outline { group: u32 },
+}
+
+/// A solid block in the level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    pub x: f32,
+    pub y: f32,
+    pub kind: BlockKind,
+    #[serde(skip)]
+    pub broken: bool,
+    #[serde(skip)]
+    pub bump_timer: f32,
+    /// Coins given out so far
+    #[serde(skip)]
+    pub coins_dispensed: u32,
+    /// Time left to keep hitting a multi-coin block, once started
+    #[serde(skip)]
+    pub coin_timer: Option<f32>,
+}
+
+impl Block {
+    /// Create a new block
+    pub fn new(x: f32, y: f32, kind: BlockKind) -> Self {
+        Self { x, y, kind, broken: false, bump_timer: 0.0, coins_dispensed: 0, coin_timer: None }
+    }
+
+    /// Create a new brick
+    pub fn brick(x: f32, y: f32) -> Self {
+        Self::new(x, y, BlockKind::Brick)
+    }
+
+    /// Whether the block still blocks movement. Outline blocks only do so
+    /// while their switch group is active.
+    pub fn is_solid(&self, group_active: bool) -> bool {
+        match self.kind {
+            BlockKind::Outline { .. } => !self.broken && group_active,
+            _ => !self.broken,
+        }
+    }
+
+    /// The switch group the block belongs to, if any
+    pub fn switch_group(&self) -> Option<u32> {
+        match self.kind {
+            BlockKind::Switch { group } | BlockKind::Outline { group } => Some(group),
+            _ => None,
+        }
+    }
+
+    /// Collision shape of the block
+    pub fn as_platform(&self) -> Platform {
+        Platform::new(self.x, self.y, BLOCK_SIZE, BLOCK_SIZE)
+    }
+
+    /// Start the small upward nudge animation
+    pub fn bump(&mut self) {
+        self.bump_timer = BUMP_DURATION;
+    }
+
+    /// Hit a coin-giving block from below, returning the number of coins it gives out
+    pub fn dispense_coin(&mut self) -> u32 {
+        match self.kind {
+            BlockKind::Question => {
+                self.bump();
+                self.coins_dispensed += 1;
+                self.kind = BlockKind::Used;
+                1
+            }
+            BlockKind::MultiCoin => {
+                self.bump();
+                // The first hit starts the clock; once it has run out the next
+                // hit still pays one last coin
+                let timer = *self.coin_timer.get_or_insert(MULTI_COIN_WINDOW);
+                self.coins_dispensed += 1;
+                if self.coins_dispensed >= MULTI_COIN_CAP || timer <= 0.0 {
+                    self.kind = BlockKind::Used;
+                }
+                1
+            }
+            BlockKind::Brick | BlockKind::Used | BlockKind::Spring | BlockKind::Switch { .. } | BlockKind::Outline { .. } => 0,
+        }
+    }
+
+    /// Shatter the block so it no longer exists in the level
+    pub fn shatter(&mut self) {
+        self.broken = true;
+        self.bump_timer = 0.0;
+    }
+
+    /// Advance the bump animation
+    pub fn update(&mut self, delta_time: f32) {
+        self.bump_timer = (self.bump_timer - delta_time).max(0.0);
+        if let Some(timer) = &mut self.coin_timer {
+            *timer = (*timer - delta_time).max(0.0);
+        }
+    }
+
+    /// Current upward offset from the bump animation
+    pub fn bump_offset(&self) -> f32 {
+        if self.bump_timer <= 0.0 {
+            return 0.0;
+        }
+        let progress = 1.0 - self.bump_timer / BUMP_DURATION;
+        (progress * std::f32::consts::PI).sin() * BUMP_HEIGHT
+    }
+
+    /// Draw the block; `group_active` is whether its switch group is toggled on
+    pub fn draw(&self, group_active: bool) {
+        if self.broken {
+            return;
+        }
+
+        let x = self.x;
+        let y = self.y - self.bump_offset();
+        let s = BLOCK_SIZE;
+
+        match self.kind {
+            BlockKind::Brick => {
+                let brick_color = Color::new(0.7, 0.3, 0.1, 1.0);
+                let mortar = Color::new(0.3, 0.1, 0.0, 1.0);
+                draw_rectangle(x, y, s, s, brick_color);
+                // Mortar lines in a staggered brick pattern
+                draw_line(x, y + s / 2.0, x + s, y + s / 2.0, 1.0, mortar);
+                draw_line(x + s / 2.0, y, x + s / 2.0, y + s / 2.0, 1.0, mortar);
+                draw_line(x + s / 4.0, y + s / 2.0, x + s / 4.0, y + s, 1.0, mortar);
+                draw_line(x + s * 0.75, y + s / 2.0, x + s * 0.75, y + s, 1.0, mortar);
+                draw_rectangle_lines(x, y, s, s, 1.0, mortar);
+            }
+            BlockKind::Question | BlockKind::MultiCoin => {
+                draw_rectangle(x, y, s, s, GOLD);
+                draw_rectangle_lines(x, y, s, s, 1.0, ORANGE);
+                draw_text("?", x + s * 0.3, y + s * 0.8, 18.0, Color::new(0.5, 0.25, 0.0, 1.0));
+            }
+            BlockKind::Spring => {
+                // Coils under a flat pad
+                let coil = Color::new(0.6, 0.6, 0.65, 1.0);
+                for i in 0..3 {
+                    let cy = y + s * 0.35 + i as f32 * s * 0.2;
+                    draw_line(x + s * 0.2, cy, x + s * 0.8, cy + s * 0.1, 2.0, coil);
+                }
+                draw_rectangle(x, y, s, s * 0.3, RED);
+                draw_rectangle(x + s * 0.1, y + s * 0.9, s * 0.8, s * 0.1, DARKGRAY);
+            }
+            BlockKind::Switch { .. } => {
+                let color = if group_active { SKYBLUE } else { BLUE };
+                draw_rectangle(x, y, s, s, color);
+                draw_rectangle_lines(x, y, s, s, 1.0, DARKBLUE);
+                draw_text("!", x + s * 0.4, y + s * 0.8, 18.0, WHITE);
+            }
+            BlockKind::Outline { .. } => {
+                let color = Color::new(0.2, 0.4, 0.9, 1.0);
+                if group_active {
+                    draw_rectangle(x, y, s, s, color);
+                    draw_rectangle_lines(x, y, s, s, 1.0, DARKBLUE);
+                } else {
+                    // Dashed outline showing where the block will appear
+                    for i in 0..4 {
+                        let offset = i as f32 * s / 4.0;
+                        draw_line(x + offset, y, x + offset + s / 8.0, y, 1.0, color);
+                        draw_line(x + offset, y + s, x + offset + s / 8.0, y + s, 1.0, color);
+                        draw_line(x, y + offset, x, y + offset + s / 8.0, 1.0, color);
+                        draw_line(x + s, y + offset, x + s, y + offset + s / 8.0, 1.0, color);
+                    }
+                }
+            }
+            BlockKind::Used => {
+                let used_color = Color::new(0.45, 0.3, 0.15, 1.0);
+                draw_rectangle(x, y, s, s, used_color);
+                draw_rectangle_lines(x, y, s, s, 1.0, DARKBROWN);
+            }
+        }
+    }
+}
+
+/// Tracks consecutive spring bounces so each one in a chain launches higher
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BounceChain {
+    /// Bounces in the current chain
+    pub count: u32,
+    /// Time left to land on another spring and continue the chain
+    pub timer: f32,
+}
+
+impl BounceChain {
+    /// Register a spring bounce and return the speed to launch the player at
+    pub fn bounce(&mut self) -> f32 {
+        self.count = if self.timer > 0.0 { self.count + 1 } else { 1 };
+        self.timer = SPRING_CHAIN_WINDOW;
+        let bonus = 1.0 + SPRING_CHAIN_BONUS * (self.count - 1) as f32;
+        (SPRING_LAUNCH * bonus).min(SPRING_MAX_LAUNCH)
+    }
+
+    /// Run down the chain window
+    pub fn update(&mut self, delta_time: f32) {
+        self.timer = (self.timer - delta_time).max(0.0);
+    }
+
+    /// End the chain, e.g. after landing on solid ground
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_coin_block_pays_out_until_cap() {
+        let mut block = Block::new(0.0, 0.0, BlockKind::MultiCoin);
+
+        let mut coins = 0;
+        for _ in 0..MULTI_COIN_CAP {
+            assert_eq!(block.kind, BlockKind::MultiCoin);
+            coins += block.dispense_coin();
+            block.update(0.2);
+        }
+        assert_eq!(coins, MULTI_COIN_CAP);
+        assert_eq!(block.kind, BlockKind::Used);
+        assert_eq!(block.dispense_coin(), 0);
+
+        // Once the window has run out the next hit is the last one
+        let mut block = Block::new(0.0, 0.0, BlockKind::MultiCoin);
+        assert_eq!(block.dispense_coin(), 1);
+        block.update(MULTI_COIN_WINDOW + 1.0);
+        assert_eq!(block.dispense_coin(), 1);
+        assert_eq!(block.kind, BlockKind::Used);
+        assert_eq!(block.coins_dispensed, 2);
+    }
+}
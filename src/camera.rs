@@ -0,0 +1,345 @@
+//! Camera
+//!
+//! The camera eases towards whatever its `CameraTarget` currently resolves
+//! to. Following the player is the normal mode, but the target can be
+//! switched to a fixed point, the midpoint between several players, or a
+//! timed path for cutscenes and boss intros. Paths can also zoom in, with
+//! the camera easing its zoom back to normal for every other target.
+//!
+//! When following the player, optional `ScrollMargins` replace centring with
+//! a classic dead zone: the view only scrolls once the player crosses a
+//! margin, and the wider side of the dead zone is behind them so more of the
+//! level ahead stays in view.
+//!
+//! The camera normally stays right of the level's start. `CameraBounds` can
+//! replace that with a fixed region the view never leaves, such as a boss
+//! arena, even when the player moves outside it.
+//!
+//! Positions in the level (`WorldPos`) and on the screen (`ScreenPos`) are
+//! separate types, and a `ScreenTransform` built from the camera is the only
+//! way to get from one to the other, so drawing code can't mix them up.
+//!
+//! The level only scrolls horizontally for now, so drawing uses `x`; `y` is
+//! tracked so that targets with a vertical component behave consistently
+//! once vertical scrolling is added.
+
+use serde::{Deserialize, Serialize};
+
+use crate::tween::lerp;
+
+/// Fraction of the remaining distance the camera covers each update
+const CAMERA_SMOOTHING: f32 = 0.1;
+
+/// A position in the level
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WorldPos {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl WorldPos {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A position on the virtual screen, from its top-left corner
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScreenPos {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl ScreenPos {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Converts between world and screen positions for a view whose top-left
+/// corner is at `origin` in the level
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScreenTransform {
+    pub origin: WorldPos,
+}
+
+impl ScreenTransform {
+    pub fn new(origin: WorldPos) -> Self {
+        Self { origin }
+    }
+
+    /// Where a point in the level appears on screen
+    pub fn to_screen(&self, pos: WorldPos) -> ScreenPos {
+        ScreenPos::new(pos.x - self.origin.x, pos.y - self.origin.y)
+    }
+
+    /// Which point in the level is shown at a screen position
+    pub fn to_world(&self, pos: ScreenPos) -> WorldPos {
+        WorldPos::new(pos.x + self.origin.x, pos.y + self.origin.y)
+    }
+}
+
+/// How far across the view, as fractions of its width, the player can go
+/// before it scrolls. Given for a player facing right; mirrored when they face left.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScrollMargins {
+    /// Scroll back once the player is this close to the left edge
+    pub behind: f32,
+    /// Scroll forwards once the player gets this far across
+    pub ahead: f32,
+}
+
+impl Default for ScrollMargins {
+    fn default() -> Self {
+        Self { behind: 0.3, ahead: 0.6 }
+    }
+}
+
+impl ScrollMargins {
+    /// Where along the view, in pixels, the dead zone starts and ends
+    pub fn dead_zone(&self, view_width: f32, facing_right: bool) -> (f32, f32) {
+        if facing_right {
+            (self.behind * view_width, self.ahead * view_width)
+        } else {
+            ((1.0 - self.ahead) * view_width, (1.0 - self.behind) * view_width)
+        }
+    }
+}
+
+/// Region of the level the view has to stay inside
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraBounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl CameraBounds {
+    /// Move a view's top-left corner so the view lies inside the bounds. A view
+    /// bigger than the bounds lines up with their top-left corner.
+    pub fn clamp(&self, x: f32, y: f32, view_width: f32, view_height: f32) -> (f32, f32) {
+        let clamp_axis = |v: f32, start: f32, size: f32, view: f32| v.min(start + size - view).max(start);
+        (clamp_axis(x, self.x, self.width, view_width), clamp_axis(y, self.y, self.height, view_height))
+    }
+}
+
+/// A point on a camera path, reached `time` seconds after the path starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraKeyframe {
+    pub time: f32,
+    pub x: f32,
+    pub y: f32,
+    /// Magnification at this point; 1.0 shows the normal view
+    pub zoom: f32,
+}
+
+impl CameraKeyframe {
+    /// A keyframe at normal zoom
+    pub fn new(time: f32, x: f32, y: f32) -> Self {
+        Self { time, x, y, zoom: 1.0 }
+    }
+
+    /// Set the magnification at this keyframe
+    pub fn zoomed(self, zoom: f32) -> Self {
+        Self { zoom, ..self }
+    }
+}
+
+/// A timeline of points for the camera to focus on in turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+    elapsed: f32,
+}
+
+impl CameraPath {
+    /// Create a path from keyframes, which are sorted by time
+    pub fn new(mut keyframes: Vec<CameraKeyframe>) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keyframes, elapsed: 0.0 }
+    }
+
+    /// Move along the path
+    pub fn advance(&mut self, delta_time: f32) {
+        self.elapsed += delta_time;
+    }
+
+    /// Whether the last keyframe has been reached
+    pub fn is_finished(&self) -> bool {
+        self.keyframes.last().is_none_or(|k| self.elapsed >= k.time)
+    }
+
+    /// Where the path is at the current time, interpolating between keyframes
+    fn sample(&self) -> Option<CameraKeyframe> {
+        let next = self.keyframes.iter().position(|k| k.time > self.elapsed);
+        match next {
+            None => self.keyframes.last().copied(),
+            Some(0) => self.keyframes.first().copied(),
+            Some(i) => {
+                let (a, b) = (self.keyframes[i - 1], self.keyframes[i]);
+                let t = (self.elapsed - a.time) / (b.time - a.time);
+                Some(CameraKeyframe { time: self.elapsed, x: lerp(a.x, b.x, t), y: lerp(a.y, b.y, t), zoom: lerp(a.zoom, b.zoom, t) })
+            }
+        }
+    }
+
+    /// Point on the path at the current time
+    pub fn point(&self) -> Option<(f32, f32)> {
+        self.sample().map(|k| (k.x, k.y))
+    }
+
+    /// Zoom on the path at the current time
+    pub fn zoom(&self) -> f32 {
+        self.sample().map_or(1.0, |k| k.zoom)
+    }
+}
+
+/// What the camera is trying to keep in the centre of the view
+#[derive(Debug, Clone, PartialEq)]
+pub enum CameraTarget {
+    /// Follow the first player
+    FollowPlayer,
+    /// Hold still on a point in the level
+    FixedPoint(f32, f32),
+    /// Keep the midpoint of the players with these indices in view
+    FollowMidpoint(Vec<usize>),
+    /// Move along a timed path
+    Path(CameraPath),
+}
+
+/// Camera position and the target it eases towards
+#[derive(Debug, Clone)]
+pub struct Camera {
+    /// Left edge of the view in level coordinates
+    pub x: f32,
+    /// Top edge of the view in level coordinates
+    pub y: f32,
+    /// Magnification; the view covers `1 / zoom` of the screen's area in each direction
+    pub zoom: f32,
+    pub target: CameraTarget,
+    /// Dead zone for following the player; None keeps them centred
+    pub margins: Option<ScrollMargins>,
+    /// Which way the followed player faces, to orient the margins
+    pub facing_right: bool,
+    /// Region the view stays inside; None only keeps it right of the level's start
+    pub bounds: Option<CameraBounds>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self { x: 0.0, y: 0.0, zoom: 1.0, target: CameraTarget::FollowPlayer, margins: None, facing_right: true, bounds: None }
+    }
+}
+
+impl Camera {
+    /// Transform for drawing what the camera currently sees. The level only
+    /// scrolls horizontally, so the view's top stays at the top of the level.
+    pub fn transform(&self) -> ScreenTransform {
+        ScreenTransform::new(WorldPos::new(self.x, 0.0))
+    }
+
+    /// Change what the camera focuses on
+    pub fn set_target(&mut self, target: CameraTarget) {
+        self.target = target;
+    }
+
+    /// Work out the point the target wants centred, given the centre of each player
+    pub fn focus_point(&self, players: &[(f32, f32)]) -> Option<(f32, f32)> {
+        match &self.target {
+            CameraTarget::FollowPlayer => players.first().copied(),
+            CameraTarget::FixedPoint(x, y) => Some((*x, *y)),
+            CameraTarget::FollowMidpoint(indices) => {
+                let points: Vec<(f32, f32)> = indices.iter().filter_map(|i| players.get(*i).copied()).collect();
+                if points.is_empty() {
+                    return None;
+                }
+                let n = points.len() as f32;
+                let (sum_x, sum_y) = points.iter().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+                Some((sum_x / n, sum_y / n))
+            }
+            CameraTarget::Path(path) => path.point(),
+        }
+    }
+
+    /// Ease the camera towards its target. The view never scrolls left of the
+    /// level's start, or outside the camera bounds if there are any.
+    pub fn update(&mut self, players: &[(f32, f32)], view_width: f32, view_height: f32, delta_time: f32) {
+        let target_zoom = match &mut self.target {
+            CameraTarget::Path(path) => {
+                path.advance(delta_time);
+                path.zoom()
+            }
+            _ => 1.0,
+        };
+        self.zoom += (target_zoom - self.zoom) * CAMERA_SMOOTHING;
+
+        let Some((focus_x, focus_y)) = self.focus_point(players) else {
+            return;
+        };
+        let target_y = focus_y - view_height / self.zoom / 2.0;
+        self.y += (target_y - self.y) * CAMERA_SMOOTHING;
+        match self.margins {
+            // Ease towards the nearest view that has the player inside the dead zone.
+            // Margins come straight from level files, so an inverted pair must not panic
+            Some(margins) if self.target == CameraTarget::FollowPlayer => {
+                let (start, end) = margins.dead_zone(view_width, self.facing_right);
+                let target_x = self.x.min(focus_x - start).max(focus_x - end);
+                self.x += (target_x - self.x) * CAMERA_SMOOTHING;
+            }
+            _ => {
+                let target_x = focus_x - view_width / self.zoom / 2.0;
+                self.x += (target_x - self.x) * CAMERA_SMOOTHING;
+            }
+        }
+
+        // Keep camera within bounds
+        match self.bounds {
+            Some(bounds) => (self.x, self.y) = bounds.clamp(self.x, self.y, view_width / self.zoom, view_height / self.zoom),
+            None => self.x = self.x.max(0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_path_interpolates_between_keyframes() {
+        let mut camera = Camera::default();
+        camera.set_target(CameraTarget::Path(CameraPath::new(vec![
+            CameraKeyframe::new(0.0, 400.0, 300.0),
+            CameraKeyframe::new(2.0, 800.0, 300.0),
+        ])));
+
+        camera.update(&[], 800.0, 600.0, 1.0);
+        assert_eq!(camera.focus_point(&[]), Some((600.0, 300.0)));
+
+        // Midpoint of two players, ignoring indices that don't exist
+        camera.set_target(CameraTarget::FollowMidpoint(vec![0, 1, 5]));
+        assert_eq!(camera.focus_point(&[(100.0, 200.0), (300.0, 400.0)]), Some((200.0, 300.0)));
+    }
+
+    #[test]
+    fn test_inverted_scroll_margins_still_follow_the_player() {
+        let margins = ScrollMargins { behind: 0.7, ahead: 0.2 };
+        let mut camera = Camera { margins: Some(margins), ..Default::default() };
+        for _ in 0..200 {
+            camera.update(&[(1000.0, 300.0)], 800.0, 600.0, 1.0 / 60.0);
+        }
+        assert!(camera.x.is_finite());
+        assert!((camera.x - (1000.0 - 0.2 * 800.0)).abs() < 1.0, "camera at {}", camera.x);
+    }
+
+    #[test]
+    fn test_world_and_screen_positions_round_trip() {
+        let camera = Camera { x: 350.0, y: 120.0, ..Default::default() };
+        let view = camera.transform();
+        let world = WorldPos::new(400.0, 250.0);
+
+        assert_eq!(view.to_screen(world), ScreenPos::new(50.0, 250.0));
+        assert_eq!(view.to_world(view.to_screen(world)), world);
+        assert_eq!(view.to_screen(view.to_world(ScreenPos::new(10.0, 20.0))), ScreenPos::new(10.0, 20.0));
+    }
+}
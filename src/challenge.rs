@@ -0,0 +1,112 @@
+//! Coin Challenges
+//!
+//! A challenge is a pad in the level that starts a countdown when the player
+//! steps on it. Collecting enough coins before time runs out earns a reward;
+//! running out of time resets the challenge so it can be tried again.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{ScreenPos, ScreenTransform, WorldPos};
+
+/// Width of a challenge trigger pad
+const PAD_WIDTH: f32 = 30.0;
+/// Height of a challenge trigger pad
+const PAD_HEIGHT: f32 = 6.0;
+
+/// What completing a challenge gives the player
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ChallengeReward {
+    #[default]
+    ExtraLife,
+    /// A lump of bonus points
+    Points(u32),
+}
+
+/// Where a challenge is up to
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ChallengeState {
+    /// Waiting for the player to step on the pad
+    #[default]
+    Ready,
+    /// Counting down; `collected` coins picked up so far
+    Active { time_left: f32, collected: u32 },
+    /// Won; the pad no longer does anything
+    Completed,
+}
+
+/// A "collect N coins within the time limit" bonus challenge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoinChallenge {
+    /// Top left of the trigger pad
+    pub x: f32,
+    pub y: f32,
+    /// Coins needed to win
+    pub target: u32,
+    /// Seconds allowed once the challenge starts
+    pub time_limit: f32,
+    #[serde(default)]
+    pub reward: ChallengeReward,
+    #[serde(skip)]
+    pub state: ChallengeState,
+}
+
+impl CoinChallenge {
+    /// Create a challenge with its trigger pad at the given position
+    pub fn new(x: f32, y: f32, target: u32, time_limit: f32, reward: ChallengeReward) -> Self {
+        Self { x, y, target, time_limit, reward, state: ChallengeState::Ready }
+    }
+
+    /// Check if the trigger pad overlaps with a rectangle
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        x < self.x + PAD_WIDTH &&
+        x + width > self.x &&
+        y < self.y + PAD_HEIGHT &&
+        y + height > self.y
+    }
+
+    /// Start the countdown if the challenge is ready. Returns whether it started.
+    pub fn start(&mut self) -> bool {
+        if self.state != ChallengeState::Ready {
+            return false;
+        }
+        self.state = ChallengeState::Active { time_left: self.time_limit, collected: 0 };
+        true
+    }
+
+    /// Count a coin towards the challenge. Returns true when this coin completes it.
+    pub fn coin_collected(&mut self) -> bool {
+        if let ChallengeState::Active { collected, .. } = &mut self.state {
+            *collected += 1;
+            if *collected >= self.target {
+                self.state = ChallengeState::Completed;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Run down the timer. Returns true when time ran out and the challenge was reset.
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        if let ChallengeState::Active { time_left, .. } = &mut self.state {
+            *time_left -= delta_time;
+            if *time_left <= 0.0 {
+                self.state = ChallengeState::Ready;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Draw the trigger pad where the view shows it
+    pub fn draw(&self, view: &ScreenTransform) {
+        let color = match self.state {
+            ChallengeState::Ready => SKYBLUE,
+            ChallengeState::Active { .. } => YELLOW,
+            ChallengeState::Completed => GRAY,
+        };
+        let ScreenPos { x, y } = view.to_screen(WorldPos::new(self.x, self.y));
+        draw_rectangle(x, y, PAD_WIDTH, PAD_HEIGHT, color);
+        draw_rectangle_lines(x, y, PAD_WIDTH, PAD_HEIGHT, 1.0, DARKBLUE);
+    }
+}
@@ -0,0 +1,107 @@
+//! Collision Layers
+//!
+//! Every entity that can touch another sits on one or more collision layers
+//! and carries a mask of the layers it reacts to. Two entities only collide
+//! when each one's mask includes a layer the other is on, so whole kinds of
+//! pairing can be switched off without another special case in the level:
+//! the player's fireballs pass through the player, and enemies' hammers pass
+//! through each other.
+
+use std::ops::BitOr;
+
+/// A set of collision layers, as bit flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollisionLayers(u32);
+
+impl CollisionLayers {
+    pub const NONE: Self = Self(0);
+    pub const PLAYER: Self = Self(1);
+    pub const ENEMY: Self = Self(1 << 1);
+    /// Fireballs and thrown items: anything the player attacks with
+    pub const PLAYER_ATTACK: Self = Self(1 << 2);
+    /// Hammers and anything else enemies throw
+    pub const ENEMY_ATTACK: Self = Self(1 << 3);
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Whether the two sets share any layer
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Both sets together
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOr for CollisionLayers {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+/// The layers an entity is on and the layers it collides with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collider {
+    pub layer: CollisionLayers,
+    pub mask: CollisionLayers,
+}
+
+impl Collider {
+    pub const PLAYER: Self = Self::new(
+        CollisionLayers::PLAYER,
+        CollisionLayers::ENEMY.union(CollisionLayers::ENEMY_ATTACK),
+    );
+    pub const ENEMY: Self = Self::new(
+        CollisionLayers::ENEMY,
+        CollisionLayers::PLAYER.union(CollisionLayers::PLAYER_ATTACK),
+    );
+    /// A friendly fireball: hits enemies and their projectiles, never the player
+    pub const FIREBALL: Self = Self::new(
+        CollisionLayers::PLAYER_ATTACK,
+        CollisionLayers::ENEMY.union(CollisionLayers::ENEMY_ATTACK),
+    );
+    pub const THROWN_ITEM: Self = Self::new(CollisionLayers::PLAYER_ATTACK, CollisionLayers::ENEMY);
+    pub const ENEMY_PROJECTILE: Self = Self::new(
+        CollisionLayers::ENEMY_ATTACK,
+        CollisionLayers::PLAYER.union(CollisionLayers::PLAYER_ATTACK),
+    );
+
+    pub const fn new(layer: CollisionLayers, mask: CollisionLayers) -> Self {
+        Self { layer, mask }
+    }
+
+    /// Whether this and another collider react to each other, wherever they
+    /// are. Entities check this before their own overlap test.
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        self.mask.intersects(other.layer) && other.mask.intersects(self.layer)
+    }
+}
+
+/// Without anything more specific, an entity collides with everything
+impl Default for Collider {
+    fn default() -> Self {
+        Self::new(CollisionLayers::ALL, CollisionLayers::ALL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_entities_on_unmatched_masks_do_not_collide() {
+        assert!(!Collider::FIREBALL.interacts_with(&Collider::PLAYER));
+        assert!(!Collider::PLAYER.interacts_with(&Collider::FIREBALL));
+        assert!(Collider::FIREBALL.interacts_with(&Collider::ENEMY));
+        assert!(Collider::PLAYER.interacts_with(&Collider::ENEMY));
+        assert!(Collider::THROWN_ITEM.interacts_with(&Collider::ENEMY));
+        assert!(!Collider::THROWN_ITEM.interacts_with(&Collider::ENEMY_PROJECTILE));
+
+        // A mask has to be matched from both sides
+        let one_sided = Collider::new(CollisionLayers::ENEMY, CollisionLayers::PLAYER_ATTACK);
+        assert!(!one_sided.interacts_with(&Collider::PLAYER));
+    }
+}
@@ -0,0 +1,184 @@
+//! Developer Console
+//!
+//! A command line toggled with the backtick key for tweaking the game while
+//! it runs. Commands are a verb followed by its arguments:
+//!
+//! - `set gravity 400` (or `jump`, `speed`) changes the player's physics
+//! - `spawn enemy 300 200` drops a goomba into the level
+//! - `toggle hitboxes` switches a debug overlay on or off
+//! - `teleport 120 80` moves the player
+//! - `bind fire q` rebinds an action to a key, unless another action has it
+
+use macroquad::prelude::*;
+
+use crate::input::{self, Action};
+use crate::simple_level::PhysicsConfig;
+
+/// Lines of output kept for display
+const LOG_LINES: usize = 5;
+
+/// A physics value the console can change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Gravity,
+    JumpStrength,
+    RunSpeed,
+}
+
+impl Setting {
+    /// Look up a setting by the name typed into the console
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gravity" => Some(Setting::Gravity),
+            "jump" => Some(Setting::JumpStrength),
+            "speed" => Some(Setting::RunSpeed),
+            _ => None,
+        }
+    }
+
+    /// Change this setting in a physics config
+    pub fn apply(&self, physics: &mut PhysicsConfig, value: f32) {
+        match self {
+            Setting::Gravity => physics.gravity = value,
+            Setting::JumpStrength => physics.jump_strength = value,
+            Setting::RunSpeed => physics.run_speed = value,
+        }
+    }
+}
+
+/// A debug overlay or cheat the console can switch on and off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugFlag {
+    /// Outline every collision box
+    Hitboxes,
+}
+
+/// Debug switches set from the console
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DebugFlags {
+    pub hitboxes: bool,
+}
+
+impl DebugFlags {
+    /// Flip a flag, returning its new state
+    pub fn toggle(&mut self, flag: DebugFlag) -> bool {
+        let value = match flag {
+            DebugFlag::Hitboxes => &mut self.hitboxes,
+        };
+        *value = !*value;
+        *value
+    }
+}
+
+/// A parsed console command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Set { setting: Setting, value: f32 },
+    SpawnEnemy { x: f32, y: f32 },
+    Toggle(DebugFlag),
+    Teleport { x: f32, y: f32 },
+    Bind { action: Action, key: KeyCode },
+}
+
+/// Parse one line typed into the console
+pub fn parse(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let number = |word: &str| word.parse::<f32>().map_err(|_| format!("'{}' isn't a number", word));
+    match words[..] {
+        ["set", name, value] => {
+            let setting = Setting::from_name(name).ok_or(format!("unknown setting '{}'", name))?;
+            Ok(Command::Set { setting, value: number(value)? })
+        }
+        ["spawn", "enemy", x, y] => Ok(Command::SpawnEnemy { x: number(x)?, y: number(y)? }),
+        ["toggle", "hitboxes"] => Ok(Command::Toggle(DebugFlag::Hitboxes)),
+        ["teleport", x, y] => Ok(Command::Teleport { x: number(x)?, y: number(y)? }),
+        ["bind", action, key] => {
+            let action = Action::from_name(action).ok_or(format!("unknown action '{}'", action))?;
+            let key = input::key_from_name(key).ok_or(format!("unknown key '{}'", key))?;
+            Ok(Command::Bind { action, key })
+        }
+        [] => Err("type a command".to_owned()),
+        _ => Err(format!("unknown command '{}'", line.trim())),
+    }
+}
+
+/// The console's input line and recent output
+#[derive(Debug, Clone, Default)]
+pub struct Console {
+    open: bool,
+    line: String,
+    log: Vec<String>,
+}
+
+impl Console {
+    /// Whether the console is showing and taking keyboard input
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Show or hide the console
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.line.clear();
+    }
+
+    /// Add a line of output
+    pub fn print(&mut self, text: String) {
+        self.log.push(text);
+        if self.log.len() > LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+
+    /// Read typed characters into the input line, returning the line once
+    /// Enter is pressed
+    pub fn read_keyboard(&mut self) -> Option<String> {
+        while let Some(c) = get_char_pressed() {
+            if c != '`' && !c.is_control() {
+                self.line.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.line.pop();
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            let line = std::mem::take(&mut self.line);
+            self.print(format!("> {}", line));
+            return Some(line);
+        }
+        None
+    }
+
+    /// Draw the console across the top of the screen
+    pub fn draw(&self, width: f32) {
+        let height = 30.0 + 20.0 * self.log.len() as f32;
+        draw_rectangle(0.0, 0.0, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+        for (i, text) in self.log.iter().enumerate() {
+            draw_text(text, 10.0, 20.0 + 20.0 * i as f32, 18.0, LIGHTGRAY);
+        }
+        draw_text(&format!("> {}_", self.line), 10.0, height - 10.0, 18.0, WHITE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_gravity_changes_physics_config() {
+        let command = parse("set gravity 400").unwrap();
+        assert_eq!(command, Command::Set { setting: Setting::Gravity, value: 400.0 });
+
+        let mut physics = PhysicsConfig::default();
+        if let Command::Set { setting, value } = command {
+            setting.apply(&mut physics, value);
+        }
+        assert_eq!(physics, PhysicsConfig { gravity: 400.0, ..Default::default() });
+
+        assert_eq!(parse("teleport 10 20"), Ok(Command::Teleport { x: 10.0, y: 20.0 }));
+        assert!(parse("set gravity lots").is_err());
+        assert!(parse("set wind 3").is_err());
+        assert_eq!(parse("bind fire q"), Ok(Command::Bind { action: Action::Fire, key: KeyCode::Q }));
+        assert!(parse("bind fire meta").is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! Draw Order Checks
+//!
+//! Layers only look right when they're drawn back to front, and a pass in the
+//! wrong place doesn't fail loudly: it just paints scenery over the level. A
+//! `DrawOrder` records the layer of every draw pass as it's made, and in debug
+//! builds panics as soon as background scenery is drawn over something meant
+//! to sit in front of it, so the mistake shows up the first time it runs.
+//!
+//! Only whole passes are recorded: whatever a pass draws is checked as being
+//! on that pass's layer, so scenery has to be its own pass to be checked.
+
+/// How far forward a draw pass sits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawLayer {
+    /// Scenery behind everything else
+    Background,
+    /// The level and the player
+    World,
+    /// Scenery in front of the level
+    Foreground,
+}
+
+/// The layers drawn so far, in order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrawOrder {
+    layers: Vec<DrawLayer>,
+}
+
+impl DrawOrder {
+    /// Record a draw on `layer`. In debug builds, panics if it's background
+    /// drawn after anything in front of it.
+    pub fn record(&mut self, layer: DrawLayer) {
+        self.layers.push(layer);
+        debug_assert!(self.is_valid(), "background drawn after something in front of it: {:?}", self.layers);
+    }
+
+    /// Every layer drawn, in order
+    pub fn layers(&self) -> &[DrawLayer] {
+        &self.layers
+    }
+
+    /// Whether every background draw came before everything else
+    pub fn is_valid(&self) -> bool {
+        let in_front = self.layers.iter().position(|&layer| layer != DrawLayer::Background);
+        in_front.is_none_or(|first| !self.layers[first..].contains(&DrawLayer::Background))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "background drawn after something in front of it")]
+    fn test_background_drawn_over_the_foreground_panics() {
+        let mut order = DrawOrder::default();
+        order.record(DrawLayer::Background);
+        order.record(DrawLayer::World);
+        order.record(DrawLayer::Foreground);
+        // The player may go over the foreground, but scenery behind the level may not
+        order.record(DrawLayer::World);
+        assert!(order.is_valid());
+        order.record(DrawLayer::Background);
+    }
+}
@@ -0,0 +1,199 @@
+//! Visual Effects
+//!
+//! Short animations played at a spot in the level, such as a squashed enemy
+//! or an explosion. Each `Effect` steps through the frames of a
+//! `SpriteAnimation` and is removed once the animation has played through.
+//! Frames are drawn procedurally, like the rest of the game's graphics.
+//!
+//! `Debris` is the rubble left by a destroyed platform: chunks that fall,
+//! land on whatever platforms remain and fade away.
+
+use macroquad::prelude::*;
+
+use crate::camera::{ScreenPos, ScreenTransform, WorldPos};
+use crate::simple_level::Platform;
+
+/// Width and height of a chunk of debris
+pub const DEBRIS_SIZE: f32 = 6.0;
+
+/// Seconds debris lasts before it has faded away
+pub const DEBRIS_LIFETIME: f32 = 1.5;
+
+/// Downward acceleration of debris, pixels per second squared
+const DEBRIS_GRAVITY: f32 = 800.0;
+
+/// A run of animation frames played once at a fixed rate
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpriteAnimation {
+    pub frame_count: u32,
+    /// Seconds each frame is shown for
+    pub frame_duration: f32,
+    pub elapsed: f32,
+}
+
+impl SpriteAnimation {
+    /// Create an animation with the given number of frames
+    pub fn new(frame_count: u32, frame_duration: f32) -> Self {
+        Self { frame_count, frame_duration, elapsed: 0.0 }
+    }
+
+    /// Advance the animation
+    pub fn update(&mut self, delta_time: f32) {
+        self.elapsed += delta_time;
+    }
+
+    /// Index of the frame to show, holding the last frame once finished
+    pub fn current_frame(&self) -> u32 {
+        let frame = (self.elapsed / self.frame_duration) as u32;
+        frame.min(self.frame_count.saturating_sub(1))
+    }
+
+    /// Whether every frame has been shown
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.frame_count as f32 * self.frame_duration
+    }
+}
+
+/// What an effect looks like
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum EffectKind {
+    /// A stomped enemy flattened against the ground
+    #[default]
+    Squash,
+    /// A burst of fire and smoke
+    Explosion,
+}
+
+/// An animation playing at a point in the level
+#[derive(Debug, Clone, Default)]
+pub struct Effect {
+    pub kind: EffectKind,
+    /// Centre of the effect
+    pub x: f32,
+    pub y: f32,
+    /// Size of the thing the effect replaces
+    pub size: f32,
+    pub animation: SpriteAnimation,
+}
+
+impl Effect {
+    /// Flattened remains of a stomped enemy
+    pub fn squash(x: f32, y: f32, size: f32) -> Self {
+        Self { kind: EffectKind::Squash, x, y, size, animation: SpriteAnimation::new(1, 0.3) }
+    }
+
+    /// A six frame explosion
+    pub fn explosion(x: f32, y: f32, size: f32) -> Self {
+        Self { kind: EffectKind::Explosion, x, y, size, animation: SpriteAnimation::new(6, 0.06) }
+    }
+
+    /// Advance the effect, returning false once it has finished and should be removed
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.animation.update(delta_time);
+        !self.animation.is_finished()
+    }
+
+    /// Draw the current frame where the view shows it
+    pub fn draw(&self, view: &ScreenTransform) {
+        let ScreenPos { x, y } = view.to_screen(WorldPos::new(self.x, self.y));
+        let frame = self.animation.current_frame() as f32;
+        match self.kind {
+            EffectKind::Squash => {
+                let w = self.size;
+                draw_rectangle(x - w / 2.0, y + self.size / 2.0 - 4.0, w, 4.0, Color::new(0.4, 0.2, 0.05, 1.0));
+            }
+            EffectKind::Explosion => {
+                // Grows over the first half, then fades out as smoke
+                let last = (self.animation.frame_count - 1).max(1) as f32;
+                let t = frame / last;
+                let radius = self.size * (0.4 + 0.6 * t);
+                let alpha = 1.0 - t * 0.8;
+                draw_circle(x, y, radius, Color::new(0.5, 0.5, 0.5, alpha * 0.6));
+                if t < 0.7 {
+                    draw_circle(x, y, radius * 0.8, Color::new(1.0, 0.5, 0.0, alpha));
+                    draw_circle(x, y, radius * 0.45, Color::new(1.0, 0.9, 0.3, alpha));
+                }
+            }
+        }
+    }
+}
+
+/// A chunk of a destroyed platform
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Debris {
+    pub x: f32,
+    pub y: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    /// Seconds left before it has faded away
+    pub life: f32,
+}
+
+impl Debris {
+    /// Break a platform into chunks spread along it, thrown up and outwards from its middle
+    pub fn from_platform(platform: &Platform) -> Vec<Debris> {
+        let count = ((platform.width / (DEBRIS_SIZE * 3.0)) as usize).clamp(2, 12);
+        let middle = (count - 1) as f32 / 2.0;
+        (0..count)
+            .map(|i| Debris {
+                x: platform.x + (i as f32 + 0.5) * platform.width / count as f32 - DEBRIS_SIZE / 2.0,
+                y: platform.y,
+                velocity_x: (i as f32 - middle) * 40.0,
+                velocity_y: if i % 2 == 0 { -160.0 } else { -100.0 },
+                life: DEBRIS_LIFETIME,
+            })
+            .collect()
+    }
+
+    /// Fall, settling on top of any platform landed on, and fade. Returns
+    /// false once the debris has faded away and should be removed.
+    pub fn update(&mut self, platforms: &[Platform], delta_time: f32) -> bool {
+        self.life -= delta_time;
+        self.velocity_y += DEBRIS_GRAVITY * delta_time;
+        self.x += self.velocity_x * delta_time;
+        let new_y = self.y + self.velocity_y * delta_time;
+        let landing = platforms
+            .iter()
+            .find(|p| self.velocity_y > 0.0 && self.y + DEBRIS_SIZE <= p.y && p.intersects(self.x, new_y, DEBRIS_SIZE, DEBRIS_SIZE));
+        match landing {
+            Some(platform) => {
+                self.y = platform.y - DEBRIS_SIZE;
+                self.velocity_y = 0.0;
+                self.velocity_x *= 0.5;
+            }
+            None => self.y = new_y,
+        }
+        self.life > 0.0
+    }
+
+    /// Draw the chunk where the view shows it, fading out towards the end
+    pub fn draw(&self, view: &ScreenTransform) {
+        let ScreenPos { x, y } = view.to_screen(WorldPos::new(self.x, self.y));
+        let alpha = (self.life / (DEBRIS_LIFETIME * 0.5)).min(1.0);
+        draw_rectangle(x, y, DEBRIS_SIZE, DEBRIS_SIZE, Color::new(0.5, 0.3, 0.1, alpha));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explosion_finishes_after_its_frames() {
+        let mut effect = Effect::explosion(0.0, 0.0, 16.0);
+        let frames = effect.animation.frame_count;
+        let mut seen = Vec::new();
+
+        let mut steps = 0;
+        while effect.update(0.02) {
+            seen.push(effect.animation.current_frame());
+            steps += 1;
+            assert!(steps < 100, "effect never finished");
+        }
+
+        // Every frame was shown in order before the effect ended
+        seen.dedup();
+        assert_eq!(seen, (0..frames).collect::<Vec<_>>());
+        assert!(effect.animation.is_finished());
+    }
+}
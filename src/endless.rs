@@ -0,0 +1,268 @@
+//! Endless Runner Mode
+//!
+//! In this mode the camera scrolls to the right on its own and the player has
+//! to keep up for as long as possible. The level is built procedurally in
+//! fixed-width chunks: new chunks are generated ahead of the camera from a
+//! seeded RNG (so a seed always produces the same course) and chunks that have
+//! scrolled out behind the camera are discarded.
+//!
+//! The run ends when the player falls off the bottom of the screen or gets
+//! left behind the camera. The score increases with the distance travelled.
+
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::camera::{ScreenTransform, WorldPos};
+use crate::frame_limiter::{FrameLimiter, FrameSettings};
+use crate::input::{InputState, KeyBindings};
+use crate::rng::SeededRng;
+use crate::simple_level::{Platform, Player, MAX_DELTA_TIME};
+use crate::viewport::Viewport;
+
+/// Endless runner tuning constants
+const CHUNK_WIDTH: f32 = 400.0;           // Horizontal size of a generated chunk
+const DEFAULT_SCROLL_SPEED: f32 = 60.0;   // Camera speed in pixels per second
+const VIEW_WIDTH: f32 = 800.0;            // Width of the visible area
+const VIEW_HEIGHT: f32 = 600.0;           // Height of the visible area
+const START_GROUND_Y: f32 = 400.0;        // Height of the starting ground
+const MIN_PLATFORM_Y: f32 = 250.0;        // Highest a generated platform may sit
+const MAX_PLATFORM_Y: f32 = 450.0;        // Lowest a generated platform may sit
+const MAX_STEP_UP: f32 = 40.0;            // Largest climb between neighbouring platforms
+const PLATFORM_HEIGHT: f32 = 20.0;        // Platform thickness
+const SCORE_DISTANCE: f32 = 10.0;         // Pixels scrolled per score point
+
+/// A slice of the procedurally generated course
+#[derive(Debug, Clone)]
+pub struct PlatformChunk {
+    pub index: u32,
+    pub start_x: f32,
+    pub platforms: Vec<Platform>,
+}
+
+impl PlatformChunk {
+    /// Right-most edge of any platform in this chunk
+    pub fn end_x(&self) -> f32 {
+        self.platforms
+            .iter()
+            .map(|p| p.x + p.width)
+            .fold(self.start_x + CHUNK_WIDTH, f32::max)
+    }
+}
+
+/// Game state for the endless runner mode
+pub struct EndlessRunner {
+    pub player: Player,
+    chunks: VecDeque<PlatformChunk>,
+    rng: SeededRng,
+    pub camera_x: f32,
+    pub scroll_speed: f32,
+    pub view_width: f32,
+    pub view_height: f32,
+    pub score: u32,
+    pub game_over: bool,
+    pub key_bindings: KeyBindings,
+    next_chunk_index: u32,
+    cursor_x: f32,
+    last_platform_y: f32,
+}
+
+impl EndlessRunner {
+    /// Create a new endless run from a seed, scrolling at the given speed
+    pub fn new(seed: u64, scroll_speed: f32) -> Self {
+        let mut runner = Self {
+            player: Player::new(50.0, START_GROUND_Y - 50.0),
+            chunks: VecDeque::new(),
+            rng: SeededRng::new(seed),
+            camera_x: 0.0,
+            scroll_speed,
+            view_width: VIEW_WIDTH,
+            view_height: VIEW_HEIGHT,
+            score: 0,
+            game_over: false,
+            key_bindings: KeyBindings::default(),
+            next_chunk_index: 0,
+            cursor_x: 0.0,
+            last_platform_y: START_GROUND_Y,
+        };
+        runner.fill_chunks();
+        runner
+    }
+
+    /// Scroll the camera forward, generating chunks ahead and dropping chunks behind
+    pub fn advance_camera(&mut self, delta_time: f32) {
+        self.camera_x += self.scroll_speed * delta_time;
+        self.score = self.score.max((self.camera_x / SCORE_DISTANCE) as u32);
+        self.fill_chunks();
+    }
+
+    /// Generate chunks until one chunk past the right edge of the view is covered
+    /// and discard chunks that are completely behind the camera
+    fn fill_chunks(&mut self) {
+        while (self.next_chunk_index as f32) * CHUNK_WIDTH < self.camera_x + self.view_width + CHUNK_WIDTH {
+            let chunk = self.generate_chunk();
+            self.chunks.push_back(chunk);
+        }
+
+        while self.chunks.front().is_some_and(|c| c.end_x() < self.camera_x) {
+            self.chunks.pop_front();
+        }
+    }
+
+    /// Build the next chunk of platforms
+    fn generate_chunk(&mut self) -> PlatformChunk {
+        let index = self.next_chunk_index;
+        let start_x = index as f32 * CHUNK_WIDTH;
+        let end_x = start_x + CHUNK_WIDTH;
+        let mut platforms = Vec::new();
+
+        if index == 0 {
+            // The first chunk is solid ground so the player has time to get going
+            platforms.push(Platform::new(0.0, START_GROUND_Y, CHUNK_WIDTH, PLATFORM_HEIGHT));
+            self.cursor_x = CHUNK_WIDTH;
+        }
+
+        // Keep gaps and height changes within what a single jump can clear
+        while self.cursor_x < end_x {
+            let gap = self.rng.range(30.0, 90.0);
+            let width = self.rng.range(80.0, 200.0);
+            let y = (self.last_platform_y + self.rng.range(-MAX_STEP_UP, MAX_STEP_UP))
+                .clamp(MIN_PLATFORM_Y, MAX_PLATFORM_Y);
+
+            platforms.push(Platform::new(self.cursor_x + gap, y, width, PLATFORM_HEIGHT));
+            self.cursor_x += gap + width;
+            self.last_platform_y = y;
+        }
+
+        self.next_chunk_index += 1;
+        PlatformChunk { index, start_x, platforms }
+    }
+
+    /// Get the chunks currently kept in memory, oldest first
+    pub fn chunks(&self) -> impl Iterator<Item = &PlatformChunk> {
+        self.chunks.iter()
+    }
+
+    /// Update the game state
+    pub fn update(&mut self, delta_time: f32) {
+        if self.game_over {
+            return;
+        }
+
+        let platforms: Vec<Platform> = self.chunks.iter().flat_map(|c| c.platforms.iter().cloned()).collect();
+        let input = InputState::from_keyboard(&self.key_bindings);
+        self.player.update(&platforms, &input, delta_time);
+
+        // The player can't run ahead of the visible area
+        if self.player.x + self.player.width > self.camera_x + self.view_width {
+            self.player.x = self.camera_x + self.view_width - self.player.width;
+            self.player.velocity_x = 0.0;
+        }
+
+        self.advance_camera(delta_time);
+
+        // Falling off the bottom or getting left behind ends the run
+        if self.player.y > self.view_height || self.player.x + self.player.width < self.camera_x {
+            self.game_over = true;
+        }
+    }
+
+    /// Draw the game
+    pub fn draw(&self) {
+        clear_background(SKYBLUE);
+
+        let view = ScreenTransform::new(WorldPos::new(self.camera_x, 0.0));
+
+        // Draw only the platforms that are on screen
+        for platform in self.chunks.iter().flat_map(|c| c.platforms.iter()) {
+            if platform.is_visible(self.camera_x, self.view_width) {
+                let at = view.to_screen(WorldPos::new(platform.x, platform.y));
+                Platform::new(at.x, at.y, platform.width, platform.height).draw();
+            }
+        }
+
+        // Draw player
+        let at = view.to_screen(WorldPos::new(self.player.x, self.player.y));
+        let mut player_copy = Player::new(at.x, at.y);
+        player_copy.facing_right = self.player.facing_right;
+        player_copy.animation_state = self.player.animation_state;
+        player_copy.animation_timer = self.player.animation_timer;
+        player_copy.draw();
+
+        self.draw_ui();
+    }
+
+    /// Draw the score and game over message
+    fn draw_ui(&self) {
+        draw_text(&format!("Score: {}", self.score), 10.0, 30.0, 24.0, WHITE);
+        draw_text("Keep up with the screen! ESC to quit", 10.0, 55.0, 20.0, WHITE);
+
+        if self.game_over {
+            let text = "Game Over!";
+            let text_width = measure_text(text, None, 40, 1.0).width;
+            let x = (self.view_width - text_width) / 2.0;
+            let y = self.view_height / 2.0;
+
+            draw_rectangle(x - 10.0, y - 30.0, text_width + 20.0, 50.0, Color::new(0.0, 0.0, 0.0, 0.7));
+            draw_text(text, x, y, 40.0, GOLD);
+            draw_text("Press R to run again", x, y + 30.0, 20.0, WHITE);
+        }
+    }
+}
+
+/// Main game loop for the endless runner mode
+pub async fn run_endless_runner(seed: u64, frame_settings: FrameSettings) {
+    let mut game = EndlessRunner::new(seed, DEFAULT_SCROLL_SPEED);
+    let mut limiter = FrameLimiter::new(frame_settings.target_fps);
+    let viewport = Viewport::default();
+
+    loop {
+        limiter.begin_frame();
+        // A long stall, like dragging the window, shouldn't jump the runner through the floor
+        let delta_time = get_frame_time().min(MAX_DELTA_TIME);
+
+        game.update(delta_time);
+        viewport.begin();
+        game.draw();
+        viewport.end();
+
+        if game.game_over && is_key_pressed(KeyCode::R) {
+            game = EndlessRunner::new(seed, DEFAULT_SCROLL_SPEED);
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+
+        limiter.end_frame();
+        next_frame().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_generated_ahead_and_discarded_behind() {
+        let mut runner = EndlessRunner::new(42, 100.0);
+        let first_index = runner.chunks().next().unwrap().index;
+        let initial_last = runner.chunks().last().unwrap().index;
+
+        // Scroll the camera forward by several chunks
+        for _ in 0..600 {
+            runner.advance_camera(1.0 / 60.0);
+        }
+        assert!(runner.camera_x > CHUNK_WIDTH * 2.0);
+
+        // New chunks exist ahead of the camera...
+        let last = runner.chunks().last().unwrap();
+        assert!(last.index > initial_last);
+        assert!(last.start_x >= runner.camera_x + runner.view_width);
+
+        // ...and the ones that scrolled out behind it are gone
+        assert!(runner.chunks().all(|c| c.index != first_index));
+        assert!(runner.chunks().all(|c| c.end_x() >= runner.camera_x));
+        assert!(runner.score > 0);
+    }
+}
@@ -0,0 +1,61 @@
+//! Game Events
+//!
+//! Collision handling doesn't apply every consequence directly. Instead it
+//! records what happened as `GameEvent`s, which the level then processes in
+//! one place. The events of the most recent step stay available afterwards so
+//! that other systems (and tests) can react to them.
+
+/// Something that happened during a game step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    /// The player's head hit the block with this index from below
+    BlockHitFromBelow { block: usize },
+    /// A brick was nudged upwards without breaking
+    BrickBumped { block: usize },
+    /// A brick was shattered
+    BrickBroken { block: usize },
+    /// A coin block gave out a coin
+    CoinDispensed { block: usize },
+    /// The player picked up the coin with this index
+    CoinCollected { coin: usize },
+    /// The player stepped on a coin challenge's pad and started the countdown
+    ChallengeStarted { challenge: usize },
+    /// Enough coins were collected in time to win a challenge
+    ChallengeCompleted { challenge: usize },
+    /// A challenge ran out of time and reset
+    ChallengeFailed { challenge: usize },
+    /// The goal became active now that its objective is complete
+    GoalActivated,
+    /// The player bounced off a spring; `chain` counts consecutive bounces
+    SpringBounce { block: usize, chain: u32 },
+    /// A switch block turned its group of outline blocks solid (`active`) or passable
+    SwitchToggled { group: u32, active: bool },
+    /// The player stomped the enemy with this index
+    EnemyStomped { enemy: usize },
+    /// A fireball defeated the enemy with this index
+    EnemyBurned { enemy: usize },
+    /// A thrown item knocked out the enemy with this index
+    EnemyKnockedOut { enemy: usize },
+    /// The enemy with this index took a hit that didn't defeat it
+    EnemyHit { enemy: usize },
+    /// The player jumped off the ground
+    PlayerJumped,
+    /// An enemy hurt the player, knocking them down to small
+    PlayerHurt,
+    /// The player lost a life and went back to the spawn point
+    PlayerDied,
+    /// The player lost their last life
+    GameOver,
+    /// The player picked up a key
+    KeyCollected { id: u32 },
+    /// The player picked up the big coin with this index
+    BigCoinCollected { coin: usize },
+    /// The platform with this index was smashed to pieces
+    PlatformDestroyed { platform: usize },
+    /// The player picked up the dropped loot with this index
+    PickupCollected { pickup: usize },
+    /// The player picked up a coin they dropped when they last died
+    DroppedCoinRecovered { coin: usize },
+    /// A second or coin was counted into the score on the results screen
+    TallyTick,
+}
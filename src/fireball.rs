@@ -0,0 +1,132 @@
+//! Fireballs
+//!
+//! Fire Mario throws fireballs that travel in the direction he is facing,
+//! bounce along the ground and burn up when they hit a wall or run out of
+//! time. Only a couple can be on screen at once.
+
+use macroquad::prelude::*;
+
+use crate::collision::Collider;
+use crate::simple_level::Platform;
+
+/// Width and height of a fireball
+pub const FIREBALL_SIZE: f32 = 8.0;
+/// Most fireballs the player can have out at once
+pub const MAX_FIREBALLS: usize = 2;
+/// Knockback a fireball gives enemies that survive it
+pub const FIREBALL_KNOCKBACK: f32 = 120.0;
+/// Horizontal speed of a thrown fireball
+const FIREBALL_SPEED: f32 = 300.0;
+/// Upward velocity after bouncing off the ground
+const FIREBALL_BOUNCE: f32 = 220.0;
+/// Fireballs fall faster than the player so their bounces stay low
+const FIREBALL_GRAVITY: f32 = 1200.0;
+/// Seconds a fireball lasts before burning out
+const FIREBALL_LIFETIME: f32 = 2.0;
+
+/// A bouncing fireball thrown by the player
+#[derive(Debug, Clone, Default)]
+pub struct Fireball {
+    pub x: f32,
+    pub y: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    /// Time left before the fireball burns out
+    pub lifetime: f32,
+    /// What the fireball hits; the player's own pass straight through them
+    pub collider: Collider,
+}
+
+impl Fireball {
+    /// Create a fireball heading left or right
+    pub fn new(x: f32, y: f32, facing_right: bool) -> Self {
+        Self {
+            x,
+            y,
+            velocity_x: if facing_right { FIREBALL_SPEED } else { -FIREBALL_SPEED },
+            velocity_y: 0.0,
+            lifetime: FIREBALL_LIFETIME,
+            collider: Collider::FIREBALL,
+        }
+    }
+
+    /// Move the fireball, bouncing it off the tops of platforms.
+    /// Returns false once it has hit a wall or burned out.
+    pub fn update(&mut self, platforms: &[Platform], delta_time: f32) -> bool {
+        self.lifetime -= delta_time;
+        if self.lifetime <= 0.0 {
+            return false;
+        }
+
+        // Running into the side of anything puts it out
+        let new_x = self.x + self.velocity_x * delta_time;
+        if platforms.iter().any(|p| p.intersects(new_x, self.y, FIREBALL_SIZE, FIREBALL_SIZE)) {
+            self.lifetime = 0.0;
+            return false;
+        }
+        self.x = new_x;
+
+        self.velocity_y += FIREBALL_GRAVITY * delta_time;
+        let new_y = self.y + self.velocity_y * delta_time;
+        match platforms.iter().find(|p| p.intersects(self.x, new_y, FIREBALL_SIZE, FIREBALL_SIZE)) {
+            Some(platform) if self.velocity_y > 0.0 => {
+                self.y = platform.y - FIREBALL_SIZE;
+                self.velocity_y = -FIREBALL_BOUNCE;
+            }
+            Some(_) => self.velocity_y = 0.0,
+            None => self.y = new_y,
+        }
+        true
+    }
+
+    /// Whether the fireball is still burning
+    pub fn is_alive(&self) -> bool {
+        self.lifetime > 0.0
+    }
+
+    /// Put the fireball out, e.g. after it hits an enemy
+    pub fn extinguish(&mut self) {
+        self.lifetime = 0.0;
+    }
+
+    /// Check if the fireball overlaps with a rectangle
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        x < self.x + FIREBALL_SIZE &&
+        x + width > self.x &&
+        y < self.y + FIREBALL_SIZE &&
+        y + height > self.y
+    }
+
+    /// Draw the fireball
+    pub fn draw(&self) {
+        let r = FIREBALL_SIZE / 2.0;
+        draw_circle(self.x + r, self.y + r, r, ORANGE);
+        draw_circle(self.x + r, self.y + r, r * 0.5, YELLOW);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fireball_bounces_on_ground_and_stops_at_walls() {
+        let floor = Platform::new(0.0, 100.0, 400.0, 20.0);
+        let wall = Platform::new(300.0, 0.0, 20.0, 100.0);
+        let mut fireball = Fireball::new(10.0, 100.0 - FIREBALL_SIZE, true);
+
+        let mut bounced = false;
+        let mut alive = true;
+        for _ in 0..120 {
+            alive = fireball.update(&[floor.clone(), wall.clone()], 1.0 / 60.0);
+            if !alive {
+                break;
+            }
+            bounced |= fireball.velocity_y < 0.0;
+            assert!(fireball.y + FIREBALL_SIZE <= 100.0);
+        }
+        assert!(bounced);
+        assert!(!alive);
+        assert!(fireball.x < 300.0);
+    }
+}
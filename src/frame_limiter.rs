@@ -0,0 +1,138 @@
+//! Frame Rate Limiting
+//!
+//! The game loop otherwise runs as fast as the machine allows. `FrameSettings`
+//! chooses between relying on vsync and capping the frame rate, and
+//! `FrameLimiter` enforces the cap by sleeping off whatever is left of each
+//! frame's time budget. `game_window_conf` applies the settings that have to
+//! be chosen when the window is created.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use macroquad::window::Conf;
+
+/// How the frame rate is controlled, how frames are anti-aliased, and whether
+/// frames are profiled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSettings {
+    pub vsync: bool,
+    pub target_fps: Option<u32>,
+    /// Time each frame's update and draw, saving histograms on exit
+    pub profile: bool,
+    /// MSAA samples per pixel; 1 turns anti-aliasing off
+    pub msaa_samples: i32,
+}
+
+impl Default for FrameSettings {
+    fn default() -> Self {
+        Self { vsync: true, target_fps: None, profile: false, msaa_samples: 1 }
+    }
+}
+
+impl FrameSettings {
+    /// Read the settings from command line arguments (`--no-vsync`, `--fps <n>`,
+    /// `--msaa <samples>`, `--profile`)
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Self {
+        let mut settings = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--no-vsync" => settings.vsync = false,
+                "--profile" => settings.profile = true,
+                "--fps" => settings.target_fps = args.next().and_then(|v| v.parse().ok()).filter(|fps| *fps > 0),
+                "--msaa" => {
+                    settings.msaa_samples = args.next().and_then(|v| v.parse().ok()).filter(|n| *n > 0).unwrap_or(1)
+                }
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    /// Swap interval to request from the window (1 waits for vsync, 0 doesn't)
+    pub fn swap_interval(&self) -> i32 {
+        if self.vsync { 1 } else { 0 }
+    }
+}
+
+/// The 800x600 game window with the given title, set up for the frame settings
+pub fn game_window_conf(title: &str, settings: &FrameSettings) -> Conf {
+    let mut conf = Conf {
+        window_title: title.to_owned(),
+        window_width: 800,
+        window_height: 600,
+        sample_count: settings.msaa_samples,
+        ..Default::default()
+    };
+    conf.platform.swap_interval = Some(settings.swap_interval());
+    conf
+}
+
+/// Sleeps at the end of each frame to hold a target frame rate
+#[derive(Debug)]
+pub struct FrameLimiter {
+    target_frame_time: Option<Duration>,
+    frame_start: Instant,
+}
+
+impl FrameLimiter {
+    /// Create a limiter for the given frame rate (`None` means uncapped)
+    pub fn new(target_fps: Option<u32>) -> Self {
+        Self {
+            target_frame_time: target_fps.map(|fps| Duration::from_secs_f64(1.0 / fps as f64)),
+            frame_start: Instant::now(),
+        }
+    }
+
+    /// Mark the start of a frame
+    pub fn begin_frame(&mut self) {
+        self.frame_start = Instant::now();
+    }
+
+    /// Sleep for the rest of the frame's time budget, returning how long it slept
+    pub fn end_frame(&mut self) -> Duration {
+        let Some(target) = self.target_frame_time else {
+            return Duration::ZERO;
+        };
+
+        let remaining = target.saturating_sub(self.frame_start.elapsed());
+        if !remaining.is_zero() {
+            thread::sleep(remaining);
+        }
+        remaining
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_sleeps_when_frame_finishes_early() {
+        let mut limiter = FrameLimiter::new(Some(20));
+        limiter.begin_frame();
+        let started = Instant::now();
+        let slept = limiter.end_frame();
+
+        // A 20 FPS frame is 50ms; allow plenty of slack for slow test machines
+        assert!(slept > Duration::from_millis(30));
+        assert!(started.elapsed() >= Duration::from_millis(30));
+
+        let mut uncapped = FrameLimiter::new(None);
+        uncapped.begin_frame();
+        assert_eq!(uncapped.end_frame(), Duration::ZERO);
+
+        let settings = FrameSettings::from_args(["--fps".to_owned(), "30".to_owned(), "--no-vsync".to_owned()]);
+        assert_eq!(settings, FrameSettings { vsync: false, target_fps: Some(30), profile: false, msaa_samples: 1 });
+    }
+
+    #[test]
+    fn test_window_conf_carries_msaa_samples() {
+        let settings = FrameSettings::from_args(["--msaa".to_owned(), "4".to_owned()]);
+        let conf = game_window_conf("Rust Mario", &settings);
+        assert_eq!(conf.sample_count, 4);
+        assert_eq!(conf.window_title, "Rust Mario");
+
+        assert_eq!(game_window_conf("Rust Mario", &FrameSettings::default()).sample_count, 1);
+    }
+}
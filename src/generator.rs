@@ -0,0 +1,300 @@
+//! Level Generators
+//!
+//! Procedural level layouts behind a common `LevelGenerator` trait, so new
+//! algorithms can be swapped in without touching the level itself. Every
+//! generator is deterministic: the same seed always builds the same level.
+//! Gaps and climbs are kept within what a single jump can clear.
+//!
+//! Which enemies appear is drawn from a weighted `SpawnTable`, whose weights
+//! shift from the easy mix at the start of a level to a harder one at its end.
+
+use serde::{Deserialize, Serialize};
+
+use crate::level_data::{EnemySpawn, LevelData};
+use crate::rng::SeededRng;
+use crate::simple_level::{Coin, EnemyKind, Platform};
+
+/// Platform thickness for generated levels
+const PLATFORM_HEIGHT: f32 = 20.0;
+/// Height of the ground in flat levels
+const GROUND_Y: f32 = 400.0;
+/// Height of the goal flag
+const GOAL_HEIGHT: f32 = 60.0;
+/// Height of a built-in enemy
+const ENEMY_SIZE: f32 = 16.0;
+/// How high above a surface coins float
+const COIN_HEIGHT: f32 = 40.0;
+
+/// How likely an enemy type is to be picked, at the easiest and hardest
+/// points of a level. Weights are relative to the other entries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpawnWeight {
+    pub kind: EnemyKind,
+    pub easy: f32,
+    pub hard: f32,
+}
+
+impl SpawnWeight {
+    pub fn new(kind: EnemyKind, easy: f32, hard: f32) -> Self {
+        Self { kind, easy, hard }
+    }
+}
+
+/// Weighted choice of enemy types, ramping between an easy and a hard mix
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnTable {
+    entries: Vec<SpawnWeight>,
+}
+
+impl Default for SpawnTable {
+    /// Mostly Goombas early on; armored enemies, splitters and the odd Hammer
+    /// Bro get more common further in
+    fn default() -> Self {
+        Self::new(vec![
+            SpawnWeight::new(EnemyKind::Goomba, 0.75, 0.45),
+            SpawnWeight::new(EnemyKind::Armored, 0.15, 0.25),
+            SpawnWeight::new(EnemyKind::Splitter, 0.08, 0.18),
+            SpawnWeight::new(EnemyKind::HammerBro, 0.02, 0.12),
+        ])
+    }
+}
+
+impl SpawnTable {
+    pub fn new(entries: Vec<SpawnWeight>) -> Self {
+        Self { entries }
+    }
+
+    /// Each enemy type's weight at a difficulty from 0 (easy) to 1 (hard)
+    pub fn weights(&self, difficulty: f32) -> Vec<(EnemyKind, f32)> {
+        let t = difficulty.clamp(0.0, 1.0);
+        self.entries.iter().map(|e| (e.kind, (e.easy + (e.hard - e.easy) * t).max(0.0))).collect()
+    }
+
+    /// Pick an enemy type at random in proportion to the weights at this difficulty
+    pub fn pick(&self, rng: &mut SeededRng, difficulty: f32) -> EnemyKind {
+        let weights = self.weights(difficulty);
+        let total: f32 = weights.iter().map(|(_, w)| w).sum();
+        let mut roll = rng.next_f32() * total;
+        for (kind, weight) in &weights {
+            if roll < *weight {
+                return *kind;
+            }
+            roll -= weight;
+        }
+        weights.iter().rev().find(|(_, w)| *w > 0.0).map_or(EnemyKind::Goomba, |(kind, _)| *kind)
+    }
+}
+
+/// Builds a level layout from a seed
+pub trait LevelGenerator {
+    /// Generate a level. The same seed always gives the same level.
+    fn generate(&self, seed: u64) -> LevelData;
+}
+
+/// The built-in generators with their default settings, picked by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GeneratorKind {
+    FlatWithGaps,
+    Staircase,
+    Cave,
+}
+
+impl GeneratorKind {
+    /// Look a generator up by its lowercase name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "flat" => Some(GeneratorKind::FlatWithGaps),
+            "staircase" => Some(GeneratorKind::Staircase),
+            "cave" => Some(GeneratorKind::Cave),
+            _ => None,
+        }
+    }
+
+    /// The generator itself
+    pub fn generator(self) -> Box<dyn LevelGenerator> {
+        match self {
+            GeneratorKind::FlatWithGaps => Box::new(FlatWithGaps::default()),
+            GeneratorKind::Staircase => Box::new(Staircase::default()),
+            GeneratorKind::Cave => Box::new(Cave::default()),
+        }
+    }
+}
+
+/// Put the pieces of a generated level together, with the player starting
+/// on the first platform and the goal at the far end of the last one
+fn assemble(name: &str, platforms: Vec<Platform>, coins: Vec<Coin>, enemies: Vec<EnemySpawn>) -> LevelData {
+    let first = &platforms[0];
+    let last = &platforms[platforms.len() - 1];
+    LevelData {
+        name: name.to_owned(),
+        player_spawn: (first.x + 20.0, first.y - 40.0),
+        goal: (last.x + last.width - 50.0, last.y - GOAL_HEIGHT),
+        platforms,
+        coins,
+        enemies,
+        trees: Vec::new(),
+        blocks: Vec::new(),
+        medal_thresholds: None,
+        ..Default::default()
+    }
+}
+
+/// Flat ground broken up by gaps to jump, with enemies patrolling some of it
+#[derive(Debug, Clone)]
+pub struct FlatWithGaps {
+    /// Number of stretches of ground
+    pub segments: usize,
+    /// Enemy types to place, getting harder towards the end
+    pub spawn_table: SpawnTable,
+}
+
+impl Default for FlatWithGaps {
+    fn default() -> Self {
+        Self { segments: 8, spawn_table: SpawnTable::default() }
+    }
+}
+
+impl LevelGenerator for FlatWithGaps {
+    fn generate(&self, seed: u64) -> LevelData {
+        let mut rng = SeededRng::new(seed);
+        let (mut platforms, mut coins, mut enemies) = (Vec::new(), Vec::new(), Vec::new());
+        let mut x = 0.0;
+        let segments = self.segments.max(2);
+        for i in 0..segments {
+            let ends = i == 0 || i == segments - 1;
+            let width = if ends { 250.0 } else { rng.range(150.0, 300.0) };
+            platforms.push(Platform::new(x, GROUND_Y, width, PLATFORM_HEIGHT));
+            if !ends {
+                coins.push(Coin::new(x + width / 2.0, GROUND_Y - COIN_HEIGHT));
+                if rng.next_f32() < 0.5 {
+                    let y = GROUND_Y - ENEMY_SIZE;
+                    let kind = self.spawn_table.pick(&mut rng, i as f32 / (segments - 1) as f32);
+                    let spawn = EnemySpawn::new(x + width / 2.0, y, x + 10.0, x + width - ENEMY_SIZE - 10.0);
+                    enemies.push(EnemySpawn { kind, ..spawn });
+                }
+            }
+            x += width + rng.range(40.0, 90.0);
+        }
+        assemble("Flat with Gaps", platforms, coins, enemies)
+    }
+}
+
+/// Platforms climbing and descending like a staircase
+#[derive(Debug, Clone, Copy)]
+pub struct Staircase {
+    /// Number of steps
+    pub steps: usize,
+}
+
+impl Default for Staircase {
+    fn default() -> Self {
+        Self { steps: 12 }
+    }
+}
+
+impl LevelGenerator for Staircase {
+    fn generate(&self, seed: u64) -> LevelData {
+        let mut rng = SeededRng::new(seed);
+        let (mut platforms, mut coins) = (Vec::new(), Vec::new());
+        let (mut x, mut y, mut width) = (0.0, 500.0, 150.0);
+        let mut climbing = true;
+        platforms.push(Platform::new(x, y, width, PLATFORM_HEIGHT));
+        for _ in 1..self.steps.max(2) {
+            // Turn around before running off the top or bottom of the screen
+            let rise = rng.range(20.0, 40.0);
+            if climbing && y - rise < 150.0 {
+                climbing = false;
+            } else if !climbing && y + rise > 500.0 {
+                climbing = true;
+            }
+            x += width + rng.range(20.0, 60.0);
+            y += if climbing { -rise } else { rise };
+            width = rng.range(80.0, 140.0);
+            platforms.push(Platform::new(x, y, width, PLATFORM_HEIGHT));
+            coins.push(Coin::new(x + width / 2.0, y - COIN_HEIGHT));
+        }
+        assemble("Staircase", platforms, coins, Vec::new())
+    }
+}
+
+/// A tunnel with an uneven floor and a low ceiling overhead
+#[derive(Debug, Clone, Copy)]
+pub struct Cave {
+    /// Number of stretches of floor
+    pub segments: usize,
+}
+
+impl Default for Cave {
+    fn default() -> Self {
+        Self { segments: 10 }
+    }
+}
+
+impl LevelGenerator for Cave {
+    fn generate(&self, seed: u64) -> LevelData {
+        let mut rng = SeededRng::new(seed);
+        let (mut floors, mut ceilings, mut coins) = (Vec::new(), Vec::new(), Vec::new());
+        let (mut x, mut y) = (0.0, GROUND_Y);
+        for i in 0..self.segments.max(2) {
+            let width = rng.range(120.0, 220.0);
+            floors.push(Platform::new(x, y, width, PLATFORM_HEIGHT));
+            let headroom = rng.range(100.0, 140.0);
+            ceilings.push(Platform::new(x, y - headroom - PLATFORM_HEIGHT, width, PLATFORM_HEIGHT));
+            if i > 0 {
+                coins.push(Coin::new(x + width / 2.0, y - COIN_HEIGHT));
+            }
+
+            // Now and then a small pit, and the floor drifts up or down a little
+            x += width + if rng.next_f32() < 0.3 { rng.range(40.0, 60.0) } else { 0.0 };
+            y = (y + rng.range(-30.0, 30.0)).clamp(330.0, 470.0);
+        }
+
+        // Keep the floor in order so the level starts and ends on it
+        let mut level = assemble("Cave", floors, coins, Vec::new());
+        level.platforms.extend(ceilings);
+        level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_level::SimpleLevel;
+
+    #[test]
+    fn test_generators_differ_but_stay_playable() {
+        let generators: [&dyn LevelGenerator; 3] = [&FlatWithGaps::default(), &Staircase::default(), &Cave::default()];
+        for seed in 0..20 {
+            let levels: Vec<LevelData> = generators.iter().map(|g| g.generate(seed)).collect();
+            let layouts: Vec<Vec<(f32, f32)>> =
+                levels.iter().map(|l| l.platforms.iter().map(|p| (p.x, p.y)).collect()).collect();
+            assert_ne!(layouts[0], layouts[1]);
+            assert_ne!(layouts[1], layouts[2]);
+
+            for level in &levels {
+                let report = SimpleLevel::from_data(level).reachability();
+                assert!(report.all_reachable(), "seed {} made an unplayable {:?}: {:?}", seed, level.name, report);
+            }
+        }
+
+        // Same seed, same level
+        assert_eq!(Staircase::default().generate(3).to_json().unwrap(), Staircase::default().generate(3).to_json().unwrap());
+    }
+
+    #[test]
+    fn test_spawn_table_picks_match_weights() {
+        let table = SpawnTable::default();
+        let mut rng = SeededRng::new(42);
+        const SAMPLES: usize = 20_000;
+        for difficulty in [0.0, 1.0] {
+            let mut counts = std::collections::HashMap::new();
+            for _ in 0..SAMPLES {
+                *counts.entry(table.pick(&mut rng, difficulty)).or_insert(0) += 1;
+            }
+            for (kind, weight) in table.weights(difficulty) {
+                let share = counts.get(&kind).copied().unwrap_or(0) as f32 / SAMPLES as f32;
+                assert!((share - weight).abs() < 0.015, "{:?} at difficulty {}: {} vs {}", kind, difficulty, share, weight);
+            }
+        }
+    }
+}
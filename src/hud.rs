@@ -0,0 +1,46 @@
+//! HUD Elements
+//!
+//! Reusable pieces of the heads-up display that are drawn in screen space on
+//! top of the level.
+
+use macroquad::prelude::*;
+
+/// Width of the boss health bar
+const BOSS_BAR_WIDTH: f32 = 400.0;
+/// Height of the boss health bar
+const BOSS_BAR_HEIGHT: f32 = 14.0;
+/// Distance from the top of the screen to the boss health bar
+const BOSS_BAR_TOP: f32 = 60.0;
+
+/// How full a health bar is, from 0 to 1
+pub fn health_fraction(current: f32, max: f32) -> f32 {
+    if max <= 0.0 { 0.0 } else { (current / max).clamp(0.0, 1.0) }
+}
+
+/// Draw a boss's name and health bar across the top of the screen. `current`
+/// can be fractional so the bar can drain smoothly after a hit.
+pub fn draw_boss_health(current: f32, max: f32, name: &str, view_width: f32) {
+    let x = (view_width - BOSS_BAR_WIDTH) / 2.0;
+    let fill = health_fraction(current, max);
+
+    let name_width = measure_text(name, None, 20, 1.0).width;
+    draw_text(name, (view_width - name_width) / 2.0, BOSS_BAR_TOP - 6.0, 20.0, WHITE);
+    draw_rectangle(x, BOSS_BAR_TOP, BOSS_BAR_WIDTH, BOSS_BAR_HEIGHT, Color::new(0.0, 0.0, 0.0, 0.6));
+    draw_rectangle(x, BOSS_BAR_TOP, BOSS_BAR_WIDTH * fill, BOSS_BAR_HEIGHT, Color::new(0.85, 0.1, 0.1, 1.0));
+    draw_rectangle_lines(x, BOSS_BAR_TOP, BOSS_BAR_WIDTH, BOSS_BAR_HEIGHT, 2.0, WHITE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_fraction_is_current_over_max() {
+        assert_eq!(health_fraction(3.0, 4.0), 0.75);
+        assert_eq!(health_fraction(2.5, 10.0), 0.25);
+        assert_eq!(health_fraction(0.0, 5.0), 0.0);
+        // Out of range values stay within the bar
+        assert_eq!(health_fraction(6.0, 5.0), 1.0);
+        assert_eq!(health_fraction(1.0, 0.0), 0.0);
+    }
+}
@@ -0,0 +1,233 @@
+//! Input Handling
+//!
+//! Keyboard keys are mapped to game actions through `KeyBindings`, and each
+//! frame the bindings are read into an `InputState` snapshot. Gameplay code
+//! only ever looks at the snapshot, never at the keyboard directly.
+
+use std::fmt;
+
+use macroquad::prelude::*;
+
+/// A game action that can be bound to keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Down,
+    Fire,
+    Grab,
+    Pause,
+    Restart,
+}
+
+impl Action {
+    /// Every bindable action, in display order
+    pub const ALL: [Action; 8] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Down,
+        Action::Fire,
+        Action::Grab,
+        Action::Pause,
+        Action::Restart,
+    ];
+
+    /// Human readable name of the action
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Jump => "Jump",
+            Action::Down => "Down",
+            Action::Fire => "Fire",
+            Action::Grab => "Grab",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+        }
+    }
+
+    /// Look up an action by the name typed into the console
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(Action::MoveLeft),
+            "right" => Some(Action::MoveRight),
+            "jump" => Some(Action::Jump),
+            "down" => Some(Action::Down),
+            "fire" => Some(Action::Fire),
+            "grab" => Some(Action::Grab),
+            "pause" => Some(Action::Pause),
+            "restart" => Some(Action::Restart),
+            _ => None,
+        }
+    }
+}
+
+/// Look up a key by name: a letter, or one of the arrow and modifier keys
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+        KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+        KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+        KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    ];
+    match name {
+        "space" => Some(KeyCode::Space),
+        "up" => Some(KeyCode::Up),
+        "down" => Some(KeyCode::Down),
+        "left" => Some(KeyCode::Left),
+        "right" => Some(KeyCode::Right),
+        "shift" => Some(KeyCode::LeftShift),
+        "ctrl" => Some(KeyCode::LeftControl),
+        "enter" => Some(KeyCode::Enter),
+        _ => match name.as_bytes() {
+            &[letter @ b'a'..=b'z'] => Some(LETTERS[(letter - b'a') as usize]),
+            _ => None,
+        },
+    }
+}
+
+/// Two actions that have been assigned the same key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub key: KeyCode,
+    pub first: Action,
+    pub second: Action,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is bound to both {} and {}", self.key, self.first.name(), self.second.name())
+    }
+}
+
+impl std::error::Error for Conflict {}
+
+/// Mapping from game actions to the keys that trigger them
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    bindings: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (Action::MoveLeft, vec![KeyCode::Left, KeyCode::A]),
+                (Action::MoveRight, vec![KeyCode::Right, KeyCode::D]),
+                (Action::Jump, vec![KeyCode::Space, KeyCode::Up, KeyCode::W]),
+                (Action::Down, vec![KeyCode::Down, KeyCode::S]),
+                (Action::Fire, vec![KeyCode::X, KeyCode::LeftShift]),
+                (Action::Grab, vec![KeyCode::Z, KeyCode::LeftControl]),
+                (Action::Pause, vec![KeyCode::P]),
+                (Action::Restart, vec![KeyCode::R]),
+            ],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Get the keys bound to an action
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Replace the keys bound to an action
+    pub fn bind(&mut self, action: Action, keys: Vec<KeyCode>) {
+        match self.bindings.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, existing)) => *existing = keys,
+            None => self.bindings.push((action, keys)),
+        }
+    }
+
+    /// Replace the keys bound to an action, but only if that leaves no conflicts.
+    /// The bindings are left untouched when the change is rejected.
+    pub fn try_bind(&mut self, action: Action, keys: Vec<KeyCode>) -> Result<(), Conflict> {
+        let mut updated = self.clone();
+        updated.bind(action, keys);
+        updated.validate()?;
+        *self = updated;
+        Ok(())
+    }
+
+    /// Check that no key is assigned to more than one action
+    pub fn validate(&self) -> Result<(), Conflict> {
+        for (i, (first, first_keys)) in self.bindings.iter().enumerate() {
+            for (second, second_keys) in &self.bindings[i + 1..] {
+                if let Some(key) = first_keys.iter().find(|k| second_keys.contains(k)) {
+                    return Err(Conflict { key: *key, first: *first, second: *second });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if any key bound to the action is held down
+    pub fn is_down(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|key| is_key_down(*key))
+    }
+
+    /// Check if any key bound to the action was pressed this frame
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|key| is_key_pressed(*key))
+    }
+}
+
+/// Snapshot of the player's input for a single frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InputState {
+    pub left: bool,
+    pub right: bool,
+    pub jump_pressed: bool,
+    pub jump_held: bool,
+    /// Let go of a ledge
+    pub down: bool,
+    pub fire_pressed: bool,
+    /// Carry an item while held, throw it on release
+    pub grab_held: bool,
+    pub pause_pressed: bool,
+    pub restart_pressed: bool,
+}
+
+impl InputState {
+    /// Read the current keyboard state through the given bindings
+    pub fn from_keyboard(bindings: &KeyBindings) -> Self {
+        Self {
+            left: bindings.is_down(Action::MoveLeft),
+            right: bindings.is_down(Action::MoveRight),
+            jump_pressed: bindings.is_pressed(Action::Jump),
+            jump_held: bindings.is_down(Action::Jump),
+            down: bindings.is_down(Action::Down),
+            fire_pressed: bindings.is_pressed(Action::Fire),
+            grab_held: bindings.is_down(Action::Grab),
+            pause_pressed: bindings.is_pressed(Action::Pause),
+            restart_pressed: bindings.is_pressed(Action::Restart),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_key_reports_both_actions() {
+        let mut bindings = KeyBindings::default();
+        assert!(bindings.validate().is_ok());
+
+        bindings.bind(Action::Fire, vec![KeyCode::Space]);
+        let conflict = bindings.validate().unwrap_err();
+        assert_eq!(conflict, Conflict { key: KeyCode::Space, first: Action::Jump, second: Action::Fire });
+        assert_eq!(conflict.to_string(), "Space is bound to both Jump and Fire");
+
+        // A conflicting change is rejected without being applied
+        let mut bindings = KeyBindings::default();
+        assert!(bindings.try_bind(Action::Fire, vec![KeyCode::Space]).is_err());
+        assert_eq!(bindings.keys(Action::Fire), &[KeyCode::X, KeyCode::LeftShift]);
+    }
+}
@@ -0,0 +1,190 @@
+//! Throwable Items
+//!
+//! Shells and throw blocks sit in the level until the player grabs one by
+//! holding the grab key next to it. The item is then carried in front of the
+//! player, and letting go of the key throws it in the direction they face.
+//! A thrown item slides along the ground and knocks out enemies in its path:
+//! shells rebound off walls and keep going, blocks break on the first thing
+//! they hit.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{ScreenPos, ScreenTransform, WorldPos};
+use crate::collision::Collider;
+use crate::simple_level::Platform;
+
+/// Width and height of an item
+pub const ITEM_SIZE: f32 = 14.0;
+/// How far from the player an item can be grabbed
+const GRAB_REACH: f32 = 6.0;
+/// Horizontal speed of a thrown item
+const THROW_SPEED: f32 = 350.0;
+/// Gravity on thrown items
+const ITEM_GRAVITY: f32 = 1000.0;
+/// How far below the level a thrown item falls before it's gone
+const FALL_LIMIT: f32 = 1000.0;
+
+/// The kinds of item the player can carry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemKind {
+    /// Rebounds off walls when thrown
+    #[default]
+    Shell,
+    /// Breaks on the first wall or enemy it hits
+    Block,
+}
+
+/// What an item is doing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ItemState {
+    /// Lying still, ready to be grabbed
+    #[default]
+    Resting,
+    /// Carried by the player
+    Held,
+    /// Flying or sliding after being thrown
+    Thrown,
+    /// Broken or lost
+    Gone,
+}
+
+/// A shell or block the player can pick up and throw
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Item {
+    pub kind: ItemKind,
+    pub x: f32,
+    pub y: f32,
+    #[serde(skip)]
+    pub velocity_x: f32,
+    #[serde(skip)]
+    pub velocity_y: f32,
+    #[serde(skip)]
+    pub state: ItemState,
+    /// What the item knocks out once thrown
+    #[serde(skip, default = "thrown_item_collider")]
+    pub collider: Collider,
+}
+
+fn thrown_item_collider() -> Collider {
+    Collider::THROWN_ITEM
+}
+
+impl Default for Item {
+    fn default() -> Self {
+        Self {
+            kind: ItemKind::default(),
+            x: 0.0,
+            y: 0.0,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            state: ItemState::default(),
+            collider: thrown_item_collider(),
+        }
+    }
+}
+
+impl Item {
+    /// Place an item at the given position
+    pub fn new(kind: ItemKind, x: f32, y: f32) -> Self {
+        Self { kind, x, y, ..Default::default() }
+    }
+
+    /// Whether a player's rectangle is close enough to grab this item
+    pub fn in_reach(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        self.state == ItemState::Resting
+            && x - GRAB_REACH < self.x + ITEM_SIZE
+            && x + width + GRAB_REACH > self.x
+            && y < self.y + ITEM_SIZE
+            && y + height > self.y
+    }
+
+    /// Hold the item in front of a player at the given position
+    pub fn carry(&mut self, x: f32, y: f32, width: f32, height: f32, facing_right: bool) {
+        self.state = ItemState::Held;
+        self.x = if facing_right { x + width } else { x - ITEM_SIZE };
+        self.y = y + (height - ITEM_SIZE) / 2.0;
+    }
+
+    /// Let go of the item, sending it off in the facing direction
+    pub fn throw(&mut self, facing_right: bool) {
+        self.state = ItemState::Thrown;
+        self.velocity_x = if facing_right { THROW_SPEED } else { -THROW_SPEED };
+        self.velocity_y = 0.0;
+    }
+
+    /// Let go of the item without throwing it, so it falls to the ground and rests there
+    pub fn let_go(&mut self) {
+        self.state = ItemState::Thrown;
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+    }
+
+    /// Whether the item is flying and can knock out enemies
+    pub fn is_thrown(&self) -> bool {
+        self.state == ItemState::Thrown
+    }
+
+    /// The item hit an enemy: blocks break, shells carry on
+    pub fn hit_enemy(&mut self) {
+        if self.kind == ItemKind::Block {
+            self.state = ItemState::Gone;
+        }
+    }
+
+    /// Move a thrown item, landing it on platforms and bouncing or breaking it on walls
+    pub fn update(&mut self, platforms: &[Platform], delta_time: f32) {
+        if self.state != ItemState::Thrown {
+            return;
+        }
+
+        let new_x = self.x + self.velocity_x * delta_time;
+        if platforms.iter().any(|p| p.intersects(new_x, self.y, ITEM_SIZE, ITEM_SIZE)) {
+            match self.kind {
+                ItemKind::Shell => self.velocity_x = -self.velocity_x,
+                ItemKind::Block => self.state = ItemState::Gone,
+            }
+        } else {
+            self.x = new_x;
+        }
+
+        self.velocity_y += ITEM_GRAVITY * delta_time;
+        let new_y = self.y + self.velocity_y * delta_time;
+        match platforms.iter().find(|p| p.intersects(self.x, new_y, ITEM_SIZE, ITEM_SIZE)) {
+            Some(platform) if self.velocity_y > 0.0 => {
+                self.y = platform.y - ITEM_SIZE;
+                self.velocity_y = 0.0;
+                // Dropped items settle where they land
+                if self.velocity_x == 0.0 {
+                    self.state = ItemState::Resting;
+                }
+            }
+            Some(_) => self.velocity_y = 0.0,
+            None => self.y = new_y,
+        }
+        if self.y > FALL_LIMIT {
+            self.state = ItemState::Gone;
+        }
+    }
+
+    /// Check if the item overlaps with a rectangle
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        x < self.x + ITEM_SIZE && x + width > self.x && y < self.y + ITEM_SIZE && y + height > self.y
+    }
+
+    /// Draw the item where the view shows it
+    pub fn draw(&self, view: &ScreenTransform) {
+        let ScreenPos { x, y } = view.to_screen(WorldPos::new(self.x, self.y));
+        match self.kind {
+            ItemKind::Shell => {
+                let r = ITEM_SIZE / 2.0;
+                draw_circle(x + r, y + r, r, Color::new(0.1, 0.6, 0.2, 1.0));
+                draw_circle_lines(x + r, y + r, r * 0.6, 1.5, Color::new(0.9, 0.9, 0.7, 1.0));
+            }
+            ItemKind::Block => {
+                draw_rectangle(x, y, ITEM_SIZE, ITEM_SIZE, Color::new(0.3, 0.5, 0.9, 1.0));
+                draw_rectangle_lines(x, y, ITEM_SIZE, ITEM_SIZE, 2.0, DARKBLUE);
+            }
+        }
+    }
+}
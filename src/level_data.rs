@@ -0,0 +1,272 @@
+//! Level Data
+//!
+//! Serializable description of a level's layout. Levels can be saved to and
+//! loaded from JSON files, and `SimpleLevel` is built from this data rather
+//! than from hardcoded positions.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::background::{BackgroundLayer, BackgroundTile, DecorationKind};
+use crate::blocks::{Block, BlockKind};
+use crate::camera::{CameraBounds, ScrollMargins};
+use crate::challenge::CoinChallenge;
+use crate::items::Item;
+use crate::loot::LootTable;
+use crate::rating::StarThresholds;
+use crate::simple_level::{BigCoin, Coin, Door, EnemyKind, GoalKind, Key, Objective, Platform, PowerUp, RespawnPolicy, Tree, Waypoint};
+use crate::time_attack::MedalThresholds;
+
+/// Platform thickness used by the built-in level
+const PLATFORM_HEIGHT: f32 = 20.0;
+
+/// Where an enemy starts and the range it patrols
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnemySpawn {
+    /// Enemy type name to look up in the roster; without one the built-in `kind` is used
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub x: f32,
+    pub y: f32,
+    pub patrol_start: f32,
+    pub patrol_end: f32,
+    #[serde(default)]
+    pub kind: EnemyKind,
+    /// Starting size of splitting enemies
+    #[serde(default = "default_size_tier")]
+    pub size_tier: u8,
+    /// Waypoints to follow, looping, instead of patrolling
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<Waypoint>,
+    /// Makes the enemy a boss, with this name over its health bar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boss: Option<String>,
+    /// What the enemy might drop when defeated, instead of its roster type's loot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loot: Option<LootTable>,
+}
+
+fn default_size_tier() -> u8 {
+    1
+}
+
+impl EnemySpawn {
+    /// A regular enemy patrolling between two points
+    pub fn new(x: f32, y: f32, patrol_start: f32, patrol_end: f32) -> Self {
+        Self {
+            name: None,
+            x,
+            y,
+            patrol_start,
+            patrol_end,
+            kind: EnemyKind::Goomba,
+            size_tier: 1,
+            path: Vec::new(),
+            boss: None,
+            loot: None,
+        }
+    }
+
+    /// An enemy of a roster type patrolling between two points
+    pub fn named(name: &str, x: f32, y: f32, patrol_start: f32, patrol_end: f32) -> Self {
+        Self { name: Some(name.to_owned()), ..Self::new(x, y, patrol_start, patrol_end) }
+    }
+}
+
+/// Everything needed to construct a level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelData {
+    pub name: String,
+    pub player_spawn: (f32, f32),
+    /// Power-up the player starts the level with
+    #[serde(default)]
+    pub start_power: PowerUp,
+    /// Let the player catch and hang from platform ledges
+    #[serde(default)]
+    pub ledge_grab: bool,
+    pub goal: (f32, f32),
+    /// What the goal looks like and how finishing the level plays out
+    #[serde(default)]
+    pub goal_kind: GoalKind,
+    pub platforms: Vec<Platform>,
+    #[serde(default)]
+    pub trees: Vec<Tree>,
+    /// Decorative tile layers drawn behind the platforms
+    #[serde(default)]
+    pub background: Vec<BackgroundLayer>,
+    /// Draw the player behind the foreground layers instead of over them,
+    /// e.g. to walk under tree crowns
+    #[serde(default)]
+    pub player_behind_foreground: bool,
+    #[serde(default)]
+    pub enemies: Vec<EnemySpawn>,
+    #[serde(default)]
+    pub keys: Vec<Key>,
+    #[serde(default)]
+    pub doors: Vec<Door>,
+    #[serde(default)]
+    pub coins: Vec<Coin>,
+    /// Rare collectibles worth bonus points, tracked across attempts
+    #[serde(default)]
+    pub big_coins: Vec<BigCoin>,
+    /// Bonus "collect N coins in time" challenges
+    #[serde(default)]
+    pub challenges: Vec<CoinChallenge>,
+    #[serde(default)]
+    pub blocks: Vec<Block>,
+    /// Shells and blocks the player can pick up and throw
+    #[serde(default)]
+    pub items: Vec<Item>,
+    #[serde(default)]
+    pub medal_thresholds: Option<MedalThresholds>,
+    #[serde(default)]
+    pub respawn_policy: RespawnPolicy,
+    #[serde(default)]
+    pub objective: Objective,
+    /// Background music track for the level
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Path of the enemy roster file giving the stats of named enemy types
+    #[serde(default)]
+    pub roster: Option<String>,
+    /// Let the player move within these margins before the camera scrolls, instead of centring them
+    #[serde(default)]
+    pub scroll_margins: Option<ScrollMargins>,
+    /// Keep the camera inside this region instead of letting it roam the whole level
+    #[serde(default)]
+    pub camera_bounds: Option<CameraBounds>,
+    /// What earns each star of the rating given on finishing; the defaults if not set
+    #[serde(default)]
+    pub star_thresholds: Option<StarThresholds>,
+    /// Start each patrolling enemy facing a random way from a random point on
+    /// its patrol, picked from the level's seed
+    #[serde(default)]
+    pub random_enemy_starts: bool,
+}
+
+impl LevelData {
+    /// Parse level data from a JSON string
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serialize the level data to a JSON string
+    pub fn to_json(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Load level data from a JSON file
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_json(&fs::read_to_string(filepath)?)
+    }
+
+    /// Save level data to a JSON file
+    pub fn save<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(filepath, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+impl Default for LevelData {
+    /// The built-in simple level
+    fn default() -> Self {
+        Self {
+            name: "Simple Level".to_owned(),
+            player_spawn: (50.0, 50.0),
+            start_power: PowerUp::Small,
+            ledge_grab: false,
+            goal: (870.0, 140.0),
+            goal_kind: GoalKind::Flag,
+            platforms: vec![
+                // Ground platforms
+                Platform::new(0.0, 400.0, 200.0, PLATFORM_HEIGHT),
+                Platform::new(250.0, 450.0, 150.0, PLATFORM_HEIGHT),
+                Platform::new(450.0, 350.0, 100.0, PLATFORM_HEIGHT),
+                Platform::new(600.0, 300.0, 120.0, PLATFORM_HEIGHT),
+                Platform::new(750.0, 250.0, 100.0, PLATFORM_HEIGHT),
+                // Some floating platforms
+                Platform::new(200.0, 300.0, 80.0, PLATFORM_HEIGHT),
+                Platform::new(350.0, 200.0, 80.0, PLATFORM_HEIGHT),
+                Platform::new(500.0, 150.0, 80.0, PLATFORM_HEIGHT),
+                // Final platform with goal
+                Platform::new(850.0, 200.0, 100.0, PLATFORM_HEIGHT),
+            ],
+            // Decorative trees
+            trees: vec![
+                Tree::new(100.0, 400.0, 40.0),
+                Tree::new(300.0, 450.0, 35.0),
+                Tree::new(520.0, 350.0, 45.0),
+                Tree::new(700.0, 250.0, 38.0),
+                Tree::new(950.0, 200.0, 42.0),
+            ],
+            background: vec![
+                // Distant clouds and hills scroll slower than the level
+                BackgroundLayer::new(0.3, vec![
+                    BackgroundTile::new(DecorationKind::Cloud, 120.0, 90.0),
+                    BackgroundTile::new(DecorationKind::Cloud, 480.0, 60.0),
+                    BackgroundTile::new(DecorationKind::Cloud, 820.0, 110.0),
+                ]),
+                BackgroundLayer::new(0.6, vec![
+                    BackgroundTile::new(DecorationKind::Hill, 0.0, 600.0),
+                    BackgroundTile::new(DecorationKind::Hill, 380.0, 600.0),
+                    BackgroundTile::new(DecorationKind::Hill, 760.0, 600.0),
+                ]),
+                BackgroundLayer::new(1.0, vec![
+                    BackgroundTile::new(DecorationKind::Bush, 20.0, 400.0),
+                    BackgroundTile::new(DecorationKind::Bush, 300.0, 400.0),
+                ]),
+            ],
+            enemies: vec![
+                EnemySpawn::new(220.0, 400.0 - 16.0, 210.0, 380.0), // Ground patrol
+                EnemySpawn::new(470.0, 350.0 - 16.0, 460.0, 540.0), // Platform patrol
+                EnemySpawn::new(620.0, 300.0 - 16.0, 610.0, 710.0), // Longer patrol
+            ],
+            keys: Vec::new(),
+            doors: Vec::new(),
+            // A trail of coins over the floating platforms
+            coins: vec![
+                Coin::new(234.0, 270.0),
+                Coin::new(384.0, 170.0),
+                Coin::new(534.0, 120.0),
+            ],
+            big_coins: Vec::new(),
+            challenges: Vec::new(),
+            // Bricks and coin blocks above the starting ground
+            blocks: vec![
+                Block::brick(110.0, 320.0),
+                Block::new(130.0, 320.0, BlockKind::MultiCoin),
+                Block::brick(150.0, 320.0),
+                Block::new(170.0, 320.0, BlockKind::Question),
+                // A spring on the low platform
+                Block::new(330.0, 430.0, BlockKind::Spring),
+            ],
+            items: Vec::new(),
+            medal_thresholds: Some(MedalThresholds { gold: 8.0, silver: 12.0, bronze: 20.0 }),
+            respawn_policy: RespawnPolicy::default(),
+            objective: Objective::default(),
+            music: Some("overworld".to_owned()),
+            roster: None,
+            scroll_margins: None,
+            camera_bounds: None,
+            star_thresholds: None,
+            random_enemy_starts: false,
+            player_behind_foreground: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip_keeps_medal_thresholds() {
+        let level = LevelData::default();
+        let loaded = LevelData::from_json(&level.to_json().unwrap()).unwrap();
+
+        assert_eq!(loaded.platforms.len(), level.platforms.len());
+        assert_eq!(loaded.medal_thresholds, level.medal_thresholds);
+    }
+}
@@ -2,5 +2,49 @@
 //! 
 //! A simple Mario-like platformer game built with macroquad.
 
+pub mod animator;
+pub mod attract;
+pub mod background;
+pub mod bank;
+pub mod blocks;
+pub mod camera;
+pub mod challenge;
+pub mod collision;
+pub mod console;
+pub mod draw_order;
+pub mod effects;
+pub mod endless;
+pub mod events;
+pub mod fireball;
+pub mod frame_limiter;
+pub mod generator;
+pub mod hud;
+pub mod input;
+pub mod items;
+pub mod level_data;
+pub mod loot;
+pub mod mirror;
+pub mod music;
+pub mod pool;
+pub mod profiler;
+pub mod progress;
+pub mod projectile;
+pub mod prompts;
+pub mod rating;
+pub mod reachability;
+pub mod replay;
+pub mod rng;
+pub mod roster;
+pub mod scoring;
+pub mod screenshot;
+pub mod shadow;
 pub mod simple_level;
-pub mod screenshot;
\ No newline at end of file
+pub mod simulation;
+pub mod sound;
+pub mod stats;
+pub mod svg;
+pub mod tally;
+pub mod time_attack;
+pub mod tutorial;
+pub mod tween;
+pub mod viewport;
\ No newline at end of file
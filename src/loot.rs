@@ -0,0 +1,112 @@
+//! Enemy Loot
+//!
+//! Enemies with a `LootTable` may drop something where they were defeated:
+//! a coin, or a power-up for the player. Whether anything drops and what it
+//! is come from the level's seeded RNG, so a replayed run drops the same
+//! things. Dropped loot waits in the air as a `Pickup` until it's touched.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::SeededRng;
+use crate::simple_level::PowerUp;
+
+/// Something an enemy can drop
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Loot {
+    Coin,
+    PowerUp(PowerUp),
+    /// Run faster and jump further for a while
+    SpeedShoes,
+}
+
+/// One possible drop, with its share of the table
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LootEntry {
+    pub loot: Loot,
+    /// Relative chance of this drop against the others in the table
+    pub weight: f32,
+}
+
+/// What an enemy might drop when defeated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LootTable {
+    /// Chance from 0 to 1 that anything drops at all
+    pub chance: f32,
+    pub entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    /// A table that always drops the one thing
+    pub fn always(loot: Loot) -> Self {
+        Self { chance: 1.0, entries: vec![LootEntry { loot, weight: 1.0 }] }
+    }
+
+    /// Roll for a drop: first whether there is one, then which by weight
+    pub fn roll(&self, rng: &mut SeededRng) -> Option<Loot> {
+        if rng.next_f32() >= self.chance {
+            return None;
+        }
+        let total: f32 = self.entries.iter().map(|entry| entry.weight).sum();
+        let mut pick = rng.range(0.0, total);
+        for entry in &self.entries {
+            if pick < entry.weight {
+                return Some(entry.loot);
+            }
+            pick -= entry.weight;
+        }
+        self.entries.last().map(|entry| entry.loot)
+    }
+}
+
+/// Dropped loot waiting to be picked up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pickup {
+    pub x: f32,
+    pub y: f32,
+    pub loot: Loot,
+    pub collected: bool,
+}
+
+impl Pickup {
+    /// Width and height of a pickup
+    pub const SIZE: f32 = 14.0;
+
+    /// Loot centred on (x, y)
+    pub fn centred(x: f32, y: f32, loot: Loot) -> Self {
+        Self { x: x - Self::SIZE / 2.0, y: y - Self::SIZE / 2.0, loot, collected: false }
+    }
+
+    /// Check if the pickup overlaps with a rectangle
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        x < self.x + Self::SIZE && x + width > self.x && y < self.y + Self::SIZE && y + height > self.y
+    }
+
+    /// Draw the pickup with its top-left corner at (x, y)
+    pub fn draw(loot: Loot, x: f32, y: f32) {
+        let r = Self::SIZE / 2.0;
+        match loot {
+            Loot::Coin => {
+                draw_circle(x + r, y + r, r * 0.85, GOLD);
+                draw_circle_lines(x + r, y + r, r * 0.85, 1.5, ORANGE);
+            }
+            Loot::PowerUp(PowerUp::FireFlower) => {
+                draw_rectangle(x + r - 1.0, y + r, 2.0, r, GREEN);
+                draw_circle(x + r, y + r * 0.8, r * 0.7, ORANGE);
+                draw_circle(x + r, y + r * 0.8, r * 0.35, YELLOW);
+            }
+            Loot::SpeedShoes => {
+                draw_rectangle(x + r * 0.4, y + r * 0.3, r * 0.7, r, SKYBLUE);
+                draw_rectangle(x + r * 0.4, y + r * 1.2, r * 1.4, r * 0.5, SKYBLUE);
+                draw_triangle(vec2(x, y + r * 0.5), vec2(x + r * 0.4, y + r * 0.3), vec2(x + r * 0.4, y + r * 0.9), WHITE);
+            }
+            // A mushroom, for growing big
+            Loot::PowerUp(_) => {
+                draw_rectangle(x + r * 0.5, y + r, r, r, BEIGE);
+                draw_circle(x + r, y + r, r, RED);
+                draw_circle(x + r * 0.6, y + r * 0.7, 2.0, WHITE);
+                draw_circle(x + r * 1.4, y + r * 0.7, 2.0, WHITE);
+            }
+        }
+    }
+}
@@ -3,23 +3,167 @@
 //! A simple Mario-like platformer game built with macroquad.
 //! Run this to start the game and enjoy jumping around!
 
-pub mod simple_level;
+pub mod animator;
+pub mod attract;
+pub mod background;
+pub mod bank;
+pub mod blocks;
+pub mod camera;
+pub mod challenge;
+pub mod collision;
+pub mod console;
+pub mod draw_order;
+pub mod effects;
+pub mod endless;
+pub mod events;
+pub mod fireball;
+pub mod frame_limiter;
+pub mod generator;
+pub mod hud;
+pub mod input;
+pub mod items;
+pub mod level_data;
+pub mod loot;
+pub mod mirror;
+pub mod music;
+pub mod pool;
+pub mod profiler;
+pub mod progress;
+pub mod projectile;
+pub mod prompts;
+pub mod rating;
+pub mod reachability;
+pub mod replay;
+pub mod rng;
+pub mod roster;
+pub mod scoring;
 pub mod screenshot;
+pub mod shadow;
+pub mod simple_level;
+pub mod simulation;
+pub mod sound;
+pub mod stats;
+pub mod svg;
+pub mod tally;
+pub mod time_attack;
+pub mod tutorial;
+pub mod tween;
+pub mod viewport;
+
+use std::sync::LazyLock;
 
 use macroquad::prelude::*;
 
+use frame_limiter::{FrameSettings, game_window_conf};
+use viewport::{DisplaySettings, DISPLAY_SETTINGS_PATH};
+
+/// Frame settings from the command line, read once for both the window and the game.
+/// Pass --fps <n> to cap the frame rate, --no-vsync to turn vsync off,
+/// --msaa <samples> to smooth edges, and --profile to save frame time histograms to frame_profile.csv on exit
+static FRAME_SETTINGS: LazyLock<FrameSettings> = LazyLock::new(|| FrameSettings::from_args(std::env::args()));
+
 /// Window configuration for the game
 fn window_conf() -> Conf {
-    Conf {
-        window_title: "Rust Mario - Simple Level".to_owned(),
-        window_width: 800,
-        window_height: 600,
-        ..Default::default()
+    // Open fullscreen if that's how the game was last left
+    let display = DisplaySettings::load(DISPLAY_SETTINGS_PATH).unwrap_or_default();
+    let conf = game_window_conf("Rust Mario - Simple Level", &FRAME_SETTINGS);
+    Conf { fullscreen: display.fullscreen, ..conf }
+}
+
+/// Level settings picked on the command line, set up the same way in every mode
+struct LevelOptions {
+    pixel_perfect: bool,
+    auto_jump: bool,
+    hardcore: bool,
+    death_coin_drop: f32,
+    render_scale: f32,
+    movement: simple_level::MovementPreset,
+}
+
+impl LevelOptions {
+    /// Set a freshly built level up with these options
+    fn apply(&self, level: &mut simple_level::SimpleLevel) {
+        level.settings.pixel_perfect = self.pixel_perfect;
+        level.settings.auto_jump = self.auto_jump;
+        level.settings.hardcore = self.hardcore;
+        level.settings.death_coin_drop = self.death_coin_drop;
+        level.settings.render_scale = self.render_scale;
+        self.movement.apply(&mut level.player.physics);
     }
 }
 
+/// The value passed after `name` on the command line, if it was given
+fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.windows(2).find(|pair| pair[0] == name).map(|pair| pair[1].as_str())
+}
+
 #[macroquad::main(window_conf)]
 async fn main() {
-    // Run the simple Mario level
-    simple_level::run_simple_level().await;
+    let frame_settings = *FRAME_SETTINGS;
+    let args: Vec<String> = std::env::args().collect();
+
+    // Pass --pixel-perfect for a crisp low-resolution retro look
+    let pixel_perfect = args.iter().any(|arg| arg == "--pixel-perfect");
+
+    // Pass --auto-jump to jump automatically at gap edges and obstacles
+    let auto_jump = args.iter().any(|arg| arg == "--auto-jump");
+
+    // Pass --hardcore for permadeath: no continues, and losing every life deletes the save
+    let hardcore = args.iter().any(|arg| arg == "--hardcore");
+
+    // Pass --movement modern for a more forgiving feel than the classic one
+    let movement = flag_value(&args, "--movement").and_then(simple_level::MovementPreset::from_name).unwrap_or_default();
+
+    // Pass --drop-coins <fraction> to drop that share of your coins where you die
+    let death_coin_drop = flag_value(&args, "--drop-coins").and_then(|value| value.parse::<f32>().ok()).unwrap_or(0.0);
+
+    // Pass --render-scale <n> to draw characters bigger or smaller than their hitboxes
+    let render_scale = flag_value(&args, "--render-scale")
+        .and_then(|value| value.parse::<f32>().ok())
+        .filter(|scale| *scale > 0.0)
+        .unwrap_or(1.0);
+
+    // Pass --generate <flat|staircase|cave> to play a freshly generated level
+    let generate = flag_value(&args, "--generate").and_then(generator::GeneratorKind::from_name);
+
+    let options = LevelOptions { pixel_perfect, auto_jump, hardcore, death_coin_drop, render_scale, movement };
+
+    // Generated and endless levels differ every time they're started
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // Pass --replay <file> to watch a shared run
+    let replay_path = flag_value(&args, "--replay");
+
+    // Pass --time-attack to race the clock for a medal
+    let time_attack = args.iter().any(|arg| arg == "--time-attack");
+
+    // Pass --endless to pick a different mode
+    if let Some(path) = replay_path {
+        let result = match replay::Replay::load(path) {
+            Ok(replay) => replay::run_replay(&replay, frame_settings).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("Couldn't play the replay {}: {}", path, e);
+        }
+    } else if args.iter().any(|arg| arg == "--endless") {
+        endless::run_endless_runner(seed, frame_settings).await;
+    } else {
+        // Run the simple Mario level, against the clock with --time-attack, once
+        // the title menu is dismissed, coming back to the menu after a lost hardcore run
+        while attract::run_title_menu(frame_settings).await {
+            let mut level = match generate {
+                Some(kind) => simple_level::SimpleLevel::generated(kind, seed),
+                None => simple_level::SimpleLevel::new(),
+            };
+            level.settings.time_attack = time_attack;
+            options.apply(&mut level);
+            if !simple_level::run_level(level, frame_settings).await {
+                break;
+            }
+        }
+    }
 }
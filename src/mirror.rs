@@ -0,0 +1,110 @@
+//! Mirror Boss
+//!
+//! The mirror is a boss that fights the player with their own moves. It
+//! replays the player's inputs from a moment ago with left and right swapped,
+//! so it runs the mirror image of the player's earlier path: jumping when
+//! they jumped and running the other way when they ran. Beating it is a
+//! matter of steering it into trouble, or getting above it in time to stomp.
+
+use std::collections::VecDeque;
+
+use crate::input::InputState;
+use crate::simple_level::{Platform, Player};
+
+/// Physics steps the mirror lags behind the player's inputs
+pub const MIRROR_DELAY_STEPS: usize = 30;
+
+/// The movement part of an input, reflected left to right
+pub fn mirrored(input: &InputState) -> InputState {
+    InputState {
+        left: input.right,
+        right: input.left,
+        jump_pressed: input.jump_pressed,
+        jump_held: input.jump_held,
+        down: input.down,
+        ..Default::default()
+    }
+}
+
+/// Holds inputs back for a fixed number of steps before handing them on
+#[derive(Debug, Clone)]
+pub struct InputDelay {
+    queue: VecDeque<InputState>,
+    delay: usize,
+}
+
+impl InputDelay {
+    /// A delay of `delay` steps; until that many inputs have gone in, no buttons come out
+    pub fn new(delay: usize) -> Self {
+        Self { queue: VecDeque::with_capacity(delay + 1), delay }
+    }
+
+    /// Queue this step's input and take the one from `delay` steps ago
+    pub fn push(&mut self, input: InputState) -> InputState {
+        self.queue.push_back(input);
+        if self.queue.len() > self.delay {
+            self.queue.pop_front().unwrap_or_default()
+        } else {
+            InputState::default()
+        }
+    }
+}
+
+/// The mirror's body, moved by the player's delayed and reflected inputs
+/// with the same physics as the player
+#[derive(Debug, Clone)]
+pub struct MirrorBody {
+    pub body: Player,
+    inputs: InputDelay,
+}
+
+impl MirrorBody {
+    /// A mirror standing at (x, y), lagging `delay` steps behind the player
+    pub fn new(x: f32, y: f32, delay: usize) -> Self {
+        Self { body: Player::new(x, y), inputs: InputDelay::new(delay) }
+    }
+
+    /// Feed in the player's input for this step and move by the reflection of an earlier one
+    pub fn update(&mut self, platforms: &[Platform], input: &InputState, delta_time: f32) {
+        let delayed = self.inputs.push(mirrored(input));
+        self.body.update(platforms, &delayed, delta_time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_moves_opposite_to_the_players_earlier_movement() {
+        let floor = [Platform::new(0.0, 400.0, 1000.0, 40.0)];
+        let mut player = Player::new(300.0, 380.0);
+        let mut mirror = MirrorBody::new(600.0, 380.0, MIRROR_DELAY_STEPS);
+        let dt = 1.0 / 60.0;
+
+        // Run right, jump, then drift back left
+        let mut script = vec![InputState { right: true, ..Default::default() }; 20];
+        script.push(InputState { right: true, jump_pressed: true, jump_held: true, ..Default::default() });
+        script.extend(vec![InputState { left: true, jump_held: true, ..Default::default() }; 40]);
+        script.extend(vec![InputState::default(); MIRROR_DELAY_STEPS]);
+
+        let mut players = vec![(player.x, player.y)];
+        let mut mirrors = vec![(mirror.body.x, mirror.body.y)];
+        for input in &script {
+            player.update(&floor, input, dt);
+            mirror.update(&floor, input, dt);
+            players.push((player.x, player.y));
+            mirrors.push((mirror.body.x, mirror.body.y));
+        }
+
+        // The mirror stands still until the delay runs out, then retraces the
+        // player's earlier path, every step right matched by one left
+        assert!(mirrors[..=MIRROR_DELAY_STEPS].iter().all(|&m| m == (600.0, 380.0)));
+        for (step, &(x, y)) in mirrors.iter().enumerate().skip(MIRROR_DELAY_STEPS) {
+            let (player_x, player_y) = players[step - MIRROR_DELAY_STEPS];
+            assert!((x - (900.0 - player_x)).abs() < 1e-3, "step {}: mirror at {}, player was at {}", step, x, player_x);
+            assert!((y - player_y).abs() < 1e-3);
+        }
+        assert!(players.iter().any(|&(_, y)| y < 370.0), "the player should have jumped");
+    }
+}
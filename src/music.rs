@@ -0,0 +1,117 @@
+//! Background Music
+//!
+//! Each level can name its own background track. `MusicPlayer` keeps track of
+//! which track is playing and crossfades to a new one instead of cutting over
+//! when the level changes. It only manages track selection and volumes; an
+//! audio backend reads `channels()` each frame and plays the tracks at those
+//! volumes.
+
+/// Default length of a crossfade, in seconds
+pub const DEFAULT_CROSSFADE: f32 = 1.5;
+
+/// Manages the playing track and crossfades between tracks
+#[derive(Debug, Clone)]
+pub struct MusicPlayer {
+    current: Option<String>,
+    outgoing: Option<String>,
+    fade_duration: f32,
+    fade_elapsed: f32,
+}
+
+impl Default for MusicPlayer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CROSSFADE)
+    }
+}
+
+impl MusicPlayer {
+    /// Create a player that crossfades over `fade_duration` seconds
+    pub fn new(fade_duration: f32) -> Self {
+        Self { current: None, outgoing: None, fade_duration, fade_elapsed: fade_duration }
+    }
+
+    /// Switch to a track (or to silence with `None`), fading out whatever was playing.
+    /// Asking for the track that's already playing does nothing.
+    pub fn play(&mut self, track: Option<&str>) {
+        if self.current.as_deref() == track {
+            return;
+        }
+        self.outgoing = self.current.take();
+        self.current = track.map(str::to_owned);
+        self.fade_elapsed = 0.0;
+    }
+
+    /// Advance the crossfade
+    pub fn update(&mut self, delta_time: f32) {
+        self.fade_elapsed = (self.fade_elapsed + delta_time).min(self.fade_duration);
+        if !self.is_fading() {
+            self.outgoing = None;
+        }
+    }
+
+    /// Whether a crossfade is in progress
+    pub fn is_fading(&self) -> bool {
+        self.fade_elapsed < self.fade_duration
+    }
+
+    /// How far through the crossfade we are, from 0 to 1
+    fn progress(&self) -> f32 {
+        if self.fade_duration <= 0.0 { 1.0 } else { self.fade_elapsed / self.fade_duration }
+    }
+
+    /// The track being faded in or playing
+    pub fn current_track(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// The track being faded out, if a crossfade is in progress
+    pub fn outgoing_track(&self) -> Option<&str> {
+        self.outgoing.as_deref()
+    }
+
+    /// Volume of the current track, from 0 to 1
+    pub fn current_volume(&self) -> f32 {
+        if self.current.is_some() { self.progress() } else { 0.0 }
+    }
+
+    /// Volume of the outgoing track, from 0 to 1
+    pub fn outgoing_volume(&self) -> f32 {
+        if self.outgoing.is_some() { 1.0 - self.progress() } else { 0.0 }
+    }
+
+    /// Every track that should be audible with its volume
+    pub fn channels(&self) -> Vec<(&str, f32)> {
+        let outgoing = self.outgoing_track().map(|t| (t, self.outgoing_volume()));
+        let current = self.current_track().map(|t| (t, self.current_volume()));
+        outgoing.into_iter().chain(current).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossfade_swaps_volumes_over_time() {
+        let mut music = MusicPlayer::new(1.0);
+        music.play(Some("overworld"));
+        music.update(1.0);
+        assert_eq!(music.channels(), vec![("overworld", 1.0)]);
+
+        music.play(Some("castle"));
+        let mut last = (music.outgoing_volume(), music.current_volume());
+        assert_eq!(last, (1.0, 0.0));
+        for _ in 0..3 {
+            music.update(0.25);
+            let now = (music.outgoing_volume(), music.current_volume());
+            assert!(now.0 < last.0, "outgoing track should get quieter");
+            assert!(now.1 > last.1, "incoming track should get louder");
+            last = now;
+        }
+
+        // Once the fade is over only the new track is left
+        music.update(0.25);
+        assert!(!music.is_fading());
+        assert_eq!(music.channels(), vec![("castle", 1.0)]);
+    }
+}
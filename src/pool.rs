@@ -0,0 +1,148 @@
+//! Object Pool
+//!
+//! Short-lived entities such as particles, projectiles and score popups come
+//! and go every few frames. Rather than pushing to and removing from a `Vec`,
+//! a `Pool` keeps their slots around and hands freed ones back out through a
+//! free list, so the storage stops growing once it has reached the peak
+//! number of live entities.
+
+/// A fixed-slot store that reuses released entries
+#[derive(Debug, Clone)]
+pub struct Pool<T> {
+    slots: Vec<T>,
+    active: Vec<bool>,
+    free: Vec<usize>,
+}
+
+impl<T: Default> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default> Pool<T> {
+    /// Create an empty pool
+    pub fn new() -> Self {
+        Self { slots: Vec::new(), active: Vec::new(), free: Vec::new() }
+    }
+
+    /// Create a pool with `capacity` slots already allocated
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut pool = Self::new();
+        pool.slots.resize_with(capacity, T::default);
+        pool.active.resize(capacity, false);
+        // Hand out the lowest indices first
+        pool.free = (0..capacity).rev().collect();
+        pool
+    }
+
+    /// Take a slot, reset to its default value, and return its index.
+    /// A freed slot is reused when there is one; otherwise the pool grows.
+    pub fn acquire(&mut self) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.slots[index] = T::default();
+                self.active[index] = true;
+                index
+            }
+            None => {
+                self.slots.push(T::default());
+                self.active.push(true);
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// Give a slot back to the pool. Releasing an inactive slot does nothing.
+    pub fn release(&mut self, index: usize) {
+        if self.active.get(index).copied().unwrap_or(false) {
+            self.active[index] = false;
+            self.free.push(index);
+        }
+    }
+
+    /// Release every slot for which `keep` returns false
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        for index in 0..self.slots.len() {
+            if self.active[index] && !keep(&self.slots[index]) {
+                self.release(index);
+            }
+        }
+    }
+
+    /// The item in an active slot
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.is_active(index).then(|| &self.slots[index])
+    }
+
+    /// The item in an active slot, mutably
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if self.is_active(index) { Some(&mut self.slots[index]) } else { None }
+    }
+
+    /// Whether the slot is currently in use
+    pub fn is_active(&self, index: usize) -> bool {
+        self.active.get(index).copied().unwrap_or(false)
+    }
+
+    /// Iterate over the items in active slots
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().zip(&self.active).filter(|(_, active)| **active).map(|(item, _)| item)
+    }
+
+    /// Iterate mutably over the items in active slots
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().zip(&self.active).filter(|(_, active)| **active).map(|(item, _)| item)
+    }
+
+    /// Number of slots in use
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    /// Whether no slots are in use
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of slots allocated, in use or not
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_released_slots_are_reused() {
+        let mut pool: Pool<u32> = Pool::new();
+        let a = pool.acquire();
+        let b = pool.acquire();
+        *pool.get_mut(b).unwrap() = 7;
+        assert_eq!(pool.capacity(), 2);
+
+        pool.release(b);
+        assert!(pool.get(b).is_none());
+        let c = pool.acquire();
+        assert_eq!(c, b);
+        assert_eq!(pool.get(c), Some(&0));
+
+        // Churning through entities never grows past the peak live count
+        for _ in 0..100 {
+            let i = pool.acquire();
+            pool.release(i);
+        }
+        assert_eq!(pool.capacity(), 3);
+        assert_eq!(pool.len(), 2);
+
+        pool.retain(|_| false);
+        assert!(pool.is_empty());
+        assert!(pool.get(a).is_none());
+
+        let mut preallocated: Pool<u32> = Pool::with_capacity(4);
+        assert_eq!(preallocated.acquire(), 0);
+        assert_eq!(preallocated.capacity(), 4);
+    }
+}
@@ -0,0 +1,116 @@
+//! Frame Profiler
+//!
+//! With `--profile`, the game loop times every frame's update and draw and
+//! files the durations into histograms. On exit they are written to a CSV
+//! file, one row per bucket, so spikes that are too brief to notice on the
+//! frame counter still show up as a tail of slow frames.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Where the profile is written when the game exits
+pub const PROFILE_PATH: &str = "frame_profile.csv";
+/// Width of each histogram bucket, in milliseconds
+const BUCKET_MS: f32 = 0.5;
+/// Number of buckets; slower samples all land in the last one
+const BUCKET_COUNT: usize = 200;
+
+/// Headline numbers for a set of samples, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: u32,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+/// Durations sorted into fixed-width buckets, plus running totals
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    buckets: Vec<u32>,
+    count: u32,
+    total: f32,
+    min: f32,
+    max: f32,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self { buckets: vec![0; BUCKET_COUNT], count: 0, total: 0.0, min: f32::INFINITY, max: 0.0 }
+    }
+}
+
+impl Histogram {
+    /// Add a sample
+    pub fn record(&mut self, duration: Duration) {
+        let ms = duration.as_secs_f32() * 1000.0;
+        let bucket = ((ms / BUCKET_MS) as usize).min(BUCKET_COUNT - 1);
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.total += ms;
+        self.min = self.min.min(ms);
+        self.max = self.max.max(ms);
+    }
+
+    /// Min, max and mean of everything recorded, or None before the first sample
+    pub fn summary(&self) -> Option<Summary> {
+        (self.count > 0).then(|| Summary { count: self.count, min: self.min, max: self.max, mean: self.total / self.count as f32 })
+    }
+
+    /// Each bucket that has samples, as its start in milliseconds and its count
+    pub fn buckets(&self) -> impl Iterator<Item = (f32, u32)> + '_ {
+        self.buckets.iter().enumerate().filter(|(_, count)| **count > 0).map(|(i, count)| (i as f32 * BUCKET_MS, *count))
+    }
+}
+
+/// Update and draw timings for every frame
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    pub update: Histogram,
+    pub draw: Histogram,
+}
+
+impl Profiler {
+    /// The histograms as CSV: a row per non-empty bucket, then a summary row per phase
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("phase,bucket_start_ms,bucket_end_ms,count\n");
+        let phases = [("update", &self.update), ("draw", &self.draw)];
+        for (phase, histogram) in phases {
+            for (start, count) in histogram.buckets() {
+                csv += &format!("{},{:.1},{:.1},{}\n", phase, start, start + BUCKET_MS, count);
+            }
+        }
+        for (phase, histogram) in phases {
+            if let Some(s) = histogram.summary() {
+                csv += &format!("# {}: {} frames, min {:.3}ms, max {:.3}ms, mean {:.3}ms\n", phase, s.count, s.min, s.max, s.mean);
+            }
+        }
+        csv
+    }
+
+    /// Write the histograms to a CSV file
+    pub fn save_csv<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(filepath, self.to_csv())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_summary_and_buckets() {
+        let mut profiler = Profiler::default();
+        assert_eq!(profiler.update.summary(), None);
+        for ms in [2, 4, 4, 10] {
+            profiler.update.record(Duration::from_millis(ms));
+        }
+
+        let summary = profiler.update.summary().unwrap();
+        assert_eq!((summary.count, summary.min, summary.max, summary.mean), (4, 2.0, 10.0, 5.0));
+        assert_eq!(profiler.update.buckets().collect::<Vec<_>>(), vec![(2.0, 1), (4.0, 2), (10.0, 1)]);
+        assert!(profiler.to_csv().contains("update,4.0,4.5,2\n"));
+    }
+}
@@ -0,0 +1,49 @@
+//! Level Progress
+//!
+//! Remembers which big coins have been found in each level, across attempts
+//! and sessions, so players can come back for the ones they missed. Levels
+//! are identified by name and big coins by their index in the level.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Big coins found so far in every level
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelProgress {
+    big_coins: BTreeMap<String, BTreeSet<usize>>,
+}
+
+impl LevelProgress {
+    /// Note that a level's big coin has been found
+    pub fn find_big_coin(&mut self, level: &str, coin: usize) {
+        self.big_coins.entry(level.to_owned()).or_default().insert(coin);
+    }
+
+    /// Whether a level's big coin has ever been found
+    pub fn has_big_coin(&self, level: &str, coin: usize) -> bool {
+        self.big_coins.get(level).is_some_and(|found| found.contains(&coin))
+    }
+
+    /// How many of a level's big coins have ever been found
+    pub fn big_coins_found(&self, level: &str) -> usize {
+        self.big_coins.get(level).map_or(0, BTreeSet::len)
+    }
+
+    /// Load progress from a JSON file, starting afresh if there isn't one yet
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        match fs::read_to_string(filepath) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Save progress to a JSON file
+    pub fn save<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(filepath, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
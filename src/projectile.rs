@@ -0,0 +1,137 @@
+//! Enemy Projectiles
+//!
+//! Things enemies throw at the player, such as a Hammer Bro's hammers. They
+//! fly in an arc straight through the scenery and hurt the player on contact.
+//! When one meets a player's fireball, `ClashRules` decides what happens to
+//! each of them, per kind of projectile.
+
+use macroquad::prelude::*;
+
+use crate::collision::Collider;
+
+/// Width and height of a hammer
+pub const HAMMER_SIZE: f32 = 10.0;
+/// Most enemy projectiles in flight at once
+pub const MAX_PROJECTILES: usize = 8;
+/// Horizontal speed of a thrown hammer
+const HAMMER_SPEED: f32 = 120.0;
+/// Upward velocity a hammer is lobbed with
+const HAMMER_LAUNCH: f32 = 350.0;
+/// Gravity pulling thrown hammers back down
+const HAMMER_GRAVITY: f32 = 900.0;
+/// Seconds a hammer stays in play
+const HAMMER_LIFETIME: f32 = 3.0;
+
+/// The kinds of thing enemies throw
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProjectileKind {
+    /// Lobbed in a high arc by Hammer Bros
+    #[default]
+    Hammer,
+}
+
+/// What happens when a player's fireball meets an enemy projectile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClashOutcome {
+    /// They cancel each other out
+    BothDestroyed,
+    /// The projectile puts the fireball out and carries on
+    FireballDestroyed,
+    /// The fireball knocks the projectile out of the air and carries on
+    ProjectileDestroyed,
+    /// They fly straight through each other
+    PassThrough,
+}
+
+/// The clash outcome for each kind of enemy projectile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClashRules {
+    pub hammer: ClashOutcome,
+}
+
+impl Default for ClashRules {
+    fn default() -> Self {
+        Self { hammer: ClashOutcome::BothDestroyed }
+    }
+}
+
+impl ClashRules {
+    /// What happens when a fireball meets a projectile of this kind
+    pub fn outcome(&self, kind: ProjectileKind) -> ClashOutcome {
+        match kind {
+            ProjectileKind::Hammer => self.hammer,
+        }
+    }
+}
+
+/// A projectile thrown by an enemy
+#[derive(Debug, Clone, Default)]
+pub struct Projectile {
+    pub kind: ProjectileKind,
+    pub x: f32,
+    pub y: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    /// Time left before the projectile disappears
+    pub lifetime: f32,
+    /// What the projectile hits; enemies' own pass straight through them
+    pub collider: Collider,
+}
+
+impl Projectile {
+    /// Lob a hammer to the left or right
+    pub fn hammer(x: f32, y: f32, towards_right: bool) -> Self {
+        Self {
+            kind: ProjectileKind::Hammer,
+            x,
+            y,
+            velocity_x: if towards_right { HAMMER_SPEED } else { -HAMMER_SPEED },
+            velocity_y: -HAMMER_LAUNCH,
+            lifetime: HAMMER_LIFETIME,
+            collider: Collider::ENEMY_PROJECTILE,
+        }
+    }
+
+    /// Width and height of the projectile
+    pub fn size(&self) -> f32 {
+        match self.kind {
+            ProjectileKind::Hammer => HAMMER_SIZE,
+        }
+    }
+
+    /// Move the projectile along its arc. Returns false once it has run out of time.
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.lifetime -= delta_time;
+        self.velocity_y += HAMMER_GRAVITY * delta_time;
+        self.x += self.velocity_x * delta_time;
+        self.y += self.velocity_y * delta_time;
+        self.is_alive()
+    }
+
+    /// Whether the projectile is still in play
+    pub fn is_alive(&self) -> bool {
+        self.lifetime > 0.0
+    }
+
+    /// Take the projectile out of play
+    pub fn destroy(&mut self) {
+        self.lifetime = 0.0;
+    }
+
+    /// Check if the projectile overlaps with a rectangle
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        let size = self.size();
+        x < self.x + size && x + width > self.x && y < self.y + size && y + height > self.y
+    }
+
+    /// Draw the projectile, spinning as it flies
+    pub fn draw(&self) {
+        let size = self.size();
+        let (cx, cy) = (self.x + size / 2.0, self.y + size / 2.0);
+        let angle = self.lifetime * 12.0;
+        let (dx, dy) = (angle.cos() * size / 2.0, angle.sin() * size / 2.0);
+        // Handle, then the head at one end
+        draw_line(cx - dx, cy - dy, cx + dx, cy + dy, 2.0, BROWN);
+        draw_circle(cx + dx, cy + dy, size * 0.3, DARKGRAY);
+    }
+}
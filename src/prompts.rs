@@ -0,0 +1,68 @@
+//! Control Prompts
+//!
+//! On-screen hints name the controls for whichever input device the player
+//! is using: the bound keys on a keyboard, or button glyphs on a gamepad.
+//! The keyboard is the fallback whenever no gamepad is active.
+
+use crate::input::{Action, KeyBindings};
+
+/// The kind of device the player is currently playing with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+/// Button glyph for an action on a standard gamepad
+fn gamepad_glyph(action: Action) -> &'static str {
+    match action {
+        Action::MoveLeft => "D-Pad Left",
+        Action::MoveRight => "D-Pad Right",
+        Action::Jump => "(A)",
+        Action::Down => "D-Pad Down",
+        Action::Fire => "(X)",
+        Action::Grab => "(B)",
+        Action::Pause => "[Start]",
+        Action::Restart => "[Select]",
+    }
+}
+
+/// How to trigger an action on the given device, e.g. "Space or Up" or "(A)"
+pub fn action_prompt(action: Action, device: InputDevice, bindings: &KeyBindings) -> String {
+    match device {
+        InputDevice::Gamepad => gamepad_glyph(action).to_owned(),
+        InputDevice::Keyboard => {
+            let keys: Vec<String> = bindings.keys(action).iter().map(|key| format!("{:?}", key)).collect();
+            keys.join(" or ")
+        }
+    }
+}
+
+/// How to move left and right, pairing up the keys, e.g. "Left/Right or A/D"
+pub fn movement_prompt(device: InputDevice, bindings: &KeyBindings) -> String {
+    match device {
+        InputDevice::Gamepad => "the D-Pad".to_owned(),
+        InputDevice::Keyboard => {
+            let left = bindings.keys(Action::MoveLeft);
+            let right = bindings.keys(Action::MoveRight);
+            let pairs: Vec<String> = left.iter().zip(right).map(|(l, r)| format!("{:?}/{:?}", l, r)).collect();
+            pairs.join(" or ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_follows_active_device() {
+        let bindings = KeyBindings::default();
+        assert_eq!(action_prompt(Action::Jump, InputDevice::Keyboard, &bindings), "Space or Up or W");
+        assert_eq!(movement_prompt(InputDevice::Keyboard, &bindings), "Left/Right or A/D");
+
+        assert_eq!(action_prompt(Action::Jump, InputDevice::Gamepad, &bindings), "(A)");
+        assert_eq!(movement_prompt(InputDevice::Gamepad, &bindings), "the D-Pad");
+    }
+}
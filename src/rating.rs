@@ -0,0 +1,127 @@
+//! Star Ratings
+//!
+//! Finishing a level earns one to three stars for how well it went: picking
+//! up the level's coins, finishing within its par time and not dying each
+//! count towards the rating. The best rating for every level is kept in a
+//! `StarRecord` between sessions, so there's always a reason to replay.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What a run has to achieve for each criterion of the star rating
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StarThresholds {
+    /// Fraction of the level's coins to collect
+    pub coins: f32,
+    /// Seconds to finish within
+    pub par_time: f32,
+    /// Most deaths allowed
+    pub deaths: u32,
+}
+
+impl Default for StarThresholds {
+    fn default() -> Self {
+        Self { coins: 1.0, par_time: 30.0, deaths: 0 }
+    }
+}
+
+/// How a finished run went, as far as the star rating is concerned
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunResult {
+    /// Fraction of the level's coins collected, from 0 to 1
+    pub coins: f32,
+    /// Seconds taken to finish
+    pub time: f32,
+    pub deaths: u32,
+}
+
+/// Stars earned by a run: three for meeting every threshold, two for all
+/// but one, and one just for finishing
+pub fn star_rating(run: &RunResult, thresholds: &StarThresholds) -> u8 {
+    let met = [run.coins >= thresholds.coins, run.time <= thresholds.par_time, run.deaths <= thresholds.deaths];
+    match met.iter().filter(|met| **met).count() {
+        3 => 3,
+        2 => 2,
+        _ => 1,
+    }
+}
+
+/// Best star rating earned on each level, by level name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StarRecord {
+    best: BTreeMap<String, u8>,
+}
+
+impl StarRecord {
+    /// Best rating earned on a level so far, if it's been finished
+    pub fn best(&self, level: &str) -> Option<u8> {
+        self.best.get(level).copied()
+    }
+
+    /// Note a rating earned on a level, keeping it if it beats the best so far
+    pub fn record(&mut self, level: &str, stars: u8) {
+        let best = self.best.entry(level.to_owned()).or_default();
+        *best = (*best).max(stars);
+    }
+
+    /// Load the record from a JSON file, starting empty if there isn't one yet
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        match fs::read_to_string(filepath) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Save the record to a JSON file
+    pub fn save<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(filepath, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Draw a row of three stars centred on (x, y), the first `stars` of them filled in
+pub fn draw_stars(x: f32, y: f32, stars: u8) {
+    const RADIUS: f32 = 12.0;
+    const SPACING: f32 = 32.0;
+    for i in 0..3 {
+        let centre = vec2(x + (i as f32 - 1.0) * SPACING, y);
+        let color = if i < stars { GOLD } else { Color::new(0.3, 0.3, 0.3, 1.0) };
+        // Five points, alternating with an inner radius, filled as a fan around the centre
+        let point = |k: usize| {
+            let angle = -std::f32::consts::FRAC_PI_2 + k as f32 * std::f32::consts::PI / 5.0;
+            let radius = if k.is_multiple_of(2) { RADIUS } else { RADIUS * 0.45 };
+            centre + vec2(angle.cos(), angle.sin()) * radius
+        };
+        for k in 0..10 {
+            draw_triangle(centre, point(k), point(k + 1), color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_star_rating_rewards_complete_fast_runs() {
+        let thresholds = StarThresholds { coins: 1.0, par_time: 20.0, deaths: 0 };
+        let perfect = RunResult { coins: 1.0, time: 12.5, deaths: 0 };
+        let slow_partial = RunResult { coins: 0.4, time: 45.0, deaths: 2 };
+        let one_slip = RunResult { deaths: 1, ..perfect };
+
+        assert_eq!(star_rating(&perfect, &thresholds), 3);
+        assert_eq!(star_rating(&one_slip, &thresholds), 2);
+        assert_eq!(star_rating(&slow_partial, &thresholds), 1);
+
+        let mut record = StarRecord::default();
+        record.record("Simple Level", 2);
+        record.record("Simple Level", 1);
+        assert_eq!(record.best("Simple Level"), Some(2));
+        assert_eq!(record.best("Castle"), None);
+    }
+}
@@ -0,0 +1,167 @@
+//! Reachability Analysis
+//!
+//! Checks whether a level can be played through by flood-filling the surfaces
+//! the player can stand on. Starting from the spawn point, a surface counts as
+//! reachable if a full-height jump (or a spring launch) from one already
+//! reached can land on it, steering freely in the air at running speed. Coins
+//! and the goal are reachable if such a jump passes through them.
+//!
+//! The analysis is optimistic: it ignores ceilings, walls in the way and
+//! enemies, so "unreachable" is a firm verdict while "reachable" means no
+//! obvious problem was found.
+
+/// Time step used when tracing jump arcs
+const TRACE_STEP: f32 = 1.0 / 120.0;
+/// Longest jump or fall traced
+const MAX_AIR_TIME: f32 = 4.0;
+
+/// The player's movement abilities
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MovementLimits {
+    pub gravity: f32,
+    /// Upward speed of a normal jump
+    pub jump_velocity: f32,
+    /// Upward speed of the strongest spring launch
+    pub spring_velocity: f32,
+    /// Horizontal speed, on the ground or in the air
+    pub run_speed: f32,
+    pub player_width: f32,
+    pub player_height: f32,
+}
+
+/// A rectangle, used for surfaces and targets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+}
+
+/// The top of something solid the player can stand on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Surface {
+    pub rect: Rect,
+    /// Standing on it launches the player like a spring
+    pub spring: bool,
+}
+
+/// What a reachability check found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reachability {
+    pub goal_reachable: bool,
+    /// Indices of coins no jump passes through
+    pub unreachable_coins: Vec<usize>,
+}
+
+impl Reachability {
+    /// Whether the goal and every coin can be reached
+    pub fn all_reachable(&self) -> bool {
+        self.goal_reachable && self.unreachable_coins.is_empty()
+    }
+}
+
+/// A jump in progress: the range of x the player's left edge starts from,
+/// the height of their feet and their upward speed
+#[derive(Debug, Clone, Copy)]
+struct Launch {
+    left: f32,
+    right: f32,
+    feet: f32,
+    velocity: f32,
+}
+
+impl Launch {
+    /// Jump from anywhere on a surface
+    fn from_surface(surface: &Surface, limits: &MovementLimits) -> Self {
+        Self {
+            left: surface.rect.x - limits.player_width,
+            right: surface.rect.x + surface.rect.width,
+            feet: surface.rect.y,
+            velocity: if surface.spring { limits.spring_velocity } else { limits.jump_velocity },
+        }
+    }
+
+    /// Call `visit` with the player's possible left edge range, feet height
+    /// and feet height one step earlier at each point along the arc
+    fn trace(&self, limits: &MovementLimits, lowest: f32, mut visit: impl FnMut(f32, f32, f32, f32) -> bool) -> bool {
+        let mut previous_feet = self.feet;
+        let mut t = TRACE_STEP;
+        while t <= MAX_AIR_TIME && previous_feet <= lowest {
+            let feet = self.feet - (self.velocity * t - limits.gravity * t * t / 2.0);
+            let reach = limits.run_speed * t;
+            if visit(self.left - reach, self.right + reach, feet, previous_feet) {
+                return true;
+            }
+            previous_feet = feet;
+            t += TRACE_STEP;
+        }
+        false
+    }
+
+    /// Whether the arc can come down on top of a surface
+    fn lands_on(&self, surface: &Surface, limits: &MovementLimits, lowest: f32) -> bool {
+        let target = surface.rect;
+        self.trace(limits, lowest, |left, right, feet, previous_feet| {
+            let crosses_top = previous_feet < target.y && feet >= target.y;
+            crosses_top && overlaps(left, right, target.x - limits.player_width, target.x + target.width)
+        })
+    }
+
+    /// Whether the player's body passes through a rectangle somewhere along the arc
+    fn touches(&self, target: &Rect, limits: &MovementLimits, lowest: f32) -> bool {
+        self.trace(limits, lowest, |left, right, feet, _| {
+            let vertical = feet > target.y && feet - limits.player_height < target.y + target.height;
+            vertical && overlaps(left, right, target.x - limits.player_width, target.x + target.width)
+        })
+    }
+}
+
+/// Whether two open ranges overlap
+fn overlaps(a_start: f32, a_end: f32, b_start: f32, b_end: f32) -> bool {
+    a_start < b_end && b_start < a_end
+}
+
+/// Flood-fill the surfaces reachable from the spawn point, then check the goal and coins
+pub fn analyze(
+    surfaces: &[Surface],
+    spawn: (f32, f32),
+    goal: Rect,
+    coins: &[Rect],
+    limits: &MovementLimits,
+) -> Reachability {
+    // Anything that falls below every surface is gone for good
+    let lowest = surfaces.iter().map(|s| s.rect.y).fold(spawn.1 + limits.player_height, f32::max);
+
+    // The player starts by dropping from the spawn point
+    let mut launches = vec![Launch {
+        left: spawn.0,
+        right: spawn.0,
+        feet: spawn.1 + limits.player_height,
+        velocity: 0.0,
+    }];
+    let mut reached = vec![false; surfaces.len()];
+    let mut next = 0;
+    while next < launches.len() {
+        let launch = launches[next];
+        next += 1;
+        for (i, surface) in surfaces.iter().enumerate() {
+            if !reached[i] && launch.lands_on(surface, limits, lowest) {
+                reached[i] = true;
+                launches.push(Launch::from_surface(surface, limits));
+            }
+        }
+    }
+
+    let reachable = |target: &Rect| launches.iter().any(|launch| launch.touches(target, limits, lowest));
+    Reachability {
+        goal_reachable: reachable(&goal),
+        unreachable_coins: coins.iter().enumerate().filter(|(_, coin)| !reachable(coin)).map(|(i, _)| i).collect(),
+    }
+}
@@ -0,0 +1,267 @@
+//! Shareable Replays
+//!
+//! A `Replay` packs everything needed to watch a run again into one small
+//! file: where its level came from, the seed its random drops came from, how
+//! the player was set to move, and the input for every physics step. The
+//! level is deterministic given those, so
+//! stepping a fresh copy through the inputs reproduces the run exactly, on
+//! any machine. Inputs are stored as run-length encoded button masks, since
+//! the same buttons are usually held for many steps in a row.
+
+use std::fs;
+use std::path::Path;
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::frame_limiter::{FrameLimiter, FrameSettings};
+use crate::generator::GeneratorKind;
+use crate::input::InputState;
+use crate::simple_level::{PhysicsConfig, SimpleLevel};
+use crate::simulation::SIMULATION_DT;
+use crate::viewport::{VIRTUAL_HEIGHT, Viewport};
+
+/// Where a finished run's replay is saved
+pub const REPLAY_PATH: &str = "last_replay.json";
+
+/// Pack the buttons a replay keeps into a bit mask
+fn buttons(input: &InputState) -> u8 {
+    [input.left, input.right, input.jump_pressed, input.jump_held, input.down, input.fire_pressed, input.grab_held]
+        .iter()
+        .enumerate()
+        .fold(0, |mask, (bit, &held)| mask | (held as u8) << bit)
+}
+
+/// Unpack a bit mask made by `buttons`
+fn input_from(mask: u8) -> InputState {
+    let held = |bit: u8| mask & (1 << bit) != 0;
+    InputState {
+        left: held(0),
+        right: held(1),
+        jump_pressed: held(2),
+        jump_held: held(3),
+        down: held(4),
+        fire_pressed: held(5),
+        grab_held: held(6),
+        ..Default::default()
+    }
+}
+
+/// Where a level came from, so it can be built again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LevelSource {
+    /// The level built into the game
+    BuiltIn,
+    /// A level file, by path
+    File(String),
+    /// A level laid out by a built-in generator
+    Generated { generator: GeneratorKind, seed: u64 },
+}
+
+impl LevelSource {
+    /// A fresh copy of the level
+    pub fn build(&self) -> Result<SimpleLevel, Box<dyn std::error::Error>> {
+        match self {
+            LevelSource::BuiltIn => Ok(SimpleLevel::new()),
+            LevelSource::File(path) => SimpleLevel::load(path),
+            LevelSource::Generated { generator, seed } => Ok(SimpleLevel::generated(*generator, *seed)),
+        }
+    }
+}
+
+/// A recorded run that can be saved, shared and played back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// Where the run's level came from
+    pub level: LevelSource,
+    /// Seed for the level's random number generator
+    pub seed: u64,
+    /// How the player moved, with any movement preset or console tweaks
+    pub physics: PhysicsConfig,
+    /// Physics iterations each step was split into
+    pub substeps: u32,
+    /// Runs of identical steps, as (number of steps, button mask)
+    inputs: Vec<(u32, u8)>,
+}
+
+impl Replay {
+    /// A replay of the given inputs, one per physics step, played with default physics
+    pub fn new(level: LevelSource, seed: u64, inputs: &[InputState]) -> Self {
+        let mut runs: Vec<(u32, u8)> = Vec::new();
+        for mask in inputs.iter().map(buttons) {
+            match runs.last_mut() {
+                Some((count, last)) if *last == mask => *count += 1,
+                _ => runs.push((1, mask)),
+            }
+        }
+        Self { level, seed, physics: PhysicsConfig::default(), substeps: 1, inputs: runs }
+    }
+
+    /// A replay of everything played on a level since its run started, or
+    /// `None` if the level was made in code and can't be built again, or the
+    /// run went on too long to record
+    pub fn record(level: &SimpleLevel) -> Option<Self> {
+        let source = level.source.clone()?;
+        let replay = Self::new(source, level.seed(), level.recorded_inputs()?);
+        Some(Self { physics: level.player.physics, substeps: level.settings.substeps, ..replay })
+    }
+
+    /// Every step's input, in order
+    pub fn inputs(&self) -> Vec<InputState> {
+        self.inputs.iter().flat_map(|&(count, mask)| std::iter::repeat_n(input_from(mask), count as usize)).collect()
+    }
+
+    /// Number of physics steps in the run
+    pub fn steps(&self) -> usize {
+        self.inputs.iter().map(|&(count, _)| count as usize).sum()
+    }
+
+    /// A fresh copy of the run's level, seeded and set up the way the run was
+    pub fn start_level(&self) -> Result<SimpleLevel, Box<dyn std::error::Error>> {
+        let mut level = self.level.build()?;
+        level.player.physics = self.physics;
+        level.settings.substeps = self.substeps;
+        level.reseed(self.seed);
+        Ok(level)
+    }
+
+    /// Load a replay from a JSON file
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(filepath)?)?)
+    }
+
+    /// Save the replay to a JSON file, kept compact for sharing
+    pub fn save<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(filepath, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Steps a fresh level through a replay's inputs
+pub struct ReplayPlayer {
+    level: SimpleLevel,
+    inputs: Vec<InputState>,
+    step: usize,
+}
+
+impl ReplayPlayer {
+    /// A player at the start of the replay
+    pub fn new(replay: &Replay) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self { level: replay.start_level()?, inputs: replay.inputs(), step: 0 })
+    }
+
+    /// The level as the replay has played it so far
+    pub fn level(&self) -> &SimpleLevel {
+        &self.level
+    }
+
+    /// Whether every recorded step has been played
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.inputs.len()
+    }
+
+    /// Play the next recorded step, returning false once there are none left
+    pub fn advance(&mut self) -> bool {
+        let Some(input) = self.inputs.get(self.step) else { return false };
+        self.level.step(input, SIMULATION_DT);
+        self.step += 1;
+        true
+    }
+
+    /// Play every remaining step
+    pub fn play_to_end(&mut self) {
+        while self.advance() {}
+    }
+}
+
+/// Watch a replay at the speed it was recorded, until it ends or ESC is pressed
+pub async fn run_replay(replay: &Replay, frame_settings: FrameSettings) -> Result<(), Box<dyn std::error::Error>> {
+    let mut limiter = FrameLimiter::new(frame_settings.target_fps);
+    let viewport = Viewport::default();
+    let mut player = ReplayPlayer::new(replay)?;
+    let mut time = 0.0;
+
+    while !is_key_pressed(KeyCode::Escape) {
+        limiter.begin_frame();
+        time += get_frame_time();
+        while time >= SIMULATION_DT {
+            time -= SIMULATION_DT;
+            player.advance();
+        }
+
+        let level = player.level();
+        let (zoom, top) = level.camera_zoom();
+        let camera = viewport.begin_zoomed(zoom, top);
+        level.draw_world(&camera);
+        viewport.begin();
+        level.draw_overlay();
+        let label = if player.is_finished() { "REPLAY OVER - press ESC" } else { "REPLAY" };
+        draw_text(label, 10.0, VIRTUAL_HEIGHT - 12.0, 24.0, YELLOW);
+        viewport.end();
+
+        limiter.end_frame();
+        next_frame().await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_level::MovementPreset;
+    use crate::simulation::{RECORDING_SCRIPT, RECORDING_STEPS, SimulationResult, parse_script};
+
+    /// Save a replay to a temporary file and load it back
+    fn round_trip(replay: &Replay, name: &str) -> Replay {
+        let path = std::env::temp_dir().join(format!("mario_replay_{}_{}.json", name, std::process::id()));
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn test_saved_replay_reproduces_the_run() {
+        let mut level = SimpleLevel::new();
+        level.reseed(42);
+        for input in parse_script(RECORDING_SCRIPT).unwrap().iter().take(RECORDING_STEPS) {
+            level.step(input, SIMULATION_DT);
+        }
+        let recorded = SimulationResult::capture(&level, RECORDING_STEPS);
+
+        let replay = Replay::record(&level).unwrap();
+        assert_eq!(replay.steps(), RECORDING_STEPS);
+        let loaded = round_trip(&replay, "built_in");
+        assert_eq!(loaded, replay);
+
+        let mut player = ReplayPlayer::new(&loaded).unwrap();
+        assert_eq!(player.level().seed(), 42);
+        player.play_to_end();
+        assert!(player.is_finished());
+        assert_eq!(SimulationResult::capture(player.level(), RECORDING_STEPS), recorded);
+    }
+    #[test]
+    fn test_replay_keeps_a_generated_level_and_modern_physics() {
+        let mut level = SimpleLevel::generated(GeneratorKind::Staircase, 7);
+        MovementPreset::Modern.apply(&mut level.player.physics);
+        level.player.physics.run_speed *= 1.25;
+        level.settings.substeps = 2;
+        level.reseed(42);
+        for input in parse_script(RECORDING_SCRIPT).unwrap().iter().take(RECORDING_STEPS) {
+            level.step(input, SIMULATION_DT);
+        }
+        let recorded = SimulationResult::capture(&level, RECORDING_STEPS);
+
+        let loaded = round_trip(&Replay::record(&level).unwrap(), "generated");
+        assert_eq!(loaded.level, LevelSource::Generated { generator: GeneratorKind::Staircase, seed: 7 });
+        assert_eq!((loaded.physics, loaded.substeps), (level.player.physics, 2));
+
+        let mut player = ReplayPlayer::new(&loaded).unwrap();
+        player.play_to_end();
+        assert_eq!(player.level().player.physics, level.player.physics);
+        assert_eq!(SimulationResult::capture(player.level(), RECORDING_STEPS), recorded);
+
+        // Levels made from data in code can't be built again
+        assert!(Replay::record(&SimpleLevel::from_data(&Default::default())).is_none());
+    }
+}
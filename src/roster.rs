@@ -0,0 +1,166 @@
+//! Enemy Roster
+//!
+//! Enemy stats live in data rather than code. A roster maps enemy type names
+//! such as `"goomba"` to their behavior, speed, size, health and point value,
+//! and level files place enemies by name. Designers can tune a roster file
+//! without recompiling.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::level_data::EnemySpawn;
+use crate::loot::LootTable;
+use crate::simple_level::{Enemy, EnemyKind};
+
+/// Tunable stats for one type of enemy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemyStats {
+    /// How the enemy behaves when hit
+    pub behavior: EnemyKind,
+    /// Patrol speed. Splitters use this at their smallest size and slow down as they grow.
+    pub speed: f32,
+    /// Width and height. Splitters use this at their smallest size.
+    pub size: f32,
+    /// Hits it takes to defeat
+    pub health: u8,
+    /// Score for defeating it
+    pub points: u32,
+    /// What it might drop when defeated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loot: Option<LootTable>,
+}
+
+/// The named enemy types levels can place
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EnemyRoster {
+    types: BTreeMap<String, EnemyStats>,
+}
+
+impl Default for EnemyRoster {
+    /// The built-in enemy types
+    fn default() -> Self {
+        let mut types = BTreeMap::new();
+        types.insert(
+            "goomba".to_owned(),
+            EnemyStats { behavior: EnemyKind::Goomba, speed: 30.0, size: 16.0, health: 1, points: 200, loot: None },
+        );
+        types.insert(
+            "splitter".to_owned(),
+            EnemyStats { behavior: EnemyKind::Splitter, speed: 90.0, size: 16.0, health: 1, points: 200, loot: None },
+        );
+        types.insert(
+            "armored".to_owned(),
+            EnemyStats { behavior: EnemyKind::Armored, speed: 20.0, size: 16.0, health: 3, points: 200, loot: None },
+        );
+        types.insert(
+            "hammer_bro".to_owned(),
+            EnemyStats { behavior: EnemyKind::HammerBro, speed: 20.0, size: 16.0, health: 1, points: 1000, loot: None },
+        );
+        Self { types }
+    }
+}
+
+impl EnemyRoster {
+    /// Parse a roster from a JSON string
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serialize the roster to a JSON string
+    pub fn to_json(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Load a roster from a JSON file
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_json(&fs::read_to_string(filepath)?)
+    }
+
+    /// Stats for an enemy type
+    pub fn get(&self, name: &str) -> Option<&EnemyStats> {
+        self.types.get(name)
+    }
+
+    /// Create an enemy of the named type, or `None` if the roster doesn't have it
+    pub fn spawn(&self, name: &str, x: f32, y: f32, patrol_start: f32, patrol_end: f32, size_tier: u8) -> Option<Enemy> {
+        let stats = self.get(name)?;
+        let mut enemy = match stats.behavior {
+            EnemyKind::Goomba | EnemyKind::Armored | EnemyKind::HammerBro => Enemy::new(x, y, patrol_start, patrol_end),
+            EnemyKind::Splitter => Enemy::splitter(x, y, patrol_start, patrol_end, size_tier),
+            EnemyKind::Mirror => Enemy::mirror(x, y),
+        };
+        // Splitters grow and slow down with their tier, the same way as the built-in ones
+        let tier = enemy.size_tier as f32;
+        enemy.kind = stats.behavior;
+        enemy.width = stats.size * (tier + 1.0) / 2.0;
+        enemy.height = enemy.width;
+        enemy.speed = stats.speed / tier;
+        enemy.health = stats.health;
+        enemy.max_health = stats.health;
+        enemy.points = stats.points;
+        enemy.loot = stats.loot.clone();
+        Some(enemy)
+    }
+
+    /// Create the enemy a level places: by type name if it has one, otherwise
+    /// the built-in enemy of its kind
+    pub fn spawn_from(&self, spawn: &EnemySpawn) -> Option<Enemy> {
+        let (x, y, start, end) = (spawn.x, spawn.y, spawn.patrol_start, spawn.patrol_end);
+        let enemy = match &spawn.name {
+            Some(name) => self.spawn(name, x, y, start, end, spawn.size_tier),
+            None => Some(match spawn.kind {
+                EnemyKind::Goomba => Enemy::new(x, y, start, end),
+                EnemyKind::Splitter => Enemy::splitter(x, y, start, end, spawn.size_tier),
+                EnemyKind::Armored => Enemy::armored(x, y, start, end),
+                EnemyKind::HammerBro => Enemy::hammer_bro(x, y, start, end),
+                EnemyKind::Mirror => Enemy::mirror(x, y),
+            }),
+        };
+        enemy.map(|enemy| Enemy {
+            boss: spawn.boss.clone(),
+            loot: spawn.loot.clone().or(enemy.loot.clone()),
+            ..enemy.with_path(spawn.path.clone())
+        })
+    }
+
+    /// Enemy type names used by a level's spawns that aren't in the roster
+    pub fn missing_types<'a>(&self, spawns: &'a [EnemySpawn]) -> Vec<&'a str> {
+        spawns
+            .iter()
+            .filter_map(|spawn| spawn.name.as_deref())
+            .filter(|name| !self.types.contains_key(*name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawning_by_name_uses_roster_stats() {
+        let json = r#"{
+            "goomba": { "behavior": "Goomba", "speed": 30.0, "size": 16.0, "health": 1, "points": 200 },
+            "speedy": { "behavior": "Goomba", "speed": 75.0, "size": 12.0, "health": 1, "points": 500 }
+        }"#;
+        let roster = EnemyRoster::from_json(json).unwrap();
+
+        let enemy = roster.spawn("speedy", 10.0, 20.0, 0.0, 100.0, 1).unwrap();
+        assert_eq!(enemy.speed, 75.0);
+        assert_eq!(enemy.points, 500);
+        assert_eq!((enemy.width, enemy.height), (12.0, 12.0));
+        assert!(roster.spawn("koopa", 0.0, 0.0, 0.0, 0.0, 1).is_none());
+
+        // Levels place enemies by type name
+        let spawn: EnemySpawn = serde_json::from_str(
+            r#"{ "type": "speedy", "x": 10.0, "y": 20.0, "patrol_start": 0.0, "patrol_end": 100.0 }"#,
+        )
+        .unwrap();
+        assert_eq!(roster.spawn_from(&spawn).unwrap().points, 500);
+        assert!(roster.missing_types(&[spawn]).is_empty());
+    }
+}
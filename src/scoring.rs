@@ -0,0 +1,72 @@
+//! Scoring Rules
+//!
+//! Every point value the level awards comes from one `ScoringConfig`, so rule
+//! variants such as a hard mode that pays out less only need a different
+//! config. Enemy point values come from the roster and are scaled here, and
+//! stomping several enemies without landing multiplies them by the combo
+//! multipliers.
+
+/// Point values and scaling applied to everything the level scores
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoringConfig {
+    /// Points for each coin, picked up or knocked out of a block
+    pub coin: u32,
+    /// Points for each big coin
+    pub big_coin: u32,
+    /// Points for each second left on the clock at the finish
+    pub time_bonus: u32,
+    /// Points for each coin collected, counted again at the finish
+    pub coin_bonus: u32,
+    /// Multiplier for the first, second, ... enemy stomped without landing;
+    /// stomps past the end keep the last one
+    pub combo_multipliers: [u32; 4],
+    /// Scale applied to every award, e.g. 0.5 for a stingier hard mode
+    pub multiplier: f32,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            coin: 100,
+            big_coin: 2000,
+            time_bonus: 50,
+            coin_bonus: 100,
+            combo_multipliers: [1, 2, 4, 8],
+            multiplier: 1.0,
+        }
+    }
+}
+
+impl ScoringConfig {
+    /// Points for something worth `base` before scaling
+    pub fn award(&self, base: u32) -> u32 {
+        (base as f32 * self.multiplier).round() as u32
+    }
+
+    /// Points for a coin
+    pub fn coin_points(&self) -> u32 {
+        self.award(self.coin)
+    }
+
+    /// Points for a big coin
+    pub fn big_coin_points(&self) -> u32 {
+        self.award(self.big_coin)
+    }
+
+    /// Points for an enemy worth `base`, stomped `combo`th in a row without landing
+    pub fn stomp_points(&self, base: u32, combo: u32) -> u32 {
+        let last = self.combo_multipliers.len() - 1;
+        let step = (combo.max(1) as usize - 1).min(last);
+        self.award(base * self.combo_multipliers[step])
+    }
+
+    /// Points for each second left at the finish
+    pub fn time_bonus_points(&self) -> u32 {
+        self.award(self.time_bonus)
+    }
+
+    /// Points for each coin counted at the finish
+    pub fn coin_bonus_points(&self) -> u32 {
+        self.award(self.coin_bonus)
+    }
+}
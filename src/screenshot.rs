@@ -1,31 +1,223 @@
-/// Screenshot functionality for the Mario game
-/// This module provides functions to capture and save screenshots of the game
+//! Screenshot functionality for the Mario game
+//! This module provides functions to capture and save screenshots of the game
 
 use macroquad::prelude::*;
-use std::path::Path;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::fs::File;
+use std::time::{SystemTime, UNIX_EPOCH};
 use gif::{Encoder, Frame, Repeat};
 
+/// Bytes per pixel in the RGBA screen data
+const BYTES_PER_PIXEL: usize = 4;
+
+/// Screen data whose length doesn't fit the reported image size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferSizeError {
+    pub width: usize,
+    pub height: usize,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for BufferSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "screen data for a {}x{} image should be {} bytes of RGBA, but got {}",
+            self.width, self.height, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for BufferSizeError {}
+
+/// Anything that can go wrong capturing or saving screenshots and recordings
+#[derive(Debug)]
+pub enum ScreenshotError {
+    /// The screen data didn't match the reported image size
+    BufferSizeMismatch(BufferSizeError),
+    /// The screen was resized partway through a recording
+    FrameSizeChanged { from: (u16, u16), to: (u16, u16) },
+    /// A capture region that doesn't lie within the screen
+    RegionOutOfBounds { region: CaptureRegion, screen: (u32, u32) },
+    /// Saving a GIF before any frames were captured
+    NoFrames,
+    /// Creating or writing the output file failed
+    Io(io::Error),
+    /// Encoding the PNG failed
+    Png(image::ImageError),
+    /// Encoding the GIF failed
+    Encode(gif::EncodingError),
+}
+
+impl fmt::Display for ScreenshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenshotError::BufferSizeMismatch(error) => error.fmt(f),
+            ScreenshotError::FrameSizeChanged { from, to } => write!(
+                f,
+                "screen size changed from {}x{} to {}x{} during recording",
+                from.0, from.1, to.0, to.1
+            ),
+            ScreenshotError::RegionOutOfBounds { region, screen } => write!(
+                f,
+                "capture region {}x{} at ({}, {}) doesn't fit within the {}x{} screen",
+                region.width, region.height, region.x, region.y, screen.0, screen.1
+            ),
+            ScreenshotError::NoFrames => write!(f, "no frames captured"),
+            ScreenshotError::Io(error) => write!(f, "couldn't write the file: {}", error),
+            ScreenshotError::Png(error) => write!(f, "couldn't encode the PNG: {}", error),
+            ScreenshotError::Encode(error) => write!(f, "couldn't encode the GIF: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ScreenshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScreenshotError::BufferSizeMismatch(error) => Some(error),
+            ScreenshotError::Io(error) => Some(error),
+            ScreenshotError::Png(error) => Some(error),
+            ScreenshotError::Encode(error) => Some(error),
+            ScreenshotError::FrameSizeChanged { .. }
+            | ScreenshotError::RegionOutOfBounds { .. }
+            | ScreenshotError::NoFrames => None,
+        }
+    }
+}
+
+impl From<BufferSizeError> for ScreenshotError {
+    fn from(error: BufferSizeError) -> Self {
+        ScreenshotError::BufferSizeMismatch(error)
+    }
+}
+
+impl From<io::Error> for ScreenshotError {
+    fn from(error: io::Error) -> Self {
+        ScreenshotError::Io(error)
+    }
+}
+
+impl From<image::ImageError> for ScreenshotError {
+    fn from(error: image::ImageError) -> Self {
+        ScreenshotError::Png(error)
+    }
+}
+
+impl From<gif::EncodingError> for ScreenshotError {
+    fn from(error: gif::EncodingError) -> Self {
+        ScreenshotError::Encode(error)
+    }
+}
+
+/// Check raw RGBA screen data against the image size and return it tightly
+/// packed. Rows padded out to a wider stride have the padding removed; any
+/// other size mismatch is an error.
+pub fn packed_rgba(bytes: Vec<u8>, width: usize, height: usize) -> Result<Vec<u8>, BufferSizeError> {
+    let row_len = width * BYTES_PER_PIXEL;
+    let expected = row_len * height;
+    if bytes.len() == expected {
+        return Ok(bytes);
+    }
+
+    let error = BufferSizeError { width, height, expected, actual: bytes.len() };
+    if height == 0 || !bytes.len().is_multiple_of(height) {
+        return Err(error);
+    }
+    let stride = bytes.len() / height;
+    if stride < row_len {
+        return Err(error);
+    }
+
+    // Each row carries some padding at the end; drop it
+    Ok(bytes.chunks_exact(stride).flat_map(|row| &row[..row_len]).copied().collect())
+}
+
+/// A rectangle of the screen to capture, in pixels from the top left
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CaptureRegion {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Parse a region written as `x,y,width,height`
+    pub fn parse(text: &str) -> Option<Self> {
+        let values: Vec<u32> = text.split(',').map(|part| part.trim().parse().ok()).collect::<Option<_>>()?;
+        match values[..] {
+            [x, y, width, height] => Some(Self::new(x, y, width, height)),
+            _ => None,
+        }
+    }
+
+    /// Whether the region is non-empty and lies entirely within a screen of this size
+    pub fn fits(&self, width: u32, height: u32) -> bool {
+        let right = self.x.checked_add(self.width);
+        let bottom = self.y.checked_add(self.height);
+        self.width > 0
+            && self.height > 0
+            && right.is_some_and(|right| right <= width)
+            && bottom.is_some_and(|bottom| bottom <= height)
+    }
+}
+
+/// Cut a region out of tightly packed RGBA data, checking it's within the image
+pub fn crop_rgba(bytes: &[u8], width: u32, height: u32, region: CaptureRegion) -> Result<Vec<u8>, ScreenshotError> {
+    if !region.fits(width, height) {
+        return Err(ScreenshotError::RegionOutOfBounds { region, screen: (width, height) });
+    }
+    let row_len = width as usize * BYTES_PER_PIXEL;
+    let start = region.x as usize * BYTES_PER_PIXEL;
+    let end = start + region.width as usize * BYTES_PER_PIXEL;
+    Ok(bytes
+        .chunks_exact(row_len)
+        .skip(region.y as usize)
+        .take(region.height as usize)
+        .flat_map(|row| &row[start..end])
+        .copied()
+        .collect())
+}
+
 /// Capture the current screen and save it as a PNG file
 /// 
 /// # Arguments
 /// * `filepath` - The path where the screenshot should be saved
 /// 
 /// # Returns
-/// * `Result<(), Box<dyn std::error::Error>>` - Ok if successful, Err if failed
-pub fn capture_screenshot<P: AsRef<Path>>(filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+/// * `Result<(), ScreenshotError>` - Ok if successful, Err if failed
+pub fn capture_screenshot<P: AsRef<Path>>(filepath: P) -> Result<(), ScreenshotError> {
+    capture_screenshot_region(filepath, None)
+}
+
+/// Capture part of the screen, or all of it when `region` is `None`, and
+/// save it as a PNG file. The region must lie within the screen.
+pub fn capture_screenshot_region<P: AsRef<Path>>(filepath: P, region: Option<CaptureRegion>) -> Result<(), ScreenshotError> {
     // Get screen data as macroquad Image
     let screen_image = get_screen_data();
     
     // Get raw bytes from the image
     let width = screen_image.width() as u32;
     let height = screen_image.height() as u32;
-    let bytes = screen_image.bytes.clone();
+    let bytes = packed_rgba(screen_image.bytes, width as usize, height as usize)?;
+
+    // Keep just the requested part of the screen
+    let (width, height, bytes) = match region {
+        Some(region) => (region.width, region.height, crop_rgba(&bytes, width, height, region)?),
+        None => (width, height, bytes),
+    };
     
-    // Create image buffer from screen data
+    // Create image buffer from screen data; `packed_rgba` already checked the size
     use image::{ImageBuffer, Rgba};
     let img_buffer = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, bytes)
-        .ok_or("Failed to create image buffer from screen data")?;
+        .expect("packed screen data fits the image size");
     
     // Save as PNG
     img_buffer.save(filepath)?;
@@ -33,12 +225,208 @@ pub fn capture_screenshot<P: AsRef<Path>>(filepath: P) -> Result<(), Box<dyn std
     Ok(())
 }
 
+/// What the screenshot generator should capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureConfig {
+    /// Play interactively for this many seconds while recording; `None`
+    /// captures a single static frame of the initial state
+    pub duration: Option<f32>,
+    /// Where the final screenshot is saved
+    pub screenshot_path: String,
+    /// Where the recording is saved when playing
+    pub gif_path: String,
+    /// Milliseconds between recorded frames
+    pub frame_interval_ms: u16,
+    /// Part of the screen the screenshot keeps; `None` keeps all of it
+    pub region: Option<CaptureRegion>,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            duration: None,
+            screenshot_path: "assets/screenshot.png".to_owned(),
+            gif_path: "assets/recording.gif".to_owned(),
+            frame_interval_ms: 100,
+            region: None,
+        }
+    }
+}
+
+impl CaptureConfig {
+    /// Read the config from command line arguments
+    /// (`--duration <secs>`, `--output <png>`, `--gif <gif>`, `--region <x,y,w,h>`)
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut config = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--duration" => {
+                    let secs: f32 = value()?.parse().map_err(|_| "--duration must be a number of seconds".to_owned())?;
+                    if secs <= 0.0 {
+                        return Err("--duration must be positive".to_owned());
+                    }
+                    config.duration = Some(secs);
+                }
+                "--output" => config.screenshot_path = value()?,
+                "--gif" => config.gif_path = value()?,
+                "--region" => {
+                    let region = CaptureRegion::parse(&value()?).ok_or("--region must be x,y,width,height".to_owned())?;
+                    config.region = Some(region);
+                }
+                other => return Err(format!("unknown argument '{}'", other)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Shrink an RGB image by `scale` with a box filter: each output pixel is
+/// the average of the block of source pixels it covers.
+/// Returns the pixels along with the new width and height.
+pub fn downscale_rgb(pixels: &[u8], width: usize, height: usize, scale: f32) -> (Vec<u8>, usize, usize) {
+    let new_width = ((width as f32 * scale).round() as usize).clamp(1, width.max(1));
+    let new_height = ((height as f32 * scale).round() as usize).clamp(1, height.max(1));
+    let mut output = Vec::with_capacity(new_width * new_height * 3);
+
+    // Source range covered by output index `i` along an axis, at least one pixel wide
+    let span = |i: usize, size: usize, new_size: usize| {
+        let start = i * size / new_size;
+        (start, ((i + 1) * size / new_size).max(start + 1))
+    };
+
+    for dy in 0..new_height {
+        let (y0, y1) = span(dy, height, new_height);
+        for dx in 0..new_width {
+            let (x0, x1) = span(dx, width, new_width);
+            let mut sum = [0u32; 3];
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let i = (y * width + x) * 3;
+                    for (channel, total) in sum.iter_mut().enumerate() {
+                        *total += pixels[i + channel] as u32;
+                    }
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u32;
+            output.extend(sum.iter().map(|total| (total / count) as u8));
+        }
+    }
+
+    (output, new_width, new_height)
+}
+
+/// Key that saves a screenshot during play
+pub const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+
+/// Directory hotkey screenshots are saved in
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Seconds the confirmation stays on screen after a hotkey screenshot
+const NOTICE_TIME: f32 = 2.0;
+
+/// Seconds the white flash after a hotkey screenshot takes to fade
+const FLASH_TIME: f32 = 0.25;
+
+/// A UTC date and time like `20240131_235959_250`, down to the millisecond,
+/// that sorts in the order the times happened
+pub fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (days, of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Days since 1970 to a calendar date, counting in 400-year eras from March
+    // so leap days fall at the end of each year
+    let days = days as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}_{:02}{:02}{:02}_{:03}",
+        year,
+        month,
+        day,
+        of_day / 3600,
+        of_day / 60 % 60,
+        of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// A path in `dir` for a screenshot taken at `time` that isn't taken yet,
+/// numbering it if another was saved in the same millisecond
+pub fn timestamped_path(dir: &Path, time: SystemTime) -> PathBuf {
+    let stamp = timestamp(time);
+    let mut path = dir.join(format!("screenshot_{}.png", stamp));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("screenshot_{}_{}.png", stamp, n));
+        n += 1;
+    }
+    path
+}
+
+/// Capture the screen to a new timestamped PNG in `dir`, creating the
+/// directory if needed, and return where it was saved
+pub fn save_timestamped_screenshot<P: AsRef<Path>>(dir: P) -> Result<PathBuf, ScreenshotError> {
+    std::fs::create_dir_all(&dir)?;
+    let path = timestamped_path(dir.as_ref(), SystemTime::now());
+    capture_screenshot(&path)?;
+    Ok(path)
+}
+
+/// Flash and confirmation shown for a moment after a hotkey screenshot
+pub struct ScreenshotNotice {
+    message: String,
+    succeeded: bool,
+    timer: f32,
+}
+
+impl ScreenshotNotice {
+    /// A notice reporting where the screenshot went, or why it didn't
+    pub fn new(result: &Result<PathBuf, ScreenshotError>) -> Self {
+        let message = match result {
+            Ok(path) => format!("Saved {}", path.display()),
+            Err(e) => format!("Screenshot failed: {}", e),
+        };
+        Self { message, succeeded: result.is_ok(), timer: NOTICE_TIME }
+    }
+
+    /// Count down the notice, returning false once it's done
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.timer -= delta_time;
+        self.timer > 0.0
+    }
+
+    /// Draw the flash and message over the whole window
+    pub fn draw(&self) {
+        let flash = (self.timer - (NOTICE_TIME - FLASH_TIME)) / FLASH_TIME;
+        if self.succeeded && flash > 0.0 {
+            draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(1.0, 1.0, 1.0, 0.6 * flash));
+        }
+        let alpha = (self.timer / 0.5).min(1.0);
+        let color = if self.succeeded { WHITE } else { RED };
+        draw_text(&self.message, 10.0, screen_height() - 10.0, 20.0, Color { a: alpha, ..color });
+    }
+}
+
 /// Game recorder that captures frames for creating animated GIFs
 pub struct GameRecorder {
     frames: Vec<Vec<u8>>,
-    width: u16,
+    delays: Vec<u16>, // per frame, in hundredths of a second
+    last_timestamp: Option<f64>,
+    source_size: Option<(u16, u16)>, // screen size of the first frame
+    width: u16,  // stored (possibly downscaled) frame size
     height: u16,
-    frame_delay: u16, // in hundredths of a second
+    frame_delay: u16, // default, in hundredths of a second
+    scale: f32,
 }
 
 impl GameRecorder {
@@ -46,23 +434,85 @@ impl GameRecorder {
     pub fn new(frame_delay_ms: u16) -> Self {
         Self {
             frames: Vec::new(),
+            delays: Vec::new(),
+            last_timestamp: None,
+            source_size: None,
             width: 0,
             height: 0,
             frame_delay: frame_delay_ms / 10, // Convert ms to hundredths of a second
+            scale: 1.0,
         }
     }
 
-    /// Capture the current screen as a frame
-    pub fn capture_frame(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Downscale every captured frame by `scale` (e.g. 0.5 for half
+    /// resolution) to keep GIFs small. Frames are stored at full resolution
+    /// unless this is set.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale.clamp(0.01, 1.0);
+        self
+    }
+
+    /// Capture the current screen as a frame shown for the default delay
+    pub fn capture_frame(&mut self) -> Result<(), ScreenshotError> {
+        let rgb_bytes = self.grab_screen()?;
+        self.add_frame(rgb_bytes, None);
+        Ok(())
+    }
+
+    /// Capture the current screen as a frame taken at `timestamp` seconds.
+    /// The previous frame is then shown for exactly the time that passed
+    /// between the two captures, so the GIF plays back at real speed even if
+    /// captures drift.
+    pub fn capture_frame_at(&mut self, timestamp: f64) -> Result<(), ScreenshotError> {
+        let rgb_bytes = self.grab_screen()?;
+        self.add_frame(rgb_bytes, Some(timestamp));
+        Ok(())
+    }
+
+    /// Capture raw RGBA pixels as a frame taken at `timestamp` seconds, the
+    /// same way `capture_frame_at` stores the screen. Lets a recording run
+    /// without a window, e.g. in tests.
+    pub fn capture_pixels_at(&mut self, bytes: Vec<u8>, width: u16, height: u16, timestamp: f64) -> Result<(), ScreenshotError> {
+        let rgb_bytes = self.prepare_frame(bytes, width, height)?;
+        self.add_frame(rgb_bytes, Some(timestamp));
+        Ok(())
+    }
+
+    /// Store a frame, fixing up the previous frame's delay from the timestamps
+    fn add_frame(&mut self, rgb_bytes: Vec<u8>, timestamp: Option<f64>) {
+        if let (Some(now), Some(previous), Some(delay)) = (timestamp, self.last_timestamp, self.delays.last_mut()) {
+            // Round the timestamps rather than the gap so rounding errors don't add up
+            let centis = (now * 100.0).round() - (previous * 100.0).round();
+            *delay = centis.clamp(1.0, u16::MAX as f64) as u16;
+        }
+        self.last_timestamp = timestamp;
+        self.frames.push(rgb_bytes);
+        self.delays.push(self.frame_delay);
+    }
+
+    /// Read the screen as tightly packed RGB at the recording's scale
+    fn grab_screen(&mut self) -> Result<Vec<u8>, ScreenshotError> {
         let screen_image = get_screen_data();
-        
-        if self.width == 0 {
-            self.width = screen_image.width() as u16;
-            self.height = screen_image.height() as u16;
+        let (width, height) = (screen_image.width() as u16, screen_image.height() as u16);
+        self.prepare_frame(screen_image.bytes, width, height)
+    }
+
+    /// Turn raw RGBA screen data into a stored frame: RGB, downscaled if requested
+    fn prepare_frame(&mut self, bytes: Vec<u8>, width: u16, height: u16) -> Result<Vec<u8>, ScreenshotError> {
+        match self.source_size {
+            None => self.source_size = Some((width, height)),
+            Some((first_width, first_height)) if (first_width, first_height) != (width, height) => {
+                // Every GIF frame has to be the same size as the first
+                return Err(ScreenshotError::FrameSizeChanged {
+                    from: (first_width, first_height),
+                    to: (width, height),
+                });
+            }
+            Some(_) => {}
         }
         
         // Convert RGBA to RGB (GIF doesn't support alpha)
-        let rgba_bytes = screen_image.bytes;
+        let rgba_bytes = packed_rgba(bytes, width as usize, height as usize)?;
         let mut rgb_bytes = Vec::with_capacity((rgba_bytes.len() * 3) / 4);
         
         for chunk in rgba_bytes.chunks(4) {
@@ -71,39 +521,65 @@ impl GameRecorder {
             rgb_bytes.push(chunk[2]); // B
             // Skip alpha channel
         }
-        
-        self.frames.push(rgb_bytes);
-        Ok(())
+
+        if self.scale >= 1.0 {
+            self.width = width;
+            self.height = height;
+            return Ok(rgb_bytes);
+        }
+        let (scaled, scaled_width, scaled_height) = downscale_rgb(&rgb_bytes, width as usize, height as usize, self.scale);
+        self.width = scaled_width as u16;
+        self.height = scaled_height as u16;
+        Ok(scaled)
     }
 
     /// Save all captured frames as an animated GIF
-    pub fn save_gif<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn save_gif<P: AsRef<Path>>(&self, filepath: P) -> Result<(), ScreenshotError> {
         if self.frames.is_empty() {
-            return Err("No frames captured".into());
+            return Err(ScreenshotError::NoFrames);
         }
 
         let file = File::create(filepath)?;
         let mut encoder = Encoder::new(file, self.width, self.height, &[])?;
         encoder.set_repeat(Repeat::Infinite)?;
 
-        for frame_data in &self.frames {
+        for (frame_data, delay) in self.frames.iter().zip(&self.delays) {
             let mut frame = Frame::from_rgb(self.width, self.height, frame_data);
-            frame.delay = self.frame_delay;
+            frame.delay = *delay;
             encoder.write_frame(&frame)?;
         }
 
         Ok(())
     }
 
+    /// Save whatever has been captured when recording stops early, e.g. the
+    /// player quitting or closing the window part way through. Returns how
+    /// many frames were saved; with none captured there's nothing to write.
+    pub fn save_on_shutdown<P: AsRef<Path>>(&self, filepath: P) -> Result<usize, ScreenshotError> {
+        if self.frames.is_empty() {
+            return Ok(0);
+        }
+        self.save_gif(filepath)?;
+        Ok(self.frame_count())
+    }
+
     /// Get the number of captured frames
     pub fn frame_count(&self) -> usize {
         self.frames.len()
     }
+
+    /// How long each frame is shown for, in hundredths of a second
+    pub fn frame_delays(&self) -> &[u16] {
+        &self.delays
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_screenshot_generation() {
         // This test would need to be run in a macroquad context
         // For now, we'll create a placeholder that documents the intended functionality
@@ -116,4 +592,140 @@ mod tests {
         
         assert!(true, "Screenshot test placeholder - requires macroquad context");
     }
+
+    #[test]
+    fn test_wrong_sized_screen_buffer_is_rejected() {
+        // 2x2 RGBA needs 16 bytes
+        let error = packed_rgba(vec![0; 15], 2, 2).unwrap_err();
+        assert_eq!(error, BufferSizeError { width: 2, height: 2, expected: 16, actual: 15 });
+        assert_eq!(error.to_string(), "screen data for a 2x2 image should be 16 bytes of RGBA, but got 15");
+
+        // Rows padded to a 12 byte stride are repacked
+        let mut padded = Vec::new();
+        for row in 0..2u8 {
+            padded.extend_from_slice(&[row; 8]);
+            padded.extend_from_slice(&[0xFF; 4]);
+        }
+        assert_eq!(packed_rgba(padded, 2, 2).unwrap(), [[0u8; 8], [1u8; 8]].concat());
+    }
+
+    #[test]
+    fn test_cropping_keeps_the_requested_region() {
+        // 4x3 RGBA where every pixel's red channel is its index
+        let bytes: Vec<u8> = (0..12u8).flat_map(|i| [i, 0, 0, 255]).collect();
+        let region = CaptureRegion::new(1, 1, 2, 2);
+        let cropped = crop_rgba(&bytes, 4, 3, region).unwrap();
+
+        assert_eq!(cropped.len(), 2 * 2 * BYTES_PER_PIXEL);
+        let reds: Vec<u8> = cropped.chunks(BYTES_PER_PIXEL).map(|pixel| pixel[0]).collect();
+        assert_eq!(reds, vec![5, 6, 9, 10]);
+
+        // Regions hanging off the screen are rejected
+        let error = crop_rgba(&bytes, 4, 3, CaptureRegion::new(3, 0, 2, 1)).unwrap_err();
+        assert!(matches!(error, ScreenshotError::RegionOutOfBounds { .. }));
+        assert_eq!(CaptureRegion::parse("0, 40,800,560"), Some(CaptureRegion::new(0, 40, 800, 560)));
+    }
+
+    #[test]
+    fn test_saving_gif_without_frames_fails() {
+        let recorder = GameRecorder::new(100);
+        let path = std::env::temp_dir().join(format!("no_frames_{}.gif", std::process::id()));
+        let error = recorder.save_gif(&path).unwrap_err();
+        assert!(matches!(error, ScreenshotError::NoFrames));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_shutdown_saves_frames_captured_so_far() {
+        let mut recorder = GameRecorder::new(100);
+        for shade in [0, 128, 255] {
+            let frame = recorder.prepare_frame(vec![shade; 2 * 2 * 4], 2, 2).unwrap();
+            recorder.add_frame(frame, None);
+        }
+        let path = std::env::temp_dir().join(format!("shutdown_{}.gif", std::process::id()));
+        assert_eq!(recorder.save_on_shutdown(&path).unwrap(), 3);
+
+        // The file decodes as a 2x2 GIF with every frame in it
+        let mut decoder = gif::DecodeOptions::new().read_info(File::open(&path).unwrap()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (2, 2));
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 3);
+        std::fs::remove_file(&path).unwrap();
+
+        // Quitting before anything was captured writes nothing
+        let empty = std::env::temp_dir().join(format!("shutdown_empty_{}.gif", std::process::id()));
+        assert_eq!(GameRecorder::new(100).save_on_shutdown(&empty).unwrap(), 0);
+        assert!(!empty.exists());
+    }
+
+    #[test]
+    fn test_frame_delays_follow_capture_timestamps() {
+        let mut recorder = GameRecorder::new(100);
+        recorder.add_frame(Vec::new(), Some(0.0));
+        recorder.add_frame(Vec::new(), Some(0.1));
+        recorder.add_frame(Vec::new(), Some(0.35));
+        recorder.add_frame(Vec::new(), Some(0.4));
+
+        // Each frame lasts until the next one; the last uses the default
+        assert_eq!(recorder.frame_delays(), &[10, 25, 5, 10]);
+    }
+
+    #[test]
+    fn test_half_scale_halves_frame_size() {
+        let mut recorder = GameRecorder::new(100).with_scale(0.5);
+        // 4x2 RGBA: a white and a black 2x2 block side by side
+        let mut rgba = Vec::new();
+        for _ in 0..2 {
+            rgba.extend_from_slice(&[255, 255, 255, 255, 255, 255, 255, 255, 0, 0, 0, 255, 0, 0, 0, 255]);
+        }
+        let frame = recorder.prepare_frame(rgba, 4, 2).unwrap();
+
+        assert_eq!((recorder.width, recorder.height), (2, 1));
+        assert_eq!(frame.len(), 2 * 3);
+        assert_eq!(frame, vec![255, 255, 255, 0, 0, 0]);
+
+        // Full resolution is the default
+        let mut recorder = GameRecorder::new(100);
+        let frame = recorder.prepare_frame(vec![0; 4 * 2 * 4], 4, 2).unwrap();
+        assert_eq!((recorder.width, recorder.height), (4, 2));
+        assert_eq!(frame.len(), 4 * 2 * 3);
+    }
+
+    #[test]
+    fn test_timestamped_paths_are_unique_pngs_in_order() {
+        let dir = std::env::temp_dir().join(format!("mario_screenshots_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let time = UNIX_EPOCH + std::time::Duration::from_millis(1_709_251_199_250);
+        assert_eq!(timestamp(time), "20240229_235959_250");
+        assert_eq!(timestamp(UNIX_EPOCH), "19700101_000000_000");
+
+        let first = timestamped_path(&dir, time);
+        assert_eq!(first, dir.join("screenshot_20240229_235959_250.png"));
+        File::create(&first).unwrap();
+        let second = timestamped_path(&dir, time);
+        assert_eq!(second, dir.join("screenshot_20240229_235959_250_2.png"));
+        let later = timestamped_path(&dir, time + std::time::Duration::from_millis(1));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for path in [&first, &second, &later] {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'), "{}", name);
+            assert_eq!(path.extension().unwrap(), "png");
+        }
+        assert!(first < later);
+    }
+
+    #[test]
+    fn test_duration_argument_enables_recording() {
+        let args = ["--duration".to_owned(), "5".to_owned()];
+        let config = CaptureConfig::from_args(args).unwrap();
+        assert_eq!(config, CaptureConfig { duration: Some(5.0), ..Default::default() });
+
+        assert_eq!(CaptureConfig::from_args(Vec::new()).unwrap().duration, None);
+        assert!(CaptureConfig::from_args(["--duration".to_owned()]).is_err());
+        assert!(CaptureConfig::from_args(["--duration".to_owned(), "-1".to_owned()]).is_err());
+    }
 }
\ No newline at end of file
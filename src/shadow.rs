@@ -0,0 +1,89 @@
+//! Player Shadow
+//!
+//! A soft elliptical shadow is drawn on the ground beneath the player to give
+//! a sense of depth. The ground is found with a downward raycast against the
+//! level's platforms, and the shadow spreads out and fades the higher the
+//! player is above it.
+
+use macroquad::prelude::*;
+
+use crate::simple_level::Platform;
+
+/// Height above the ground at which the shadow has faded out completely
+const MAX_SHADOW_HEIGHT: f32 = 200.0;
+/// Opacity of the shadow when the player stands on the ground
+const MAX_SHADOW_ALPHA: f32 = 0.45;
+/// How much wider the shadow gets at the fade-out height
+const MAX_EXTRA_SPREAD: f32 = 0.6;
+
+/// Size and strength of the shadow for a given height
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowShape {
+    /// Multiplier applied to the shadow's base width
+    pub scale: f32,
+    /// Opacity of the shadow
+    pub alpha: f32,
+}
+
+/// Work out the shadow's shape from the height above the ground:
+/// small and dark up close, bigger and fainter higher up
+pub fn shadow_scale(height_above_ground: f32) -> ShadowShape {
+    let t = (height_above_ground / MAX_SHADOW_HEIGHT).clamp(0.0, 1.0);
+    ShadowShape {
+        scale: 1.0 + MAX_EXTRA_SPREAD * t,
+        alpha: MAX_SHADOW_ALPHA * (1.0 - t),
+    }
+}
+
+/// Cast a ray straight down from `from_y` across the horizontal span
+/// `[x, x + width]` and return the top of the nearest platform it hits
+pub fn ground_below(x: f32, width: f32, from_y: f32, platforms: &[Platform]) -> Option<f32> {
+    platforms
+        .iter()
+        .filter(|p| x < p.x + p.width && x + width > p.x && p.y >= from_y)
+        .map(|p| p.y)
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+/// Draw a shadow centred at `center_x` on the ground at `ground_y`
+pub fn draw_shadow(center_x: f32, ground_y: f32, base_width: f32, height_above_ground: f32) {
+    let shape = shadow_scale(height_above_ground);
+    if shape.alpha <= 0.0 {
+        return;
+    }
+
+    let half_width = base_width * shape.scale / 2.0;
+    let color = Color::new(0.0, 0.0, 0.0, shape.alpha);
+    // Flattened ellipse with a lighter outer ring for a soft edge
+    draw_ellipse(center_x, ground_y, half_width * 1.2, 4.0 * shape.scale, 0.0, Color::new(0.0, 0.0, 0.0, shape.alpha * 0.5));
+    draw_ellipse(center_x, ground_y, half_width, 3.0 * shape.scale, 0.0, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shadow_grows_and_fades_with_height() {
+        let grounded = shadow_scale(0.0);
+        assert_eq!(grounded, ShadowShape { scale: 1.0, alpha: MAX_SHADOW_ALPHA });
+
+        let mid_air = shadow_scale(100.0);
+        assert!(mid_air.scale > grounded.scale);
+        assert!(mid_air.alpha < grounded.alpha && mid_air.alpha > 0.0);
+
+        let high = shadow_scale(500.0);
+        assert_eq!(high.alpha, 0.0);
+        assert_eq!(high.scale, 1.0 + MAX_EXTRA_SPREAD);
+
+        // The raycast finds the nearest platform below, ignoring ones above or to the side
+        let platforms = [
+            Platform::new(0.0, 400.0, 100.0, 20.0),
+            Platform::new(0.0, 300.0, 100.0, 20.0),
+            Platform::new(0.0, 100.0, 100.0, 20.0),
+            Platform::new(200.0, 250.0, 100.0, 20.0),
+        ];
+        assert_eq!(ground_below(40.0, 20.0, 200.0, &platforms), Some(300.0));
+        assert_eq!(ground_below(150.0, 20.0, 200.0, &platforms), None);
+    }
+}
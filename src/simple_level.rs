@@ -3,7 +3,7 @@
 //! This module implements a minimal Mario-like platformer game with:
 //! - A controllable player character (red square representing Mario)
 //! - Static platforms to jump on
-//! - A goal zone (green flag) to reach for winning
+//! - A goal (flag, door or orb) to reach for winning
 //! - Basic physics (gravity, jumping, collision detection)
 //! 
 //! Controls:
@@ -14,29 +14,258 @@
 //! The game is designed to be easily extendable for future features like
 //! enemies, coins, power-ups, multiple levels, etc.
 
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::animator::{SpriteSheet, ENEMY_SPRITES_PATH, PLAYER_SPRITES_PATH};
+use crate::background::BackgroundLayer;
+use crate::bank::{CoinBank, InsufficientCoins};
+use crate::blocks::{Block, BlockKind, BounceChain, BLOCK_SIZE, SPRING_MAX_LAUNCH};
+use crate::camera::{Camera, CameraKeyframe, CameraPath, CameraTarget, ScreenTransform, WorldPos};
+use crate::challenge::{ChallengeReward, ChallengeState, CoinChallenge};
+use crate::collision::Collider;
+use crate::console::{self, Command, Console, DebugFlags};
+use crate::draw_order::{DrawLayer, DrawOrder};
+use crate::effects::{Debris, Effect};
+use crate::events::GameEvent;
+use crate::fireball::{Fireball, FIREBALL_KNOCKBACK, FIREBALL_SIZE, MAX_FIREBALLS};
+use crate::frame_limiter::{FrameLimiter, FrameSettings};
+use crate::generator::{GeneratorKind, LevelGenerator};
+use crate::hud;
+use crate::input::{Action, InputState, KeyBindings};
+use crate::items::{Item, ItemState, ITEM_SIZE};
+use crate::level_data::LevelData;
+use crate::loot::{Loot, LootTable, Pickup};
+use crate::mirror::{MirrorBody, MIRROR_DELAY_STEPS};
+use crate::music::MusicPlayer;
+use crate::pool::Pool;
+use crate::progress::LevelProgress;
+use crate::profiler::{Profiler, PROFILE_PATH};
+use crate::projectile::{ClashOutcome, ClashRules, Projectile, MAX_PROJECTILES};
+use crate::prompts::{action_prompt, InputDevice};
+use crate::rating::{self, RunResult, StarRecord, StarThresholds};
+use crate::reachability::{self, MovementLimits, Reachability, Rect, Surface};
+use crate::replay::{LevelSource, REPLAY_PATH, Replay};
+use crate::rng::SeededRng;
+use crate::roster::EnemyRoster;
+use crate::scoring::ScoringConfig;
+use crate::screenshot::{ScreenshotNotice, SCREENSHOT_DIR, SCREENSHOT_KEY, save_timestamped_screenshot};
+use crate::shadow;
+use crate::sound::{SoundId, SoundPlayer};
+use crate::stats::RunStats;
+use crate::svg::SvgDocument;
+use crate::tally::Tally;
+use crate::time_attack::{Medal, MedalThresholds};
+use crate::tutorial::Tutorial;
+use crate::viewport::{
+    snap_to_grid, DisplaySettings, ViewCamera, Viewport, DISPLAY_SETTINGS_PATH, FULLSCREEN_KEY,
+};
 
 /// Game constants for easy tuning
 const GRAVITY: f32 = 800.0;           // Pixels per second squared
 const JUMP_STRENGTH: f32 = 300.0;     // Initial jump velocity
 const PLAYER_SPEED: f32 = 200.0;      // Horizontal movement speed
 const PLAYER_SIZE: f32 = 20.0;        // Player width and height
-const PLATFORM_HEIGHT: f32 = 20.0;    // Platform thickness
+const SPEED_SHOES_TIME: f32 = 8.0;    // Seconds speed shoes last
+const SPEED_SHOES_BOOST: f32 = 1.5;   // Run speed multiplier while wearing speed shoes
+const SPEED_TRAIL_LENGTH: usize = 3;  // Afterimages trailing the player in speed shoes
+const SPEED_TRAIL_GAP: f32 = 0.02;    // Seconds of movement between afterimages
+const BIG_PLAYER_HEIGHT: f32 = 32.0;  // Player height when big
+const GROUND_PROBE: f32 = 1.0;        // Depth checked below the feet for ground
+const JUMP_CUT: f32 = 0.4;            // Fraction of jump speed kept when jump is let go early
+const ICE_FRICTION: f32 = 150.0;      // How quickly the player speeds up and slows down on ice
+const MIN_BOUNCE_SPEED: f32 = 60.0;   // Rebounds slower than this settle onto the surface instead
+const AUTO_JUMP_LOOKAHEAD: f32 = 8.0; // How far ahead auto-jump looks for gaps and obstacles
+const BREATHING_RATE: f32 = 2.5;      // Idle breathing speed, radians per second
+const BLINK_INTERVAL: f32 = 3.0;      // Seconds between idle blinks
+const BLINK_DURATION: f32 = 0.12;     // How long the eyes stay shut
 const GOAL_SIZE: f32 = 30.0;          // Goal flag size
+const SPLITTER_BASE_SIZE: f32 = 8.0;  // Splitter size per tier (plus one)
+const SPLITTER_BASE_SPEED: f32 = 90.0; // Tier 1 splitter speed, slower when bigger
+const SPLIT_GRACE_TIME: f32 = 0.3;    // Seconds before split halves can hurt or be stomped
+const GOAL_FLASH_TIME: f32 = 1.0;     // How long the goal flashes after it activates
+const ORB_SIZE: f32 = 24.0;           // Orb goal diameter
+const FLAG_SLIDE_SPEED: f32 = 120.0;  // How fast the player slides down the flagpole
+const DOOR_ENTER_TIME: f32 = 0.4;     // Seconds before the player disappears through a door
+const ORB_BURST_TIME: f32 = 0.6;      // How long a touched orb takes to burst
+const GOAL_PAN_TIME: f32 = 1.0;       // Seconds the camera takes to pan and zoom onto the goal
+const GOAL_SEQUENCE_TIME: f32 = 2.0;  // Seconds of the goal sequence before the results show
+const GOAL_ZOOM: f32 = 2.0;           // How far the camera zooms in on the goal
+const POPUP_TIME: f32 = 0.6;          // How long score popups stay on screen
+const POPUP_RISE_SPEED: f32 = 40.0;   // How fast score popups float upwards
+const STOMP_BOUNCE: f32 = 200.0;      // Upward velocity after stomping an enemy
+const STOMP_KNOCKBACK: f32 = 150.0;   // Push given to an enemy that survives a stomp
+const KNOCKBACK_DECAY: f32 = 600.0;   // How quickly knockback slows down, pixels per second squared
+const ENEMY_INVULNERABLE_TIME: f32 = 0.5; // Seconds an enemy ignores hits after taking one
+const ARMORED_HEALTH: u8 = 3;         // Hits it takes to defeat an armored enemy
+const HURT_INVULNERABLE_TIME: f32 = 1.5; // Seconds enemies can't hurt the player after a hit
+const FIRE_COOLDOWN: f32 = 0.25;      // Seconds between fireballs
+const FIRE_BUFFER_TIME: f32 = 0.15;   // How early a fire press counts before the cooldown ends
+const ENEMY_POINTS: u32 = 200;        // Score for defeating a built-in enemy
+const HAMMER_BRO_POINTS: u32 = 1000;  // Score for defeating a Hammer Bro
+const MIRROR_HEALTH: u8 = 3;          // Stomps it takes to defeat a mirror
+const MIRROR_POINTS: u32 = 5000;      // Score for defeating a mirror
+const HAMMER_INTERVAL: f32 = 2.0;     // Seconds between a Hammer Bro's throws
+const TELEGRAPH_TIME: f32 = 0.5;      // Wind-up before an enemy attack fires, so the player can react
+const BOSS_BAR_DRAIN: f32 = 2.0;      // Health per second the boss bar drains by after a hit
+const FAR_UPDATE_MARGIN: f32 = 800.0; // Distance beyond the view after which enemies update less often
+const FAR_UPDATE_INTERVAL: usize = 4; // Far away enemies update once every this many physics steps
+const SAFE_SPAWN_STEP: f32 = 8.0;     // Distance between spots tried when the spawn point is blocked
+const SAFE_SPAWN_TRIES: u32 = 50;     // Steps searched up and outwards before giving up on a blocked spawn
+const STARTING_LIVES: u32 = 3;        // Lives the player starts a level with
+const CONTINUE_COST: u32 = 50;        // Banked coins it costs to continue after a game over
+const DROPPED_COIN_SPACING: f32 = 16.0; // Gap between coins dropped on death
+const DROPPED_COINS_PER_ROW: u32 = 8; // Dropped coins laid side by side before starting a row above
+const BANK_PATH: &str = "coin_bank.json"; // Where the coin bank is kept between sessions
+const STARS_PATH: &str = "star_ratings.json"; // Where the best star rating for each level is kept
+const PROGRESS_PATH: &str = "level_progress.json"; // Where the big coins found in each level are kept
+const HARDCORE_PULSE_RATE: f32 = 3.0; // Radians per second the hardcore border pulses at with plenty of lives
+const DEFAULT_SEED: u64 = 1985;       // Seed for enemy drops and random enemy starts until a level is reseeded
+const ENEMY_START_STREAM: u64 = 0x5EED; // Mixed into the seed for enemy starts, apart from the drops
+pub const MAX_DELTA_TIME: f32 = 0.05; // Longest frame simulated in one step
+const FIXED_DT: f32 = 1.0 / 60.0;     // Physics time step when running in real time
+const MAX_RECORDED_STEPS: usize = 216_000; // Steps a run records for its replay, an hour at 60 a second
+const AUTO_PAUSE_DELTA: f32 = 0.25;   // Frame time treated as a stall/focus loss
+const FRAME_STEP_KEY: KeyCode = KeyCode::Period; // Advances a paused game by one physics step
+const VIEW_WIDTH: f32 = 800.0;        // Default visible area width
+const VIEW_HEIGHT: f32 = 600.0;       // Default visible area height
+
+/// Timing for a platform that blinks in and out of existence
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Blink {
+    /// Seconds for a full on-then-off cycle
+    pub period: f32,
+    /// How far into its cycle the platform starts. Platforms half a period
+    /// apart alternate, for rhythm sections.
+    #[serde(default)]
+    pub offset: f32,
+}
+
+impl Blink {
+    /// Whether the platform is solid at this many seconds into the level.
+    /// It's on for the first half of each cycle and off for the second.
+    pub fn is_on(&self, time: f32) -> bool {
+        (time + self.offset).rem_euclid(self.period) < self.period / 2.0
+    }
+}
+
+/// A path a platform travels back and forth along
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Motion {
+    /// How far the platform travels from its starting position
+    pub travel: (f32, f32),
+    /// Seconds for a full there-and-back trip
+    pub period: f32,
+}
+
+impl Motion {
+    /// Offset from the starting position at this many seconds into the level,
+    /// easing in and out at each end of the path
+    pub fn offset_at(&self, time: f32) -> (f32, f32) {
+        let t = (1.0 - (std::f32::consts::TAU * time / self.period).cos()) / 2.0;
+        (self.travel.0 * t, self.travel.1 * t)
+    }
+}
 
 /// Represents a rectangular platform that the player can stand on
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Platform {
     pub x: f32,
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    /// Makes the platform blink in and out on a timer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blink: Option<Blink>,
+    /// Makes the platform travel back and forth, carrying whatever stands on it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motion: Option<Motion>,
+    /// Lets boss attacks and big Mario's head smash the platform
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub destructible: bool,
+    /// Whether the platform has been smashed, leaving nothing to collide with
+    #[serde(skip)]
+    pub destroyed: bool,
+    /// How quickly the player speeds up and slows down standing on the
+    /// platform, pixels per second squared, in place of their own
+    /// acceleration and friction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friction: Option<f32>,
+    /// Fraction of the player's falling speed thrown back up on landing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restitution: Option<f32>,
 }
 
 impl Platform {
     /// Create a new platform
     pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
-        Self { x, y, width, height }
+        Self {
+            x,
+            y,
+            width,
+            height,
+            blink: None,
+            motion: None,
+            destructible: false,
+            destroyed: false,
+            friction: None,
+            restitution: None,
+        }
+    }
+
+    /// Create a slippery ice platform, slow to get going on and slow to stop on
+    pub fn icy(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { friction: Some(ICE_FRICTION), ..Self::new(x, y, width, height) }
+    }
+
+    /// Create a platform that bounces the player back up when they land on it
+    pub fn bouncy(x: f32, y: f32, width: f32, height: f32, restitution: f32) -> Self {
+        Self { restitution: Some(restitution), ..Self::new(x, y, width, height) }
+    }
+
+    /// Create a platform that blinks in and out on a timer
+    pub fn blinking(x: f32, y: f32, width: f32, height: f32, blink: Blink) -> Self {
+        Self { blink: Some(blink), ..Self::new(x, y, width, height) }
+    }
+
+    /// Create a platform that travels back and forth from its starting position
+    pub fn moving(x: f32, y: f32, width: f32, height: f32, motion: Motion) -> Self {
+        Self { motion: Some(motion), ..Self::new(x, y, width, height) }
+    }
+
+    /// Whether the platform is solid at this many seconds into the level
+    pub fn is_solid_at(&self, time: f32) -> bool {
+        !self.destroyed && self.blink.is_none_or(|blink| blink.is_on(time))
+    }
+
+    /// The platform where it is at this many seconds into the level
+    pub fn at_time(&self, time: f32) -> Platform {
+        let (dx, dy) = self.motion.map_or((0.0, 0.0), |motion| motion.offset_at(time));
+        Platform { x: self.x + dx, y: self.y + dy, ..self.clone() }
+    }
+
+    /// How far the platform moves between `time` and `delta_time` later.
+    /// Anything standing on it is carried the same distance.
+    pub fn carry_delta(&self, time: f32, delta_time: f32) -> (f32, f32) {
+        match self.motion {
+            Some(motion) => {
+                let (x0, y0) = motion.offset_at(time);
+                let (x1, y1) = motion.offset_at(time + delta_time);
+                (x1 - x0, y1 - y0)
+            }
+            None => (0.0, 0.0),
+        }
+    }
+
+    /// Whether something spanning `x..x + width` with its feet at `feet_y`
+    /// is standing on top of this platform
+    pub fn carries(&self, x: f32, width: f32, feet_y: f32) -> bool {
+        (feet_y - self.y).abs() <= GROUND_PROBE && x < self.x + self.width && x + width > self.x
     }
 
     /// Check if a point is inside this platform
@@ -53,11 +282,24 @@ impl Platform {
         y + height > self.y
     }
 
+    /// Check if any part of this platform falls inside the horizontal view
+    pub fn is_visible(&self, camera_x: f32, view_width: f32) -> bool {
+        self.x + self.width >= camera_x && self.x <= camera_x + view_width
+    }
+
     /// Draw the platform
     pub fn draw(&self) {
-        draw_rectangle(self.x, self.y, self.width, self.height, BROWN);
+        // Tinted to show surfaces that don't behave like plain ground
+        let (fill, border) = if self.restitution.is_some() {
+            (PINK, MAROON)
+        } else if self.friction.is_some() {
+            (SKYBLUE, BLUE)
+        } else {
+            (BROWN, DARKBROWN)
+        };
+        draw_rectangle(self.x, self.y, self.width, self.height, fill);
         // Add a slight border for visual appeal
-        draw_rectangle_lines(self.x, self.y, self.width, self.height, 2.0, DARKBROWN);
+        draw_rectangle_lines(self.x, self.y, self.width, self.height, 2.0, border);
     }
 }
 
@@ -69,8 +311,172 @@ pub enum AnimationState {
     Jumping,
 }
 
+impl AnimationState {
+    /// Name of the sprite sheet animation played in this state
+    pub fn tag(&self) -> &'static str {
+        match self {
+            AnimationState::Idle => "idle",
+            AnimationState::Walking => "walk",
+            AnimationState::Jumping => "jump",
+        }
+    }
+}
+
+/// The player's power-up state, which also decides their size
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum PowerUp {
+    #[default]
+    Small,
+    Big,
+    /// Big, and able to throw fireballs
+    FireFlower,
+}
+
+impl PowerUp {
+    /// Player hitbox height for this power-up state
+    pub fn height(&self) -> f32 {
+        match self {
+            PowerUp::Small => PLAYER_SIZE,
+            PowerUp::Big | PowerUp::FireFlower => BIG_PLAYER_HEIGHT,
+        }
+    }
+}
+
+/// Which solids the player collided with during an update
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CollisionInfo {
+    /// Index of the platform the player landed on
+    pub ground: Option<usize>,
+    /// Index of the platform the player hit with their head
+    pub ceiling: Option<usize>,
+}
+
+/// A platform's top corner the player is hanging from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LedgeHang {
+    /// Position of the corner
+    pub x: f32,
+    pub y: f32,
+    /// Whether the platform is to the player's right
+    pub on_right: bool,
+}
+
+/// Saving rates where infinity means "at once", which JSON can't hold: they're kept as null
+mod instant_rate {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(rate: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        if rate.is_finite() { serializer.serialize_some(rate) } else { serializer.serialize_none() }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        Ok(Option::<f32>::deserialize(deserializer)?.unwrap_or(f32::INFINITY))
+    }
+}
+
+/// Player movement values that can be changed while the game runs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PhysicsConfig {
+    /// Pixels per second squared
+    pub gravity: f32,
+    /// Initial jump velocity
+    pub jump_strength: f32,
+    /// Horizontal movement speed
+    pub run_speed: f32,
+    /// Whether the player catches ledges they fall past while pressing into them
+    pub ledge_grab: bool,
+    /// How quickly the player speeds up to `run_speed`, pixels per second
+    /// squared; infinite reaches it at once
+    #[serde(with = "instant_rate")]
+    pub acceleration: f32,
+    /// How quickly the player slows to a stop with no direction held,
+    /// pixels per second squared; infinite stops at once
+    #[serde(with = "instant_rate")]
+    pub friction: f32,
+    /// Seconds after running off a ledge during which a jump still works
+    pub coyote_time: f32,
+    /// Seconds a jump pressed just before landing is held on to
+    pub jump_buffer: f32,
+    /// Whether letting go of jump early cuts the jump short
+    pub variable_jump: bool,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            gravity: GRAVITY,
+            jump_strength: JUMP_STRENGTH,
+            run_speed: PLAYER_SPEED,
+            ledge_grab: false,
+            acceleration: f32::INFINITY,
+            friction: f32::INFINITY,
+            coyote_time: 0.0,
+            jump_buffer: 0.0,
+            variable_jump: false,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    /// Default physics with gravity and jump velocity chosen so a jump peaks
+    /// `height` pixels up after `time_to_apex` seconds
+    pub fn from_jump(height: f32, time_to_apex: f32) -> Self {
+        Self {
+            gravity: 2.0 * height / (time_to_apex * time_to_apex),
+            jump_strength: 2.0 * height / time_to_apex,
+            ..Self::default()
+        }
+    }
+
+    /// How high a jump rises, in pixels
+    pub fn jump_height(&self) -> f32 {
+        self.jump_strength * self.jump_strength / (2.0 * self.gravity)
+    }
+
+    /// Seconds a jump takes to reach its peak
+    pub fn time_to_apex(&self) -> f32 {
+        self.jump_strength / self.gravity
+    }
+}
+
+/// Named bundles of the movement feel settings, so a feel can be picked
+/// without tuning each one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MovementPreset {
+    /// Stiff and exact: full speed at once, instant stops, no grace periods
+    #[default]
+    Classic,
+    /// Forgiving: momentum, late and early jumps that still count, and short hops
+    Modern,
+}
+
+impl MovementPreset {
+    /// Look a preset up by its lowercase name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(MovementPreset::Classic),
+            "modern" => Some(MovementPreset::Modern),
+            _ => None,
+        }
+    }
+
+    /// Set the feel settings of `physics` to this preset's, leaving gravity,
+    /// jump strength and run speed alone
+    pub fn apply(self, physics: &mut PhysicsConfig) {
+        let (acceleration, friction, coyote_time, jump_buffer, variable_jump) = match self {
+            MovementPreset::Classic => (f32::INFINITY, f32::INFINITY, 0.0, 0.0, false),
+            MovementPreset::Modern => (1200.0, 1600.0, 0.1, 0.1, true),
+        };
+        physics.acceleration = acceleration;
+        physics.friction = friction;
+        physics.coyote_time = coyote_time;
+        physics.jump_buffer = jump_buffer;
+        physics.variable_jump = variable_jump;
+    }
+}
+
 /// Represents the player character (Mario)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Player {
     pub x: f32,
     pub y: f32,
@@ -82,6 +488,31 @@ pub struct Player {
     pub facing_right: bool,
     pub animation_state: AnimationState,
     pub animation_timer: f32,
+    pub keys: HashSet<u32>,
+    pub power: PowerUp,
+    /// Time left after being hurt during which enemies can't hurt the player again
+    pub invuln_timer: f32,
+    /// Time left before another fireball can be thrown
+    pub fire_cooldown: f32,
+    /// Time left during which an early fire press still throws once the cooldown ends
+    pub fire_buffer_timer: f32,
+    /// How the player moves, tweakable while the game runs
+    pub physics: PhysicsConfig,
+    /// Index of the item the player is carrying, if any
+    pub held: Option<usize>,
+    /// The ledge the player is hanging from, if any
+    pub ledge: Option<LedgeHang>,
+    /// Time left in which a jump still works after leaving the ground
+    pub coyote_timer: f32,
+    /// Time left during which an early jump press fires on landing
+    pub jump_buffer_timer: f32,
+    /// Whether the player is in the air from a jump of their own, which
+    /// letting go of jump can cut short; springs and stomps don't count
+    pub jumping: bool,
+    /// Time left wearing speed shoes, running faster and jumping further
+    pub speed_timer: f32,
+    /// What the player bumps into: enemies and their projectiles
+    pub collider: Collider,
 }
 
 impl Player {
@@ -98,17 +529,48 @@ impl Player {
             facing_right: true,
             animation_state: AnimationState::Idle,
             animation_timer: 0.0,
+            keys: HashSet::new(),
+            power: PowerUp::Small,
+            invuln_timer: 0.0,
+            fire_cooldown: 0.0,
+            fire_buffer_timer: 0.0,
+            physics: PhysicsConfig::default(),
+            held: None,
+            ledge: None,
+            coyote_timer: 0.0,
+            jump_buffer_timer: 0.0,
+            jumping: false,
+            speed_timer: 0.0,
+            collider: Collider::PLAYER,
         }
     }
 
-    /// Update player physics and handle input
-    pub fn update(&mut self, platforms: &[Platform], delta_time: f32) {
-        // Handle input
-        self.handle_input();
+    /// Change the power-up state, resizing the player while keeping their feet in place
+    pub fn set_power(&mut self, power: PowerUp) {
+        let new_height = power.height();
+        self.y += self.height - new_height;
+        self.height = new_height;
+        self.power = power;
+    }
+
+    /// Update player physics and handle input, reporting what the player collided with
+    pub fn update(&mut self, platforms: &[Platform], input: &InputState, delta_time: f32) -> CollisionInfo {
+        let mut collisions = CollisionInfo::default();
+        self.invuln_timer = (self.invuln_timer - delta_time).max(0.0);
+        self.speed_timer = (self.speed_timer - delta_time).max(0.0);
+        self.animation_timer += delta_time;
+        if self.ledge.is_some() {
+            self.hang(platforms, input);
+            return collisions;
+        }
+
+        // Handle input, on the grip of whatever the player is standing on
+        let surface_friction = if self.on_ground { self.ground_below(platforms).and_then(|i| platforms[i].friction) } else { None };
+        self.handle_input(input, surface_friction, delta_time);
 
         // Apply gravity
         if !self.on_ground {
-            self.velocity_y += GRAVITY * delta_time;
+            self.velocity_y += self.physics.gravity * delta_time;
         }
 
         // Update position
@@ -120,6 +582,10 @@ impl Player {
         for platform in platforms {
             if platform.intersects(new_x, self.y, self.width, self.height) {
                 can_move_x = false;
+                if self.physics.ledge_grab && self.try_grab_ledge(platform, platforms, new_y) {
+                    self.update_animation_state();
+                    return collisions;
+                }
                 break;
             }
         }
@@ -130,58 +596,115 @@ impl Player {
             self.velocity_x = 0.0;
         }
 
-        // Check vertical collisions
-        let mut can_move_y = true;
-        self.on_ground = false;
-
-        for platform in platforms {
-            if platform.intersects(self.x, new_y, self.width, self.height) {
-                // Landing on top of platform
-                if self.velocity_y > 0.0 && self.y <= platform.y {
-                    self.y = platform.y - self.height;
-                    self.velocity_y = 0.0;
-                    self.on_ground = true;
-                    can_move_y = false;
-                }
-                // Hitting platform from below
-                else if self.velocity_y < 0.0 && self.y >= platform.y + platform.height {
-                    self.y = platform.y + platform.height;
-                    self.velocity_y = 0.0;
-                    can_move_y = false;
-                }
+        // Check vertical collisions, resolving against the nearest surface so that
+        // overlapping two platforms at a seam lands the same whatever their order
+        let hits = platforms.iter().enumerate().filter(|(_, p)| p.intersects(self.x, new_y, self.width, self.height));
+        if self.velocity_y > 0.0 {
+            // Landing on top of the highest platform
+            let landing = hits.filter(|(_, p)| self.y <= p.y).min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y));
+            if let Some((index, platform)) = landing {
+                self.y = platform.y - self.height;
+                // Bouncy surfaces throw some of the fall back up, until it's too little to notice
+                let rebound = self.velocity_y * platform.restitution.unwrap_or(0.0);
+                self.velocity_y = if rebound >= MIN_BOUNCE_SPEED { -rebound } else { 0.0 };
+                collisions.ground = Some(index);
+            }
+        } else if self.velocity_y < 0.0 {
+            // Hitting the lowest platform from below
+            let bottom = |p: &Platform| p.y + p.height;
+            let ceiling = hits.filter(|(_, p)| self.y >= bottom(p)).max_by(|(_, a), (_, b)| bottom(a).total_cmp(&bottom(b)));
+            if let Some((index, platform)) = ceiling {
+                self.y = bottom(platform);
+                self.velocity_y = 0.0;
+                collisions.ceiling = Some(index);
             }
         }
 
-        if can_move_y && !self.on_ground {
+        if collisions.ground.is_none() && collisions.ceiling.is_none() {
             self.y = new_y;
         }
 
-        // Keep player on screen (basic boundary checking)
-        if self.x < 0.0 {
-            self.x = 0.0;
-            self.velocity_x = 0.0;
+        // Grounding comes from probing below the feet rather than from the collision
+        // pass, which finds nothing to push against while standing still
+        self.on_ground = self.velocity_y >= 0.0 && self.ground_below(platforms).is_some();
+        if self.on_ground {
+            self.coyote_timer = self.physics.coyote_time;
+            self.jumping = false;
         }
-        if self.x + self.width > screen_width() {
-            self.x = screen_width() - self.width;
-            self.velocity_x = 0.0;
+
+        // Update animation state
+        self.update_animation_state();
+
+        collisions
+    }
+
+    /// Catch the top corner of a platform the player is pushing into, if
+    /// their head is passing its top edge on the way down this step and
+    /// there's room to climb up onto it
+    fn try_grab_ledge(&mut self, platform: &Platform, platforms: &[Platform], new_y: f32) -> bool {
+        let on_right = self.velocity_x > 0.0;
+        let pressing = self.velocity_x != 0.0 && (platform.x > self.x) == on_right;
+        let passing_top = self.velocity_y > 0.0 && self.y < platform.y && new_y >= platform.y;
+        if !pressing || !passing_top {
+            return false;
+        }
+        let (corner_x, hang_x, climb_x) = if on_right {
+            (platform.x, platform.x - self.width, platform.x)
+        } else {
+            (platform.x + platform.width, platform.x + platform.width, platform.x + platform.width - self.width)
+        };
+        if platforms.iter().any(|p| p.intersects(climb_x, platform.y - self.height, self.width, self.height)) {
+            return false;
         }
 
-        // Reset if player falls off screen
-        if self.y > screen_height() {
-            self.x = 50.0;
-            self.y = 50.0;
-            self.velocity_x = 0.0;
-            self.velocity_y = 0.0;
+        self.ledge = Some(LedgeHang { x: corner_x, y: platform.y, on_right });
+        self.x = hang_x;
+        self.y = platform.y;
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+        self.on_ground = false;
+        true
+    }
+
+    /// Hang from the held ledge: Jump/Up climbs onto it, Down lets go. The
+    /// player also falls if the platform goes away.
+    fn hang(&mut self, platforms: &[Platform], input: &InputState) {
+        let Some(ledge) = self.ledge else { return };
+        if !platforms.iter().any(|p| p.contains_point(ledge.x, ledge.y)) || input.down {
+            self.ledge = None;
+        } else if input.jump_pressed {
+            self.ledge = None;
+            self.x = if ledge.on_right { ledge.x } else { ledge.x - self.width };
+            self.y = ledge.y - self.height;
+            self.on_ground = true;
         }
+    }
 
-        // Apply friction when on ground
-        if self.on_ground {
-            self.velocity_x *= 0.8;
+    /// Whether a player running along the ground has reached the edge of a
+    /// gap or is about to run into something, so auto-jump should jump for them
+    pub fn wants_auto_jump(&self, platforms: &[Platform], input: &InputState) -> bool {
+        if !self.on_ground || input.left == input.right {
+            return false;
         }
+        let ahead_x = if input.right { self.x + self.width } else { self.x - AUTO_JUMP_LOOKAHEAD };
+        let blocked = platforms.iter().any(|p| p.intersects(ahead_x, self.y, AUTO_JUMP_LOOKAHEAD, self.height - GROUND_PROBE));
+        let gap = !platforms.iter().any(|p| p.intersects(ahead_x, self.y + self.height, AUTO_JUMP_LOOKAHEAD, GROUND_PROBE));
+        blocked || gap
+    }
 
-        // Update animation state and timer
-        self.animation_timer += delta_time;
-        self.update_animation_state();
+    /// Index of the platform directly under the player's feet, if any
+    pub fn ground_below(&self, platforms: &[Platform]) -> Option<usize> {
+        platforms.iter().position(|p| p.intersects(self.x, self.y + self.height, self.width, GROUND_PROBE))
+    }
+
+    /// Move the player back to a spawn point and stop all movement
+    pub fn respawn(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+        self.ledge = None;
+        self.jumping = false;
     }
 
     /// Update animation state based on player movement
@@ -195,44 +718,98 @@ impl Player {
         }
     }
 
-    /// Handle player input for movement and jumping
-    fn handle_input(&mut self) {
-        // Horizontal movement
-        if is_key_down(KeyCode::Left) || is_key_down(KeyCode::A) {
-            self.velocity_x = -PLAYER_SPEED;
+    /// Handle player input for movement and jumping. A surface's friction
+    /// replaces the player's own speeding up and slowing down.
+    fn handle_input(&mut self, input: &InputState, surface_friction: Option<f32>, delta_time: f32) {
+        // Horizontal movement, speeding up and slowing down as fast as the physics allows
+        let target = if input.left {
             self.facing_right = false;
-        } else if is_key_down(KeyCode::Right) || is_key_down(KeyCode::D) {
-            self.velocity_x = PLAYER_SPEED;
+            -self.run_speed()
+        } else if input.right {
             self.facing_right = true;
+            self.run_speed()
         } else {
-            self.velocity_x = 0.0;
-        }
+            0.0
+        };
+        let rate = surface_friction.unwrap_or(if target == 0.0 { self.physics.friction } else { self.physics.acceleration });
+        self.velocity_x = if rate.is_infinite() {
+            target
+        } else {
+            let change = rate * delta_time;
+            if (target - self.velocity_x).abs() <= change { target } else { self.velocity_x + change * (target - self.velocity_x).signum() }
+        };
 
-        // Jumping
-        if (is_key_pressed(KeyCode::Space) || is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W)) && self.on_ground {
-            self.velocity_y = -JUMP_STRENGTH;
+        // Jumping, allowing for a press just before landing or just after leaving the ground
+        self.coyote_timer = (self.coyote_timer - delta_time).max(0.0);
+        self.jump_buffer_timer = (self.jump_buffer_timer - delta_time).max(0.0);
+        if input.jump_pressed {
+            self.jump_buffer_timer = self.physics.jump_buffer;
+        }
+        let wants_jump = input.jump_pressed || self.jump_buffer_timer > 0.0;
+        if wants_jump && (self.on_ground || self.coyote_timer > 0.0) {
+            self.velocity_y = -self.physics.jump_strength;
             self.on_ground = false;
+            self.jumping = true;
+            self.coyote_timer = 0.0;
+            self.jump_buffer_timer = 0.0;
+        }
+
+        // Letting go of jump on the way up cuts the jump short
+        let cut_speed = self.physics.jump_strength * JUMP_CUT;
+        if self.physics.variable_jump && self.jumping && !input.jump_held && self.velocity_y < -cut_speed {
+            self.velocity_y = -cut_speed;
+        }
+    }
+
+    /// Top running speed, boosted while wearing speed shoes
+    pub fn run_speed(&self) -> f32 {
+        if self.speed_timer > 0.0 { self.physics.run_speed * SPEED_SHOES_BOOST } else { self.physics.run_speed }
+    }
+
+    /// Whether the player is still recovering from a hit
+    pub fn is_invulnerable(&self) -> bool {
+        self.invuln_timer > 0.0
+    }
+
+    /// Vertical offset of the drawn body: a bob in step while walking and a
+    /// slow breathing rise and fall while idle. Purely visual.
+    pub fn bob_offset(&self) -> f32 {
+        match self.animation_state {
+            AnimationState::Walking => (self.animation_timer * 8.0).sin() * 1.0,
+            AnimationState::Idle => (self.animation_timer * BREATHING_RATE).sin() * 0.5,
+            AnimationState::Jumping => 0.0,
         }
     }
 
+    /// Whether the idle player's eyes are shut for a blink
+    pub fn is_blinking(&self) -> bool {
+        self.animation_state == AnimationState::Idle
+            && self.animation_timer % BLINK_INTERVAL > BLINK_INTERVAL - BLINK_DURATION
+    }
+
     /// Draw the player with enhanced Mario-like graphics
     pub fn draw(&self) {
+        // Flash while recovering from a hit
+        if self.is_invulnerable() && (self.invuln_timer * 20.0) as i32 % 2 == 0 {
+            return;
+        }
+
         let x = self.x;
         let y = self.y;
         let w = self.width;
         let h = self.height;
 
-        // Animation-based slight bobbing for walking
-        let walking_offset = if self.animation_state == AnimationState::Walking {
-            (self.animation_timer * 8.0).sin() * 1.0
-        } else {
-            0.0
-        };
-        let draw_y = y + walking_offset;
+        // Bob while walking, breathe while idle; feet only move with the walk
+        let draw_y = y + self.bob_offset();
+        let feet_y = if self.animation_state == AnimationState::Walking { draw_y } else { y };
 
-        // Mario's body (overalls)
-        draw_rectangle(x + 2.0, draw_y + 8.0, w - 4.0, h - 8.0, BLUE);
-        draw_rectangle_lines(x + 2.0, draw_y + 8.0, w - 4.0, h - 8.0, 1.0, DARKBLUE);
+        // Mario's body (overalls, white when he has the fire flower)
+        let (overalls, overalls_outline) = match self.power {
+            PowerUp::FireFlower => (WHITE, LIGHTGRAY),
+            PowerUp::Small | PowerUp::Big => (BLUE, DARKBLUE),
+        };
+        draw_rectangle(x + 2.0, draw_y + 8.0, w - 4.0, h - 8.0, overalls);
+        draw_rectangle_lines(x + 2.0, draw_y + 8.0, w - 4.0, h - 8.0, 1.0, overalls_outline);
 
         // Mario's shirt (red)
         draw_rectangle(x + 4.0, draw_y + 10.0, w - 8.0, 6.0, RED);
@@ -249,10 +826,15 @@ impl Player {
         draw_circle(x + w / 2.0, draw_y + 3.0, 2.5, WHITE);
         draw_text("M", x + w / 2.0 - 2.0, draw_y + 5.5, 8.0, RED);
 
-        // Eyes (direction-aware)
+        // Eyes (direction-aware), closed for a moment now and then while idle
         let eye_offset = if self.facing_right { 1.0 } else { -1.0 };
-        draw_circle(x + w / 2.0 - 2.0 + eye_offset, draw_y + 6.0, 1.0, BLACK);
-        draw_circle(x + w / 2.0 + 2.0 + eye_offset, draw_y + 6.0, 1.0, BLACK);
+        for eye_x in [x + w / 2.0 - 2.0 + eye_offset, x + w / 2.0 + 2.0 + eye_offset] {
+            if self.is_blinking() {
+                draw_line(eye_x - 1.0, draw_y + 6.0, eye_x + 1.0, draw_y + 6.0, 1.0, BLACK);
+            } else {
+                draw_circle(eye_x, draw_y + 6.0, 1.0, BLACK);
+            }
+        }
 
         // Mustache
         draw_rectangle(x + w / 2.0 - 3.0, draw_y + 8.0, 6.0, 2.0, Color::new(0.4, 0.2, 0.1, 1.0));
@@ -271,8 +853,8 @@ impl Player {
 
         // Feet/shoes (brown)
         let foot_color = Color::new(0.4, 0.2, 0.1, 1.0);
-        draw_rectangle(x + 1.0, draw_y + h - 2.0, 6.0, 3.0, foot_color);
-        draw_rectangle(x + w - 7.0, draw_y + h - 2.0, 6.0, 3.0, foot_color);
+        draw_rectangle(x + 1.0, feet_y + h - 2.0, 6.0, 3.0, foot_color);
+        draw_rectangle(x + w - 7.0, feet_y + h - 2.0, 6.0, 3.0, foot_color);
 
         // Jumping pose adjustments
         if self.animation_state == AnimationState::Jumping {
@@ -291,42 +873,261 @@ impl Player {
     }
 }
 
+/// A key that unlocks every door sharing its id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Key {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    #[serde(skip)]
+    pub collected: bool,
+}
+
+impl Key {
+    /// Size of the key's pickup area
+    const SIZE: f32 = 14.0;
+
+    /// Create a new key at the specified position
+    pub fn new(id: u32, x: f32, y: f32) -> Self {
+        Self { id, x, y, collected: false }
+    }
+
+    /// Check if the key overlaps with a rectangle
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        x < self.x + Self::SIZE &&
+        x + width > self.x &&
+        y < self.y + Self::SIZE &&
+        y + height > self.y
+    }
+
+    /// Draw the key
+    pub fn draw(&self) {
+        let s = Self::SIZE;
+        // Bow (ring) and shaft with teeth
+        draw_circle_lines(self.x + s * 0.3, self.y + s * 0.3, s * 0.25, 2.0, GOLD);
+        draw_rectangle(self.x + s * 0.4, self.y + s * 0.25, s * 0.6, 2.0, GOLD);
+        draw_rectangle(self.x + s * 0.75, self.y + s * 0.25, 2.0, s * 0.3, GOLD);
+        draw_rectangle(self.x + s * 0.9, self.y + s * 0.25, 2.0, s * 0.2, GOLD);
+    }
+}
+
+/// A coin placed in the level that is picked up by touching it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Coin {
+    pub x: f32,
+    pub y: f32,
+    #[serde(skip)]
+    pub collected: bool,
+}
+
+impl Coin {
+    /// Diameter of the coin's pickup area
+    const SIZE: f32 = 12.0;
+
+    /// Create a new coin at the specified position
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y, collected: false }
+    }
+
+    /// Check if the coin overlaps with a rectangle
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        x < self.x + Self::SIZE &&
+        x + width > self.x &&
+        y < self.y + Self::SIZE &&
+        y + height > self.y
+    }
+
+    /// Draw the coin
+    pub fn draw(&self) {
+        let r = Self::SIZE / 2.0;
+        draw_circle(self.x + r, self.y + r, r, GOLD);
+        draw_circle_lines(self.x + r, self.y + r, r, 1.5, ORANGE);
+        draw_rectangle(self.x + r - 1.0, self.y + 3.0, 2.0, Self::SIZE - 6.0, ORANGE);
+    }
+}
+
+/// A rare, large coin worth bonus points. Which ones have been found is
+/// remembered between attempts (see `LevelProgress`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BigCoin {
+    pub x: f32,
+    pub y: f32,
+    #[serde(skip)]
+    pub collected: bool,
+}
+
+impl BigCoin {
+    /// Diameter of the big coin
+    const SIZE: f32 = 24.0;
+
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y, collected: false }
+    }
+
+    /// Check if the big coin overlaps with a rectangle
+    pub fn intersects(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        x < self.x + Self::SIZE &&
+        x + width > self.x &&
+        y < self.y + Self::SIZE &&
+        y + height > self.y
+    }
+
+    /// Draw the big coin at a screen position, faded if it was found on an earlier attempt
+    pub fn draw(x: f32, y: f32, found_before: bool) {
+        let r = Self::SIZE / 2.0;
+        let alpha = if found_before { 0.45 } else { 1.0 };
+        let fade = |color: Color| Color { a: alpha, ..color };
+        draw_circle(x + r, y + r, r, fade(GOLD));
+        draw_circle_lines(x + r, y + r, r, 2.5, fade(ORANGE));
+        draw_circle_lines(x + r, y + r, r * 0.6, 1.5, fade(ORANGE));
+        draw_rectangle(x + r - 1.5, y + 6.0, 3.0, Self::SIZE - 12.0, fade(ORANGE));
+    }
+}
+
+/// A door that stays shut until the player holds the key with the same id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Door {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Door {
+    /// Create a new door
+    pub fn new(id: u32, x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { id, x, y, width, height }
+    }
+
+    /// Check if the door is open for a player holding the given keys
+    pub fn is_open(&self, keys: &HashSet<u32>) -> bool {
+        keys.contains(&self.id)
+    }
+
+    /// Collision shape of the door while it's locked
+    pub fn as_platform(&self) -> Platform {
+        Platform::new(self.x, self.y, self.width, self.height)
+    }
+
+    /// Draw the door, either shut with a keyhole or as an open frame
+    pub fn draw(&self, open: bool) {
+        if open {
+            draw_rectangle_lines(self.x, self.y, self.width, self.height, 2.0, DARKBROWN);
+        } else {
+            draw_rectangle(self.x, self.y, self.width, self.height, Color::new(0.55, 0.35, 0.15, 1.0));
+            draw_rectangle_lines(self.x, self.y, self.width, self.height, 2.0, DARKBROWN);
+            // Keyhole
+            let cx = self.x + self.width / 2.0;
+            let cy = self.y + self.height / 2.0;
+            draw_circle(cx, cy, 3.0, BLACK);
+            draw_rectangle(cx - 1.0, cy, 2.0, 6.0, BLACK);
+        }
+    }
+}
+
+/// What the goal looks like and what happens when the player touches it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GoalKind {
+    /// A flagpole the player slides down
+    #[default]
+    Flag,
+    /// A door the player walks through
+    Door,
+    /// An orb that bursts when touched
+    Orb,
+}
+
 /// Represents the goal that the player needs to reach
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Goal {
     pub x: f32,
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    pub kind: GoalKind,
 }
 
 impl Goal {
-    /// Create a new goal at the specified position
-    pub fn new(x: f32, y: f32) -> Self {
-        Self {
-            x,
-            y,
-            width: GOAL_SIZE,
-            height: GOAL_SIZE * 2.0,
+    /// Create a new goal of the given kind at the specified position
+    pub fn new(x: f32, y: f32, kind: GoalKind) -> Self {
+        let (width, height) = match kind {
+            GoalKind::Flag => (GOAL_SIZE, GOAL_SIZE * 2.0),
+            GoalKind::Door => (GOAL_SIZE, GOAL_SIZE * 1.5),
+            GoalKind::Orb => (ORB_SIZE, ORB_SIZE),
+        };
+        Self { x, y, width, height, kind }
+    }
+
+    /// Draw the goal, with a burst around it while `flash` counts down.
+    /// `finished` is how long ago the player touched it, if they have.
+    pub fn draw(&self, flash: f32, finished: Option<f32>) {
+        if flash > 0.0 {
+            let t = flash / GOAL_FLASH_TIME;
+            let center = (self.x + self.width / 2.0, self.y + self.height / 2.0);
+            let radius = self.height * (1.5 - t);
+            draw_circle(center.0, center.1, radius, Color::new(1.0, 1.0, 0.6, 0.5 * t));
+        }
+
+        match self.kind {
+            GoalKind::Flag => {
+                // Flag pole
+                draw_rectangle(self.x + self.width * 0.8, self.y, 3.0, self.height, DARKGRAY);
+
+                // Flag
+                draw_rectangle(self.x, self.y, self.width * 0.8, self.height * 0.4, GREEN);
+                draw_rectangle_lines(self.x, self.y, self.width * 0.8, self.height * 0.4, 2.0, DARKGREEN);
+
+                // Flag text
+                draw_text("GOAL", self.x + 2.0, self.y + 15.0, 12.0, WHITE);
+            }
+            GoalKind::Door => {
+                // Frame, then the door itself, swung open once the player walks in
+                let frame = Color::new(0.35, 0.2, 0.1, 1.0);
+                draw_rectangle(self.x - 3.0, self.y - 3.0, self.width + 6.0, self.height + 3.0, frame);
+                let fill = if finished.is_some() { BLACK } else { Color::new(0.6, 0.35, 0.15, 1.0) };
+                draw_rectangle(self.x, self.y, self.width, self.height, fill);
+                if finished.is_none() {
+                    draw_circle(self.x + self.width * 0.8, self.y + self.height * 0.55, 2.5, GOLD);
+                }
+            }
+            GoalKind::Orb => {
+                let center = (self.x + self.width / 2.0, self.y + self.height / 2.0);
+                let radius = self.width / 2.0;
+                match finished {
+                    // Rings spreading out as it bursts
+                    Some(time) => {
+                        let t = (time / ORB_BURST_TIME).min(1.0);
+                        if t < 1.0 {
+                            let color = Color::new(0.7, 0.5, 1.0, 1.0 - t);
+                            draw_circle_lines(center.0, center.1, radius * (1.0 + 3.0 * t), 3.0, color);
+                            draw_circle_lines(center.0, center.1, radius * (1.0 + 1.5 * t), 2.0, color);
+                        }
+                    }
+                    None => {
+                        draw_circle(center.0, center.1, radius, Color::new(0.55, 0.3, 0.9, 1.0));
+                        draw_circle(center.0 - radius * 0.3, center.1 - radius * 0.3, radius * 0.3, Color::new(1.0, 1.0, 1.0, 0.7));
+                    }
+                }
+            }
         }
     }
 
-    /// Draw the goal as a flag
-    pub fn draw(&self) {
-        // Flag pole
-        draw_rectangle(self.x + self.width * 0.8, self.y, 3.0, self.height, DARKGRAY);
-        
-        // Flag
-        draw_rectangle(self.x, self.y, self.width * 0.8, self.height * 0.4, GREEN);
-        draw_rectangle_lines(self.x, self.y, self.width * 0.8, self.height * 0.4, 2.0, DARKGREEN);
-        
-        // Flag text
-        draw_text("GOAL", self.x + 2.0, self.y + 15.0, 12.0, WHITE);
+    /// Move the player through the goal's finishing sequence: sliding down a
+    /// flagpole to its foot. Doors and orbs leave the player where they are.
+    fn play_finish(&self, player: &mut Player, delta_time: f32) {
+        if self.kind == GoalKind::Flag {
+            player.x = self.x + self.width * 0.8 - player.width;
+            let foot = self.y + self.height - player.height;
+            player.y = (player.y + FLAG_SLIDE_SPEED * delta_time).min(foot).max(player.y);
+            player.velocity_x = 0.0;
+            player.velocity_y = 0.0;
+        }
     }
 }
 
 /// Represents a decorative tree in the environment
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tree {
     pub x: f32,
     pub y: f32,
@@ -370,6 +1171,54 @@ pub enum EnemyDirection {
     Right,
 }
 
+/// The different kinds of enemy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum EnemyKind {
+    /// Walks back and forth and dies when stomped
+    #[default]
+    Goomba,
+    /// Splits into two smaller, faster copies when stomped
+    Splitter,
+    /// Takes several hits, getting knocked back by each one that doesn't finish it
+    Armored,
+    /// Lobs hammers at the player every few seconds
+    HammerBro,
+    /// Copies the player's moves from a moment ago, mirrored left to right
+    Mirror,
+}
+
+impl EnemyKind {
+    /// Name of the sprite sheet animation enemies of this kind play
+    pub fn tag(&self) -> &'static str {
+        match self {
+            EnemyKind::Goomba => "goomba",
+            EnemyKind::Splitter => "splitter",
+            EnemyKind::Armored => "armored",
+            EnemyKind::HammerBro => "hammer_bro",
+            EnemyKind::Mirror => "mirror",
+        }
+    }
+}
+
+/// A point on a scripted enemy path
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub x: f32,
+    pub y: f32,
+    /// Speed the enemy travels at on its way to this point
+    pub speed: f32,
+    /// Seconds to wait on arriving
+    #[serde(default)]
+    pub pause: f32,
+}
+
+impl Waypoint {
+    /// A waypoint reached at the given speed, without stopping
+    pub fn new(x: f32, y: f32, speed: f32) -> Self {
+        Self { x, y, speed, pause: 0.0 }
+    }
+}
+
 /// Represents a simple enemy (Goomba-like)
 #[derive(Debug, Clone)]
 pub struct Enemy {
@@ -381,6 +1230,45 @@ pub struct Enemy {
     pub speed: f32,
     pub patrol_start: f32,
     pub patrol_end: f32,
+    pub alive: bool,
+    pub kind: EnemyKind,
+    /// Splitter size: each stomp splits it into two of the next tier down,
+    /// and tier 1 enemies die instead of splitting
+    pub size_tier: u8,
+    /// Time left before a freshly spawned enemy starts interacting with the player
+    pub spawn_timer: f32,
+    /// Hits left before the enemy is defeated
+    pub health: u8,
+    /// Hits the enemy takes from full health
+    pub max_health: u8,
+    /// Name shown on the boss health bar, for enemies that are bosses
+    pub boss: Option<String>,
+    /// Horizontal velocity from the last hit, decaying back to zero
+    pub knockback_velocity: f32,
+    /// Time left during which further hits are ignored
+    pub invulnerable_timer: f32,
+    /// Score for defeating the enemy
+    pub points: u32,
+    /// Time left before a Hammer Bro starts winding up its next throw
+    pub throw_timer: f32,
+    /// Time left winding up an attack; it fires when this runs out
+    pub telegraph_timer: f32,
+    /// Time built up while far away enemies skip updates, used up on their next one
+    pub pending_time: f32,
+    /// Points to travel between in order, looping, instead of patrolling
+    pub path: Vec<Waypoint>,
+    /// Index of the waypoint the enemy is heading for
+    pub waypoint: usize,
+    /// Time left waiting at the last waypoint reached
+    pub pause_timer: f32,
+    /// Body a mirror moves with the player's delayed, reflected inputs
+    pub mirror: Option<MirrorBody>,
+    /// What the enemy might drop when defeated
+    pub loot: Option<LootTable>,
+    /// What the enemy bumps into: the player and their attacks
+    pub collider: Collider,
+    /// How fast the enemy moved itself over its last update, or a mirror over its last step
+    pub velocity: (f32, f32),
 }
 
 impl Enemy {
@@ -395,14 +1283,243 @@ impl Enemy {
             speed: 30.0,
             patrol_start,
             patrol_end,
+            alive: true,
+            kind: EnemyKind::Goomba,
+            size_tier: 1,
+            spawn_timer: 0.0,
+            health: 1,
+            max_health: 1,
+            boss: None,
+            knockback_velocity: 0.0,
+            invulnerable_timer: 0.0,
+            points: ENEMY_POINTS,
+            throw_timer: HAMMER_INTERVAL,
+            telegraph_timer: 0.0,
+            pending_time: 0.0,
+            path: Vec::new(),
+            waypoint: 0,
+            pause_timer: 0.0,
+            mirror: None,
+            loot: None,
+            collider: Collider::ENEMY,
+            velocity: (0.0, 0.0),
         }
     }
 
-    /// Update enemy movement
-    pub fn update(&mut self, delta_time: f32) {
-        // Simple patrol AI
-        match self.direction {
-            EnemyDirection::Right => {
+    /// Face a random way and start from a random point along the patrol.
+    /// Enemies following a path or the player's moves keep their start.
+    pub fn randomize_start(&mut self, rng: &mut SeededRng) {
+        if !self.path.is_empty() || self.mirror.is_some() {
+            return;
+        }
+        self.direction = if rng.next_f32() < 0.5 { EnemyDirection::Left } else { EnemyDirection::Right };
+        if self.patrol_end > self.patrol_start {
+            self.x = rng.range(self.patrol_start, self.patrol_end);
+        }
+    }
+
+    /// Have the enemy follow a path of waypoints instead of patrolling
+    pub fn with_path(self, path: Vec<Waypoint>) -> Self {
+        Self { path, waypoint: 0, pause_timer: 0.0, ..self }
+    }
+
+    /// Create a Hammer Bro, which throws hammers at the player
+    pub fn hammer_bro(x: f32, y: f32, patrol_start: f32, patrol_end: f32) -> Self {
+        Self {
+            kind: EnemyKind::HammerBro,
+            speed: 20.0,
+            points: HAMMER_BRO_POINTS,
+            ..Self::new(x, y, patrol_start, patrol_end)
+        }
+    }
+
+    /// Create a mirror, which copies the player's moves from `MIRROR_DELAY_STEPS` ago,
+    /// running left when they ran right
+    pub fn mirror(x: f32, y: f32) -> Self {
+        Self {
+            kind: EnemyKind::Mirror,
+            width: PLAYER_SIZE,
+            height: PLAYER_SIZE,
+            health: MIRROR_HEALTH,
+            max_health: MIRROR_HEALTH,
+            points: MIRROR_POINTS,
+            mirror: Some(MirrorBody::new(x, y, MIRROR_DELAY_STEPS)),
+            ..Self::new(x, y, x, x)
+        }
+    }
+
+    /// Move a mirror by the player's input from a few steps ago. Other enemies ignore this.
+    pub fn follow_mirror(&mut self, platforms: &[Platform], input: &InputState, delta_time: f32) {
+        let Some(mirror) = self.mirror.as_mut() else { return };
+        mirror.update(platforms, input, delta_time);
+        if delta_time > 0.0 {
+            self.velocity = ((mirror.body.x - self.x) / delta_time, (mirror.body.y - self.y) / delta_time);
+        }
+        self.x = mirror.body.x;
+        self.y = mirror.body.y;
+        self.direction = if mirror.body.facing_right { EnemyDirection::Right } else { EnemyDirection::Left };
+    }
+
+    /// Count down to a Hammer Bro's next throw, returning true when it's time.
+    /// Each throw is telegraphed: the enemy stops and winds up for
+    /// `TELEGRAPH_TIME` before the hammer leaves its hand.
+    pub fn ready_to_throw(&mut self, delta_time: f32) -> bool {
+        if self.kind != EnemyKind::HammerBro {
+            return false;
+        }
+        if self.is_telegraphing() {
+            self.telegraph_timer -= delta_time;
+            if self.telegraph_timer > 0.0 {
+                return false;
+            }
+            self.telegraph_timer = 0.0;
+            self.throw_timer = HAMMER_INTERVAL;
+            return true;
+        }
+        self.throw_timer -= delta_time;
+        if self.throw_timer <= 0.0 {
+            self.telegraph_timer = TELEGRAPH_TIME;
+        }
+        false
+    }
+
+    /// Whether the enemy is winding up an attack
+    pub fn is_telegraphing(&self) -> bool {
+        self.telegraph_timer > 0.0
+    }
+
+    /// Create an armored enemy that survives its first few hits
+    pub fn armored(x: f32, y: f32, patrol_start: f32, patrol_end: f32) -> Self {
+        Self {
+            kind: EnemyKind::Armored,
+            health: ARMORED_HEALTH,
+            max_health: ARMORED_HEALTH,
+            speed: 20.0,
+            ..Self::new(x, y, patrol_start, patrol_end)
+        }
+    }
+
+    /// Hit the enemy from the given direction. A hit that doesn't defeat it
+    /// knocks it back with `force` and makes it briefly invulnerable.
+    /// Returns true if the hit defeated the enemy.
+    pub fn take_hit(&mut self, direction: EnemyDirection, force: f32) -> bool {
+        if !self.alive || self.invulnerable_timer > 0.0 {
+            return false;
+        }
+
+        self.health = self.health.saturating_sub(1);
+        if self.health == 0 {
+            self.alive = false;
+            return true;
+        }
+
+        self.knockback_velocity = match direction {
+            EnemyDirection::Left => -force,
+            EnemyDirection::Right => force,
+        };
+        self.invulnerable_timer = ENEMY_INVULNERABLE_TIME;
+        false
+    }
+
+    /// Whether the enemy can currently hurt or be hurt by the player
+    pub fn is_interactive(&self) -> bool {
+        self.alive && self.spawn_timer <= 0.0 && self.invulnerable_timer <= 0.0
+    }
+
+    /// Create a splitting enemy of the given size tier
+    pub fn splitter(x: f32, y: f32, patrol_start: f32, patrol_end: f32, size_tier: u8) -> Self {
+        let size_tier = size_tier.max(1);
+        let size = SPLITTER_BASE_SIZE * (size_tier as f32 + 1.0);
+        Self {
+            width: size,
+            height: size,
+            speed: SPLITTER_BASE_SPEED / size_tier as f32,
+            kind: EnemyKind::Splitter,
+            size_tier,
+            ..Self::new(x, y, patrol_start, patrol_end)
+        }
+    }
+
+    /// The two smaller enemies a stomped splitter breaks into, if it's big
+    /// enough to split at all
+    pub fn split(&self) -> Option<[Enemy; 2]> {
+        if self.kind != EnemyKind::Splitter || self.size_tier <= 1 {
+            return None;
+        }
+
+        // Scale down from this enemy so the halves keep any roster tuning
+        let tier = self.size_tier - 1;
+        let scale = (tier as f32 + 1.0) / (self.size_tier as f32 + 1.0);
+        let mut left = Enemy {
+            width: self.width * scale,
+            height: self.height * scale,
+            speed: self.speed * self.size_tier as f32 / tier as f32,
+            points: self.points,
+            ..Enemy::splitter(self.x, self.y, self.patrol_start, self.patrol_end, tier)
+        };
+        let mut right = left.clone();
+
+        // Keep both halves standing on the same floor and send them apart
+        let floor = self.y + self.height;
+        left.y = floor - left.height;
+        right.y = floor - right.height;
+        right.x = self.x + self.width - right.width;
+        left.direction = EnemyDirection::Left;
+        right.direction = EnemyDirection::Right;
+
+        // Give the stomp bounce time to carry the player clear of the halves
+        left.spawn_timer = SPLIT_GRACE_TIME;
+        right.spawn_timer = SPLIT_GRACE_TIME;
+
+        Some([left, right])
+    }
+
+    /// Update enemy movement, measuring its velocity over the time it was given
+    pub fn update(&mut self, delta_time: f32) {
+        let start = (self.x, self.y);
+        self.advance(delta_time);
+        // Mirrors keep the velocity `follow_mirror` gave them
+        if self.mirror.is_none() && delta_time > 0.0 {
+            self.velocity = ((self.x - start.0) / delta_time, (self.y - start.1) / delta_time);
+        }
+    }
+
+    /// Move the enemy on its own: along its path, with knockback, or on patrol
+    fn advance(&mut self, delta_time: f32) {
+        self.spawn_timer = (self.spawn_timer - delta_time).max(0.0);
+        self.invulnerable_timer = (self.invulnerable_timer - delta_time).max(0.0);
+
+        // Enemies stand still while winding up an attack
+        if self.is_telegraphing() {
+            return;
+        }
+
+        // Mirrors are moved by the player's inputs instead, in `follow_mirror`
+        if self.mirror.is_some() {
+            return;
+        }
+
+        // Enemies with a path follow it exactly, ignoring knockback
+        if !self.path.is_empty() {
+            self.follow_path(delta_time);
+            return;
+        }
+
+        // Knockback overrides walking until it has worn off
+        if self.knockback_velocity != 0.0 {
+            self.x = (self.x + self.knockback_velocity * delta_time).clamp(self.patrol_start, self.patrol_end);
+            let decay = KNOCKBACK_DECAY * delta_time;
+            self.knockback_velocity = if self.knockback_velocity.abs() <= decay {
+                0.0
+            } else {
+                self.knockback_velocity - decay * self.knockback_velocity.signum()
+            };
+            return;
+        }
+
+        // Simple patrol AI
+        match self.direction {
+            EnemyDirection::Right => {
                 self.x += self.speed * delta_time;
                 if self.x >= self.patrol_end {
                     self.direction = EnemyDirection::Left;
@@ -417,19 +1534,54 @@ impl Enemy {
         }
     }
 
+    /// Head for the next waypoint, waiting there if it asks for a pause, then
+    /// move on to the one after, looping back to the first at the end
+    fn follow_path(&mut self, delta_time: f32) {
+        if self.pause_timer > 0.0 {
+            self.pause_timer = (self.pause_timer - delta_time).max(0.0);
+            return;
+        }
+        let target = self.path[self.waypoint % self.path.len()];
+        let (dx, dy) = (target.x - self.x, target.y - self.y);
+        let distance = (dx * dx + dy * dy).sqrt();
+        let travel = target.speed * delta_time;
+        if dx != 0.0 {
+            self.direction = if dx > 0.0 { EnemyDirection::Right } else { EnemyDirection::Left };
+        }
+        if travel >= distance {
+            self.x = target.x;
+            self.y = target.y;
+            self.pause_timer = target.pause;
+            self.waypoint = (self.waypoint + 1) % self.path.len();
+        } else {
+            self.x += dx / distance * travel;
+            self.y += dy / distance * travel;
+        }
+    }
+
     /// Draw the enemy (Goomba-like)
     pub fn draw(&self) {
+        // Blink while invulnerable
+        if self.invulnerable_timer > 0.0 && (self.invulnerable_timer * 20.0) as i32 % 2 == 0 {
+            return;
+        }
+
         let x = self.x;
         let y = self.y;
         let w = self.width;
         let h = self.height;
 
-        // Body (brown mushroom-like)
-        let body_color = Color::new(0.5, 0.3, 0.1, 1.0);
+        // Body (brown mushroom-like, purple for splitters)
+        let (body_color, head_color) = match self.kind {
+            EnemyKind::Goomba => (Color::new(0.5, 0.3, 0.1, 1.0), Color::new(0.4, 0.2, 0.05, 1.0)),
+            EnemyKind::Splitter => (Color::new(0.5, 0.2, 0.6, 1.0), Color::new(0.4, 0.1, 0.5, 1.0)),
+            EnemyKind::Armored => (Color::new(0.45, 0.45, 0.5, 1.0), Color::new(0.3, 0.3, 0.35, 1.0)),
+            EnemyKind::HammerBro => (Color::new(0.2, 0.55, 0.25, 1.0), Color::new(0.1, 0.4, 0.15, 1.0)),
+            EnemyKind::Mirror => (Color::new(0.65, 0.75, 0.95, 0.8), Color::new(0.5, 0.6, 0.85, 0.8)),
+        };
         draw_rectangle(x + 2.0, y + h * 0.3, w - 4.0, h * 0.7, body_color);
         
-        // Head (round, darker brown)
-        let head_color = Color::new(0.4, 0.2, 0.05, 1.0);
+        // Head (round, darker)
         draw_circle(x + w / 2.0, y + h * 0.25, w * 0.4, head_color);
 
         // Eyes (angry looking)
@@ -445,6 +1597,25 @@ impl Enemy {
         // Feet
         draw_rectangle(x, y + h - 3.0, 5.0, 3.0, BLACK);
         draw_rectangle(x + w - 5.0, y + h - 3.0, 5.0, 3.0, BLACK);
+
+        // Winding up: flash and raise the weapon overhead
+        if self.is_telegraphing() {
+            if (self.telegraph_timer * 16.0) as i32 % 2 == 0 {
+                draw_rectangle(x, y, w, h, Color::new(1.0, 1.0, 1.0, 0.5));
+            }
+            draw_line(x + w / 2.0, y, x + w / 2.0, y - 8.0, 2.0, BROWN);
+            draw_circle(x + w / 2.0, y - 9.0, 3.0, DARKGRAY);
+        }
+    }
+
+    /// Check if the player is landing on top of this enemy. What counts is
+    /// how they move relative to each other: the player has to be closing in
+    /// from above, with their feet either in the top half of the enemy or
+    /// above its top before this step's relative movement brought them together.
+    pub fn is_stomped_by(&self, player: &Player, delta_time: f32) -> bool {
+        let closing_speed = player.velocity_y - self.velocity.1;
+        let depth = player.y + player.height - self.y;
+        closing_speed > 0.0 && (depth < self.height * 0.5 || depth - closing_speed * delta_time <= 0.0)
     }
 
     /// Check if enemy intersects with a rectangle
@@ -456,207 +1627,4095 @@ impl Enemy {
     }
 }
 
+/// Where a shape in the level's list of solids came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Solid {
+    Platform(usize),
+    Block(usize),
+    Door,
+}
+
+/// Where the progress kept between sessions is saved
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveFiles {
+    pub bank: PathBuf,
+    pub stars: PathBuf,
+    pub progress: PathBuf,
+}
+
+impl Default for SaveFiles {
+    fn default() -> Self {
+        Self { bank: BANK_PATH.into(), stars: STARS_PATH.into(), progress: PROGRESS_PATH.into() }
+    }
+}
+
+impl SaveFiles {
+    /// Delete every save file, skipping any that don't exist
+    pub fn delete(&self) -> std::io::Result<()> {
+        for path in [&self.bank, &self.stars, &self.progress] {
+            match fs::remove_file(path) {
+                Err(error) if error.kind() != std::io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options chosen for a play session rather than by the level: controls,
+/// display, difficulty and where things are saved. They all survive the
+/// level restarting.
+#[derive(Clone)]
+pub struct SessionSettings {
+    pub time_attack: bool,
+    pub key_bindings: KeyBindings,
+    /// Device the control hints are written for. Only the keyboard is read
+    /// for now, so this stays on `Keyboard` unless set explicitly.
+    pub input_device: InputDevice,
+    /// Point values for everything the level awards
+    pub scoring: ScoringConfig,
+    /// Debug overlays switched on from the console
+    pub debug: DebugFlags,
+    /// Render at a low retro resolution, snapping moving things to its pixel grid
+    pub pixel_perfect: bool,
+    /// Scroll the view by whole pixels, so thin outlines don't shimmer as the camera eases
+    pub snap_camera: bool,
+    /// Permadeath: no continues, and running out of lives deletes the save
+    pub hardcore: bool,
+    /// Fraction of the coins collected this attempt dropped where the player
+    /// dies, to be picked up again by getting back there; 0 keeps them all
+    pub death_coin_drop: f32,
+    /// Where the coin bank, star ratings and level progress are saved
+    pub save_files: SaveFiles,
+    /// Physics iterations each step is split into, for more accurate collisions
+    pub substeps: u32,
+    /// Accessibility option: jump automatically at gap edges and obstacles while running
+    pub auto_jump: bool,
+    /// How much bigger than their hitboxes the player and other entities are drawn
+    pub render_scale: f32,
+    /// Sprite sheet the player is drawn from, for the animations it has
+    pub player_sprites: Option<SpriteSheet>,
+    /// Sprite sheet enemies are drawn from, one animation per kind
+    pub enemy_sprites: Option<SpriteSheet>,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            time_attack: false,
+            key_bindings: KeyBindings::default(),
+            input_device: InputDevice::default(),
+            scoring: ScoringConfig::default(),
+            debug: DebugFlags::default(),
+            pixel_perfect: false,
+            snap_camera: true,
+            hardcore: false,
+            death_coin_drop: 0.0,
+            save_files: SaveFiles::default(),
+            substeps: 1,
+            auto_jump: false,
+            render_scale: 1.0,
+            player_sprites: None,
+            enemy_sprites: None,
+        }
+    }
+}
+
+/// Give every enemy a random start for `seed`, the same ones every time
+fn randomize_enemy_starts(enemies: &mut [Enemy], seed: u64) {
+    let mut rng = SeededRng::new(seed ^ ENEMY_START_STREAM);
+    for enemy in enemies {
+        enemy.randomize_start(&mut rng);
+    }
+}
+
+/// One step of drawing the level, in the order `SimpleLevel::draw_passes` gives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawPass {
+    /// The decoration layer with this index
+    Decoration(usize),
+    /// Trees standing behind the platforms
+    Trees,
+    /// Platforms, enemies, pickups and everything else but the player
+    Level,
+    Player,
+}
+
+/// What happens to the level's entities when the player dies
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum RespawnPolicy {
+    /// Classic: everything goes back to how it was when the level started
+    Reset,
+    /// Modern: defeated enemies stay defeated
+    #[default]
+    Persist,
+}
+
+/// Floating text showing points just scored
+#[derive(Debug, Clone, Default)]
+pub struct ScorePopup {
+    pub x: f32,
+    pub y: f32,
+    pub value: u32,
+    pub timer: f32,
+}
+
+/// Blend between an entity's position at the previous and current physics
+/// step. `alpha` is how far rendering is past the previous step, from 0 to 1.
+pub fn interpolate(previous: (f32, f32), current: (f32, f32), alpha: f32) -> (f32, f32) {
+    let alpha = alpha.clamp(0.0, 1.0);
+    (
+        previous.0 + (current.0 - previous.0) * alpha,
+        previous.1 + (current.1 - previous.1) * alpha,
+    )
+}
+
+/// Middle of the bottom edge of a box: where things that stand on the ground
+/// are scaled about, so they stay standing on it
+fn bottom_centre(x: f32, y: f32, width: f32, height: f32) -> Vec2 {
+    vec2(x + width / 2.0, y + height)
+}
+
+/// Middle of a square, for scaling things that float or fly
+fn centre(x: f32, y: f32, size: f32) -> Vec2 {
+    vec2(x + size / 2.0, y + size / 2.0)
+}
+
+/// Positions at the start of the latest physics step, used to interpolate rendering
+#[derive(Debug, Clone, Default)]
+struct PreviousPositions {
+    player: (f32, f32),
+    enemies: Vec<(f32, f32)>,
+    camera_x: f32,
+}
+
+/// What the player has to do before the goal counts
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Objective {
+    /// Just reach the goal
+    #[default]
+    ReachGoal,
+    /// The goal stays hidden until every coin in the level is collected
+    CollectAllCoins,
+}
+
+/// Whether the level is running or paused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameState {
+    Playing,
+    Paused,
+}
+
 /// Main game state and logic
 pub struct SimpleLevel {
     pub player: Player,
     platforms: Vec<Platform>,
     goal: Goal,
     trees: Vec<Tree>,
+    background: Vec<BackgroundLayer>,
     enemies: Vec<Enemy>,
+    initial_enemies: Vec<Enemy>,
+    keys: Vec<Key>,
+    coins: Vec<Coin>,
+    big_coins: Vec<BigCoin>,
+    challenges: Vec<CoinChallenge>,
+    doors: Vec<Door>,
+    blocks: Vec<Block>,
+    initial_blocks: Vec<Block>,
+    /// Shells and blocks that can be picked up and thrown
+    items: Vec<Item>,
+    /// Loot dropped by defeated enemies
+    pickups: Vec<Pickup>,
+    /// Coins the player dropped where they last died, waiting to be picked up again
+    dropped_coins: Vec<Coin>,
+    /// Decides what defeated enemies drop, seeded so runs replay the same
+    pub loot_rng: SeededRng,
+    /// What `loot_rng` was seeded with at the start of the run
+    seed: u64,
+    /// Every step's input since the run started, after auto-jump, for replays,
+    /// or `None` once the run has gone on too long to keep them all
+    recorded_inputs: Option<Vec<InputState>>,
+    events: Vec<GameEvent>,
+    respawn_policy: RespawnPolicy,
+    objective: Objective,
+    goal_flash: f32,
+    /// Seconds since the player touched the goal, while its finishing sequence plays
+    finish_time: Option<f32>,
+    popups: Pool<ScorePopup>,
+    fireballs: Pool<Fireball>,
+    /// Hammers and the like thrown by enemies
+    projectiles: Pool<Projectile>,
+    /// What happens when fireballs meet enemy projectiles
+    pub clash_rules: ClashRules,
+    effects: Pool<Effect>,
+    /// Chunks of destroyed platforms
+    debris: Pool<Debris>,
+    bounce_chain: BounceChain,
+    /// Enemies stomped since the player last stood on something
+    stomp_combo: u32,
+    /// Switch groups whose outline blocks are currently solid
+    active_switch_groups: HashSet<u32>,
+    music: Option<String>,
+    tutorial: Tutorial,
+    previous: PreviousPositions,
+    /// Unsimulated time carried over between frames
+    accumulator: f32,
+    /// Jump/fire presses from frames too short to run a physics step
+    queued_presses: (bool, bool),
+    /// How far between the previous and current physics step to draw
+    render_alpha: f32,
     pub game_won: bool,
-    camera_x: f32,
+    pub coins_collected: u32,
+    /// Score and coins collected when the attempt started, put back when a
+    /// death resets the level so nothing in it counts twice
+    attempt_start: (u32, u32),
+    /// Coins paid into the bank since the attempt started, taken back out
+    /// when a death resets the level and puts them back
+    coins_banked: u32,
+    /// Big coins picked up this attempt
+    pub big_coins_collected: u32,
+    /// Which big coins have been found in every level, kept between sessions
+    pub progress: LevelProgress,
+    pub score: u32,
+    /// Tallies for this attempt, shown on the results screen
+    pub stats: RunStats,
+    /// Sound effects raised by events, waiting for the audio backend
+    pub sounds: SoundPlayer,
+    pub lives: u32,
+    /// Out of lives: the game stops until the player continues or restarts
+    pub game_over: bool,
+    /// Coins saved up across levels and sessions, spent on continues
+    pub bank: CoinBank,
+    camera: Camera,
+    spawn_point: (f32, f32),
+    pub level_time: f32,
+    medal_thresholds: Option<MedalThresholds>,
+    pub medal: Option<Medal>,
+    star_thresholds: StarThresholds,
+    /// Stars earned by finishing the level this attempt
+    pub stars: Option<u8>,
+    /// Time and coins counting into the score once the level is finished
+    tally: Option<Tally>,
+    /// Best stars earned on every level, kept between sessions
+    pub star_record: StarRecord,
+    pub state: GameState,
+    view_width: f32,
+    view_height: f32,
+    /// Highest score reached in any attempt at this level
+    pub best_score: u32,
+    /// Health shown on the boss bar, catching up with the on-screen boss's real health
+    boss_bar: Option<f32>,
+    /// Developer console for tweaking the level while it runs
+    console: Console,
+    /// Draw the player behind foreground decoration layers instead of over
+    /// them, as the level data asks
+    pub player_behind_foreground: bool,
+    /// Set once a finished hardcore run has been dismissed
+    pub back_to_menu: bool,
+    /// Physics iterations run so far, for staggering far away enemy updates
+    update_tick: u32,
+    /// Options chosen for the session, kept when the level restarts
+    pub settings: SessionSettings,
+    /// What the level was built from, kept so it can be restarted
+    data: LevelData,
+    roster: EnemyRoster,
+    /// Where the level came from, so a replay can build it again; `None`
+    /// for levels made from data in code
+    pub source: Option<LevelSource>,
 }
 
 impl SimpleLevel {
     /// Create a new game level
     pub fn new() -> Self {
-        let mut platforms = Vec::new();
-        
-        // Create a simple level layout
-        // Ground platforms
-        platforms.push(Platform::new(0.0, 400.0, 200.0, PLATFORM_HEIGHT));
-        platforms.push(Platform::new(250.0, 450.0, 150.0, PLATFORM_HEIGHT));
-        platforms.push(Platform::new(450.0, 350.0, 100.0, PLATFORM_HEIGHT));
-        platforms.push(Platform::new(600.0, 300.0, 120.0, PLATFORM_HEIGHT));
-        platforms.push(Platform::new(750.0, 250.0, 100.0, PLATFORM_HEIGHT));
-        
-        // Some floating platforms
-        platforms.push(Platform::new(200.0, 300.0, 80.0, PLATFORM_HEIGHT));
-        platforms.push(Platform::new(350.0, 200.0, 80.0, PLATFORM_HEIGHT));
-        platforms.push(Platform::new(500.0, 150.0, 80.0, PLATFORM_HEIGHT));
-        
-        // Final platform with goal
-        platforms.push(Platform::new(850.0, 200.0, 100.0, PLATFORM_HEIGHT));
-
-        // Add decorative trees
-        let mut trees = Vec::new();
-        trees.push(Tree::new(100.0, 400.0, 40.0));
-        trees.push(Tree::new(300.0, 450.0, 35.0));
-        trees.push(Tree::new(520.0, 350.0, 45.0));
-        trees.push(Tree::new(700.0, 250.0, 38.0));
-        trees.push(Tree::new(950.0, 200.0, 42.0));
-
-        // Add enemies
-        let mut enemies = Vec::new();
-        enemies.push(Enemy::new(220.0, 400.0 - 16.0, 210.0, 380.0)); // Ground patrol
-        enemies.push(Enemy::new(470.0, 350.0 - 16.0, 460.0, 540.0)); // Platform patrol
-        enemies.push(Enemy::new(620.0, 300.0 - 16.0, 610.0, 710.0)); // Longer patrol
+        Self { source: Some(LevelSource::BuiltIn), ..Self::from_data(&LevelData::default()) }
+    }
+
+    /// Create the default level with the player starting in the given power-up state
+    pub fn new_with(start_power: PowerUp) -> Self {
+        Self::from_data(&LevelData { start_power, ..Default::default() })
+    }
+
+    /// Create a game level from level data, with the built-in enemy types
+    pub fn from_data(data: &LevelData) -> Self {
+        Self::from_data_with_roster(data, EnemyRoster::default())
+    }
+
+    /// Create a game level from level data, looking up enemies placed by type name in `roster`.
+    /// Spawns naming a type the roster doesn't have are left out.
+    pub fn from_data_with_roster(data: &LevelData, roster: EnemyRoster) -> Self {
+        let mut player = Player::new(data.player_spawn.0, data.player_spawn.1);
+        player.set_power(data.start_power);
+        player.physics.ledge_grab = data.ledge_grab;
+
+        let mut enemies: Vec<Enemy> = data.enemies.iter().filter_map(|e| roster.spawn_from(e)).collect();
+        if data.random_enemy_starts {
+            randomize_enemy_starts(&mut enemies, DEFAULT_SEED);
+        }
 
         Self {
-            player: Player::new(50.0, 50.0),
-            platforms,
-            goal: Goal::new(870.0, 140.0),
-            trees,
+            player,
+            platforms: data.platforms.clone(),
+            goal: Goal::new(data.goal.0, data.goal.1, data.goal_kind),
+            trees: data.trees.clone(),
+            background: data.background.clone(),
+            initial_enemies: enemies.clone(),
             enemies,
+            keys: data.keys.clone(),
+            coins: data.coins.clone(),
+            big_coins: data.big_coins.clone(),
+            challenges: data.challenges.clone(),
+            doors: data.doors.clone(),
+            blocks: data.blocks.clone(),
+            initial_blocks: data.blocks.clone(),
+            items: data.items.clone(),
+            events: Vec::new(),
+            respawn_policy: data.respawn_policy,
+            objective: data.objective,
+            goal_flash: 0.0,
+            finish_time: None,
+            popups: Pool::with_capacity(8),
+            fireballs: Pool::with_capacity(MAX_FIREBALLS),
+            projectiles: Pool::with_capacity(MAX_PROJECTILES),
+            clash_rules: ClashRules::default(),
+            effects: Pool::with_capacity(8),
+            debris: Pool::with_capacity(24),
+            bounce_chain: BounceChain::default(),
+            stomp_combo: 0,
+            active_switch_groups: HashSet::new(),
+            music: data.music.clone(),
+            tutorial: Tutorial::default(),
+            previous: PreviousPositions::default(),
+            accumulator: 0.0,
+            queued_presses: (false, false),
+            render_alpha: 1.0,
             game_won: false,
-            camera_x: 0.0,
+            coins_collected: 0,
+            attempt_start: (0, 0),
+            coins_banked: 0,
+            big_coins_collected: 0,
+            pickups: Vec::new(),
+            dropped_coins: Vec::new(),
+            loot_rng: SeededRng::new(DEFAULT_SEED),
+            seed: DEFAULT_SEED,
+            recorded_inputs: Some(Vec::new()),
+            progress: LevelProgress::default(),
+            stats: RunStats::default(),
+            sounds: SoundPlayer::new(VIEW_WIDTH),
+            lives: STARTING_LIVES,
+            game_over: false,
+            bank: CoinBank::default(),
+            score: 0,
+            camera: Camera { margins: data.scroll_margins, bounds: data.camera_bounds, ..Default::default() },
+            spawn_point: data.player_spawn,
+            level_time: 0.0,
+            medal_thresholds: data.medal_thresholds,
+            star_thresholds: data.star_thresholds.unwrap_or_default(),
+            tally: None,
+            stars: None,
+            star_record: StarRecord::default(),
+            medal: None,
+            state: GameState::Playing,
+            view_width: VIEW_WIDTH,
+            view_height: VIEW_HEIGHT,
+            best_score: 0,
+            boss_bar: None,
+            console: Console::default(),
+            player_behind_foreground: data.player_behind_foreground,
+            back_to_menu: false,
+            update_tick: 0,
+            settings: SessionSettings::default(),
+            data: data.clone(),
+            roster,
+            source: None,
+        }
+    }
+
+    /// Start the level over from its initial state, keeping the session
+    /// settings and the stats that outlive a single attempt (best score, coin
+    /// bank, physics, tutorial, console)
+    pub fn reset(&mut self) {
+        let mut fresh = Self::from_data_with_roster(&self.data, self.roster.clone());
+        fresh.source = self.source.clone();
+        fresh.settings = self.settings.clone();
+        fresh.best_score = self.best_score.max(self.score);
+        fresh.bank = self.bank;
+        fresh.star_record = self.star_record.clone();
+        fresh.progress = self.progress.clone();
+        fresh.player.physics = self.player.physics;
+        fresh.console = self.console.clone();
+        fresh.reseed(self.seed);
+        fresh.tutorial = self.tutorial.clone();
+        fresh.view_width = self.view_width;
+        fresh.view_height = self.view_height;
+        *self = fresh;
+    }
+
+    /// Restart the level's random number generator from a seed. In a level
+    /// with random enemy starts, the enemies go back to new starts for it.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.loot_rng = SeededRng::new(seed);
+        if self.data.random_enemy_starts {
+            randomize_enemy_starts(&mut self.initial_enemies, seed);
+            self.enemies = self.initial_enemies.clone();
         }
     }
 
-    /// Update the game state
+    /// What the level's random number generator was last seeded with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Every physics step's input since the run started, as a replay needs
+    /// them, or `None` if the run outgrew the recording
+    pub fn recorded_inputs(&self) -> Option<&[InputState]> {
+        self.recorded_inputs.as_deref()
+    }
+
+    /// Create a level laid out by a procedural generator
+    pub fn from_generator(generator: &dyn LevelGenerator, seed: u64) -> Self {
+        Self::from_data(&generator.generate(seed))
+    }
+
+    /// Create a level laid out by one of the built-in generators, which a
+    /// replay can lay out again
+    pub fn generated(kind: GeneratorKind, seed: u64) -> Self {
+        let source = Some(LevelSource::Generated { generator: kind, seed });
+        Self { source, ..Self::from_generator(kind.generator().as_ref(), seed) }
+    }
+
+    /// Load a game level from a JSON level file, along with the enemy roster it names
+    pub fn load<P: AsRef<std::path::Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = LevelData::load(&filepath)?;
+        let roster = match &data.roster {
+            Some(path) => EnemyRoster::load(path)?,
+            None => EnemyRoster::default(),
+        };
+        let missing = roster.missing_types(&data.enemies);
+        if !missing.is_empty() {
+            return Err(format!("unknown enemy types: {}", missing.join(", ")).into());
+        }
+        let source = Some(LevelSource::File(filepath.as_ref().to_string_lossy().into_owned()));
+        Ok(Self { source, ..Self::from_data_with_roster(&data, roster) })
+    }
+
+    /// Update the game state from the keyboard
     pub fn update(&mut self, delta_time: f32) {
-        if !self.game_won {
-            self.player.update(&self.platforms, delta_time);
-            
-            // Update enemies
-            for enemy in &mut self.enemies {
-                enemy.update(delta_time);
+        // The console takes over the keyboard, and freezes the game, while it's open
+        if is_key_pressed(KeyCode::GraveAccent) {
+            self.console.toggle();
+        }
+        if self.console.is_open() {
+            if let Some(line) = self.console.read_keyboard() {
+                let reply = self.run_console_command(&line);
+                self.console.print(reply);
             }
-            
-            // Check enemy collisions (simple reset for now)
-            for enemy in &self.enemies {
-                if enemy.intersects(self.player.x, self.player.y, self.player.width, self.player.height) {
-                    // Reset player position on enemy collision
-                    self.player.x = 50.0;
-                    self.player.y = 50.0;
-                    self.player.velocity_x = 0.0;
-                    self.player.velocity_y = 0.0;
-                }
+            return;
+        }
+
+        let input = InputState::from_keyboard(&self.settings.key_bindings);
+        if is_key_pressed(FRAME_STEP_KEY) {
+            self.step_frame(&input);
+        }
+        self.advance(&input, delta_time);
+    }
+
+    /// Run exactly one fixed physics step while the game is paused, so
+    /// physics can be inspected frame by frame (pair it with `toggle hitboxes`).
+    /// The game stays paused afterwards. Returns whether a step was run.
+    pub fn step_frame(&mut self, input: &InputState) -> bool {
+        if self.state != GameState::Paused {
+            return false;
+        }
+        self.simulate(input, FIXED_DT);
+        self.accumulator = 0.0;
+        self.render_alpha = 1.0;
+        true
+    }
+
+    /// Run a line typed into the developer console, returning what it did or
+    /// why it couldn't
+    pub fn run_console_command(&mut self, line: &str) -> String {
+        let command = match console::parse(line) {
+            Ok(command) => command,
+            Err(message) => return message,
+        };
+        match command {
+            Command::Set { setting, value } => {
+                setting.apply(&mut self.player.physics, value);
+                format!("{:?} set to {}", setting, value)
             }
-            
-            // Simple camera follow
-            let target_camera_x = self.player.x - screen_width() / 2.0;
-            self.camera_x = self.camera_x + (target_camera_x - self.camera_x) * 0.1;
-            
-            // Keep camera within bounds
-            self.camera_x = self.camera_x.max(0.0);
-            
-            // Check if player reached the goal
-            if self.player.intersects(self.goal.x, self.goal.y, self.goal.width, self.goal.height) {
-                self.game_won = true;
+            Command::SpawnEnemy { x, y } => {
+                self.enemies.push(Enemy::new(x, y, x - 50.0, x + 50.0));
+                format!("spawned an enemy at ({}, {})", x, y)
             }
+            Command::Toggle(flag) => {
+                let on = self.settings.debug.toggle(flag);
+                format!("{:?} {}", flag, if on { "on" } else { "off" })
+            }
+            Command::Teleport { x, y } => {
+                self.player.respawn(x, y);
+                format!("teleported to ({}, {})", x, y)
+            }
+            Command::Bind { action, key } => match self.settings.key_bindings.try_bind(action, vec![key]) {
+                Ok(()) => format!("{} bound to {:?}", action.name(), key),
+                Err(conflict) => format!("{}, not changed", conflict),
+            },
         }
     }
 
-    /// Draw the game
-    pub fn draw(&self) {
-        clear_background(SKYBLUE);
-        
-        // Apply camera offset
-        let camera_offset = -self.camera_x;
-        
-        // Draw trees (background elements)
-        for tree in &self.trees {
-            let tree_with_offset = Tree::new(
-                tree.x + camera_offset,
-                tree.y,
-                tree.height,
-            );
-            tree_with_offset.draw();
+    /// Run the physics in fixed steps for a frame that took `frame_time`,
+    /// carrying any leftover time over to the next frame. Drawing then
+    /// interpolates between the last two steps so motion stays smooth at any
+    /// refresh rate.
+    pub fn advance(&mut self, input: &InputState, frame_time: f32) {
+        // A hardcore run that's over can only go back to the menu
+        if input.restart_pressed && !self.run_over() {
+            self.reset();
+            return;
         }
-        
-        // Draw platforms
-        for platform in &self.platforms {
-            let platform_with_offset = Platform::new(
-                platform.x + camera_offset,
-                platform.y,
-                platform.width,
-                platform.height,
-            );
-            platform_with_offset.draw();
+        if !self.handle_pause(input, frame_time) {
+            self.accumulator = 0.0;
+            return;
         }
-        
-        // Draw enemies
-        for enemy in &self.enemies {
-            let mut enemy_with_offset = enemy.clone();
-            enemy_with_offset.x += camera_offset;
-            enemy_with_offset.draw();
+
+        self.accumulator += frame_time.min(MAX_DELTA_TIME);
+        let mut input = *input;
+        // A press on a frame too short for a step would otherwise be lost,
+        // so hold on to it for the next step
+        input.jump_pressed |= self.queued_presses.0;
+        input.fire_pressed |= self.queued_presses.1;
+        self.queued_presses = (input.jump_pressed, input.fire_pressed);
+        while self.accumulator >= FIXED_DT {
+            self.simulate(&input, FIXED_DT);
+            self.accumulator -= FIXED_DT;
+            // Presses only count once, not for every step this frame
+            input.jump_pressed = false;
+            input.fire_pressed = false;
+            self.queued_presses = (false, false);
         }
-        
-        // Draw goal
-        let goal_copy = Goal::new(self.goal.x + camera_offset, self.goal.y);
-        goal_copy.draw();
-        
-        // Draw player (on top of everything)
-        let mut player_copy = Player::new(self.player.x + camera_offset, self.player.y);
-        player_copy.facing_right = self.player.facing_right;
-        player_copy.animation_state = self.player.animation_state;
-        player_copy.animation_timer = self.player.animation_timer;
-        player_copy.draw();
-        
-        // Draw UI
-        self.draw_ui();
+        self.render_alpha = self.accumulator / FIXED_DT;
     }
 
-    /// Draw the user interface
-    fn draw_ui(&self) {
-        // Instructions
-        draw_text("Use Arrow Keys or WASD to move, Space/Up to jump", 10.0, 30.0, 20.0, WHITE);
-        draw_text("Reach the green flag to win! ESC to quit", 10.0, 55.0, 20.0, WHITE);
-        
-        // Win message
-        if self.game_won {
-            let win_text = "Congratulations! You reached the goal!";
-            let text_width = measure_text(win_text, None, 40, 1.0).width;
-            let x = (screen_width() - text_width) / 2.0;
-            let y = screen_height() / 2.0;
-            
-            // Background for text
-            draw_rectangle(x - 10.0, y - 30.0, text_width + 20.0, 50.0, Color::new(0.0, 0.0, 0.0, 0.7));
-            draw_text(win_text, x, y, 40.0, GOLD);
-            draw_text("Press ESC to quit", x + 50.0, y + 30.0, 20.0, WHITE);
+    /// Advance the game state by one frame with the given input.
+    ///
+    /// This doesn't touch the window or keyboard, so it can be driven headlessly.
+    pub fn step(&mut self, input: &InputState, delta_time: f32) {
+        // A hardcore run that's over can only go back to the menu
+        if input.restart_pressed && !self.run_over() {
+            self.reset();
+            return;
+        }
+        if self.handle_pause(input, delta_time) {
+            self.simulate(input, delta_time);
+            self.render_alpha = 1.0;
         }
     }
 
-    /// Check if the game should quit
-    pub fn should_quit(&self) -> bool {
-        is_key_pressed(KeyCode::Escape)
+    /// Toggle pausing from input or a stalled frame. Returns whether the
+    /// game should run this frame.
+    fn handle_pause(&mut self, input: &InputState, frame_time: f32) -> bool {
+        match self.state {
+            GameState::Paused => {
+                if input.pause_pressed {
+                    self.state = GameState::Playing;
+                }
+                false
+            }
+            GameState::Playing => {
+                // macroquad doesn't report focus changes, so treat a stalled frame
+                // (alt-tab, dragging the window, ...) as the window losing focus.
+                // The very first frame is skipped since it includes startup time.
+                let stalled = frame_time > AUTO_PAUSE_DELTA && self.level_time > 0.0;
+                if input.pause_pressed || stalled {
+                    self.state = GameState::Paused;
+                    return false;
+                }
+                true
+            }
+        }
     }
 
-    /// Generate a screenshot of the current game state and save it to the specified path
-    /// 
-    /// # Arguments
-    /// * `filepath` - The path where the screenshot should be saved
-    /// 
-    /// # Returns
-    /// * `Result<(), Box<dyn std::error::Error>>` - Ok if successful, Err if failed
-    pub fn take_screenshot<P: AsRef<std::path::Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
-        // First render the current game state to ensure the screen has the latest frame
-        self.draw();
-        
-        // Capture and save the screenshot
-        crate::screenshot::capture_screenshot(filepath)
-    }
-}
+    /// Run one physics step
+    fn simulate(&mut self, input: &InputState, delta_time: f32) {
+        // Never simulate more than a short frame at once
+        let delta_time = delta_time.min(MAX_DELTA_TIME);
+        self.events.clear();
+        self.previous = PreviousPositions {
+            player: (self.player.x, self.player.y),
+            enemies: self.enemies.iter().map(|e| (e.x, e.y)).collect(),
+            camera_x: self.camera.x,
+        };
 
-/// Main game loop for the simple level
-pub async fn run_simple_level() {
-    let mut game = SimpleLevel::new();
-    
-    loop {
-        let delta_time = get_frame_time();
-        
-        // Update game state
-        game.update(delta_time);
-        
-        // Draw everything
-        game.draw();
-        
-        // Check for quit
-        if game.should_quit() {
-            break;
+        // Smaller substeps catch collisions that fast movement would skip past.
+        // Presses only count in the first one.
+        let substeps = self.settings.substeps.max(1);
+        let mut input = *input;
+        if self.settings.auto_jump && !self.game_over && self.player.wants_auto_jump(&self.solids().1, &input) {
+            input.jump_pressed = true;
+            input.jump_held = true;
         }
-        
-        next_frame().await;
+        // Stop recording a run too long for its replay to be worth keeping
+        if let Some(recorded) = &mut self.recorded_inputs {
+            if recorded.len() < MAX_RECORDED_STEPS {
+                recorded.push(InputState { pause_pressed: false, restart_pressed: false, ..input });
+            } else {
+                self.recorded_inputs = None;
+            }
+        }
+        for _ in 0..substeps {
+            self.simulate_substep(&input, delta_time / substeps as f32);
+            input.jump_pressed = false;
+            input.fire_pressed = false;
+        }
+        for event in &self.events {
+            self.stats.record(event);
+        }
+        self.play_event_sounds();
+        self.update_boss_bar(delta_time);
     }
-}
\ No newline at end of file
+
+    /// One physics iteration of a step. Events it raises are added to the step's list.
+    fn simulate_substep(&mut self, input: &InputState, delta_time: f32) {
+        let first_event = self.events.len();
+        if self.run_over() {
+            if input.jump_pressed || input.restart_pressed {
+                self.back_to_menu = true;
+            }
+        } else if self.game_over {
+            // Jump on the game over screen pays for a continue, if the bank can afford it
+            if input.jump_pressed {
+                let _ = self.continue_game();
+            }
+        } else if !self.game_won {
+            self.carry_riders(delta_time);
+            self.level_time += delta_time;
+            self.tutorial.update(input, delta_time);
+            let (sources, shapes) = self.solids();
+            let (was_on_ground, start_x) = (self.player.on_ground, self.player.x);
+            let collisions = self.player.update(&shapes, input, delta_time);
+            if input.jump_pressed && was_on_ground && !self.player.on_ground {
+                self.events.push(GameEvent::PlayerJumped);
+            }
+            self.stats.distance += (self.player.x - start_x).abs();
+            self.stats.time_played += delta_time;
+            match collisions.ceiling.map(|i| sources[i]) {
+                Some(Solid::Block(block)) => self.events.push(GameEvent::BlockHitFromBelow { block }),
+                // Big Mario's head smashes destructible platforms, like bricks
+                Some(Solid::Platform(platform)) if self.player.power != PowerUp::Small && self.platforms[platform].destructible => {
+                    self.destroy_platform(platform);
+                }
+                _ => {}
+            }
+
+            // Springs launch the player; chaining them builds height, anything else
+            // ends the chain, and any stomp combo with it
+            self.bounce_chain.update(delta_time);
+            match collisions.ground.map(|i| sources[i]) {
+                Some(Solid::Block(block)) if self.blocks[block].kind == BlockKind::Spring => {
+                    self.player.velocity_y = -self.bounce_chain.bounce();
+                    self.player.on_ground = false;
+                    self.player.jumping = false;
+                    self.blocks[block].bump();
+                    self.events.push(GameEvent::SpringBounce { block, chain: self.bounce_chain.count });
+                }
+                Some(_) => {
+                    self.bounce_chain.reset();
+                    self.stomp_combo = 0;
+                }
+                None => {}
+            }
+
+            for block in &mut self.blocks {
+                block.update(delta_time);
+            }
+
+            // A fire press during the cooldown is held on to, and fires as soon as it ends
+            self.player.fire_cooldown = (self.player.fire_cooldown - delta_time).max(0.0);
+            if input.fire_pressed {
+                self.player.fire_buffer_timer = FIRE_BUFFER_TIME;
+            }
+            if self.player.fire_buffer_timer > 0.0 && self.throw_fireball() {
+                self.player.fire_buffer_timer = 0.0;
+            }
+            self.player.fire_buffer_timer = (self.player.fire_buffer_timer - delta_time).max(0.0);
+            for fireball in self.fireballs.iter_mut() {
+                fireball.update(&shapes, delta_time);
+            }
+            self.update_held_item(input);
+            for item in &mut self.items {
+                item.update(&shapes, delta_time);
+            }
+
+            // Pick up any keys the player touches
+            for key in self.keys.iter_mut().filter(|k| !k.collected) {
+                if key.intersects(self.player.x, self.player.y, self.player.width, self.player.height) {
+                    key.collected = true;
+                    self.player.keys.insert(key.id);
+                    self.events.push(GameEvent::KeyCollected { id: key.id });
+                }
+            }
+
+            // And any coins
+            for (i, coin) in self.coins.iter_mut().enumerate().filter(|(_, c)| !c.collected) {
+                if coin.intersects(self.player.x, self.player.y, self.player.width, self.player.height) {
+                    coin.collected = true;
+                    self.events.push(GameEvent::CoinCollected { coin: i });
+                }
+            }
+            for (i, coin) in self.big_coins.iter_mut().enumerate().filter(|(_, c)| !c.collected) {
+                if coin.intersects(self.player.x, self.player.y, self.player.width, self.player.height) {
+                    coin.collected = true;
+                    self.events.push(GameEvent::BigCoinCollected { coin: i });
+                }
+            }
+            for (i, pickup) in self.pickups.iter_mut().enumerate().filter(|(_, p)| !p.collected) {
+                if pickup.intersects(self.player.x, self.player.y, self.player.width, self.player.height) {
+                    pickup.collected = true;
+                    self.events.push(GameEvent::PickupCollected { pickup: i });
+                }
+            }
+            for (i, coin) in self.dropped_coins.iter_mut().enumerate().filter(|(_, c)| !c.collected) {
+                if coin.intersects(self.player.x, self.player.y, self.player.width, self.player.height) {
+                    coin.collected = true;
+                    self.events.push(GameEvent::DroppedCoinRecovered { coin: i });
+                }
+            }
+
+            // Stepping on a challenge pad starts its countdown
+            for (i, challenge) in self.challenges.iter_mut().enumerate() {
+                if challenge.intersects(self.player.x, self.player.y, self.player.width, self.player.height) && challenge.start() {
+                    self.events.push(GameEvent::ChallengeStarted { challenge: i });
+                }
+                if challenge.update(delta_time) {
+                    self.events.push(GameEvent::ChallengeFailed { challenge: i });
+                }
+            }
+            self.goal_flash = (self.goal_flash - delta_time).max(0.0);
+
+            for popup in self.popups.iter_mut() {
+                popup.y -= POPUP_RISE_SPEED * delta_time;
+                popup.timer -= delta_time;
+            }
+            self.popups.retain(|popup| popup.timer > 0.0);
+
+            for effect in self.effects.iter_mut() {
+                effect.update(delta_time);
+            }
+            self.effects.retain(|effect| !effect.animation.is_finished());
+
+            // Debris falls onto whatever is still standing
+            let (_, standing) = self.solids();
+            for piece in self.debris.iter_mut() {
+                piece.update(&standing, delta_time);
+            }
+            self.debris.retain(|piece| piece.life > 0.0);
+
+            // Keep player within the level (basic boundary checking)
+            let level_width = self.level_width();
+            if self.player.x < 0.0 {
+                self.player.x = 0.0;
+                self.player.velocity_x = 0.0;
+            }
+            if self.player.x + self.player.width > level_width {
+                self.player.x = level_width - self.player.width;
+                self.player.velocity_x = 0.0;
+            }
+
+            // Falling off screen costs the player their life
+            if self.player.y > self.view_height {
+                self.kill_player();
+            }
+            
+            // Mirrors move with the player's inputs from a moment ago
+            for enemy in self.enemies.iter_mut().filter(|e| e.alive) {
+                enemy.follow_mirror(&shapes, input, delta_time);
+            }
+
+            // Enemies are processed in a fixed order so a step always plays out the same:
+            //  1. every enemy present at the start of the step updates, in index order
+            //  2. collisions are judged against the player as they were before the pass,
+            //     so stomping one enemy can't change how the next one treats the player
+            //  3. enemies spawned by splits are appended only after every pass, and first
+            //     update next step; defeated enemies stay in place, so indices are stable
+            //  Enemies well outside the view only update every few steps, catching up on
+            //  the time they skipped, staggered by index so the work is spread out.
+            //  Anything near the screen updates every step, so nothing visible jumps.
+            self.update_tick = self.update_tick.wrapping_add(1);
+            let near = (self.camera.x - FAR_UPDATE_MARGIN, self.camera.x + self.view_width + FAR_UPDATE_MARGIN);
+            for (index, enemy) in self.enemies.iter_mut().enumerate().filter(|(_, e)| e.alive) {
+                enemy.pending_time += delta_time;
+                let is_near = enemy.x + enemy.width > near.0 && enemy.x < near.1;
+                if is_near || (self.update_tick as usize + index).is_multiple_of(FAR_UPDATE_INTERVAL) {
+                    let elapsed = std::mem::take(&mut enemy.pending_time);
+                    enemy.update(elapsed);
+                }
+            }
+
+            // Hammer Bros lob hammers towards the player
+            let player_center = self.player.x + self.player.width / 2.0;
+            let mut thrown = Vec::new();
+            for enemy in self.enemies.iter_mut().filter(|e| e.is_interactive()) {
+                if enemy.ready_to_throw(delta_time) {
+                    let center = enemy.x + enemy.width / 2.0;
+                    thrown.push(Projectile::hammer(center, enemy.y, player_center > center));
+                }
+            }
+            for projectile in thrown {
+                self.spawn_projectile(projectile);
+            }
+
+            // Check enemy collisions: landing on top stomps, anything else hurts the player
+            let mut player_hit = false;
+            let mut stomped = false;
+            let mut spawned = Vec::new();
+            for (index, enemy) in self.enemies.iter_mut().enumerate().filter(|(_, e)| e.is_interactive()) {
+                if enemy.collider.interacts_with(&self.player.collider)
+                    && enemy.intersects(self.player.x, self.player.y, self.player.width, self.player.height)
+                {
+                    if enemy.is_stomped_by(&self.player, delta_time) {
+                        stomped = true;
+                        // Armored enemies are pushed away from the player until their last hit
+                        let away = if enemy.x + enemy.width / 2.0 < self.player.x + self.player.width / 2.0 {
+                            EnemyDirection::Left
+                        } else {
+                            EnemyDirection::Right
+                        };
+                        if !enemy.take_hit(away, STOMP_KNOCKBACK) {
+                            self.events.push(GameEvent::EnemyHit { enemy: index });
+                            continue;
+                        }
+                        spawned.extend(enemy.split().into_iter().flatten());
+                        self.events.push(GameEvent::EnemyStomped { enemy: index });
+                    } else {
+                        player_hit = true;
+                    }
+                }
+            }
+            if stomped {
+                self.player.velocity_y = -STOMP_BOUNCE;
+                self.player.jumping = false;
+            }
+            // Fireballs burn enemies (armored ones survive a few and get knocked back)
+            for fireball in self.fireballs.iter_mut() {
+                let hit = self
+                    .enemies
+                    .iter_mut()
+                    .enumerate()
+                    .find(|(_, e)| {
+                        e.is_interactive()
+                            && fireball.collider.interacts_with(&e.collider)
+                            && fireball.intersects(e.x, e.y, e.width, e.height)
+                    });
+                if let Some((index, enemy)) = hit {
+                    fireball.extinguish();
+                    let direction = if fireball.velocity_x < 0.0 { EnemyDirection::Left } else { EnemyDirection::Right };
+                    if enemy.take_hit(direction, FIREBALL_KNOCKBACK) {
+                        self.events.push(GameEvent::EnemyBurned { enemy: index });
+                    } else {
+                        self.events.push(GameEvent::EnemyHit { enemy: index });
+                    }
+                }
+            }
+
+            // Thrown items knock out enemies in their path
+            for item in self.items.iter_mut().filter(|i| i.is_thrown()) {
+                let hit = self
+                    .enemies
+                    .iter_mut()
+                    .enumerate()
+                    .find(|(_, e)| {
+                        e.is_interactive() && item.collider.interacts_with(&e.collider) && item.intersects(e.x, e.y, e.width, e.height)
+                    });
+                if let Some((index, enemy)) = hit {
+                    item.hit_enemy();
+                    let direction = if item.velocity_x < 0.0 { EnemyDirection::Left } else { EnemyDirection::Right };
+                    if enemy.take_hit(direction, FIREBALL_KNOCKBACK) {
+                        self.events.push(GameEvent::EnemyKnockedOut { enemy: index });
+                    } else {
+                        self.events.push(GameEvent::EnemyHit { enemy: index });
+                    }
+                }
+            }
+
+            // Enemy projectiles fly, clash with fireballs and hurt the player
+            for projectile in self.projectiles.iter_mut() {
+                projectile.update(delta_time);
+            }
+            for fireball in self.fireballs.iter_mut() {
+                for projectile in self.projectiles.iter_mut().filter(|p| p.is_alive()) {
+                    if !fireball.is_alive()
+                        || !fireball.collider.interacts_with(&projectile.collider)
+                        || !projectile.intersects(fireball.x, fireball.y, FIREBALL_SIZE, FIREBALL_SIZE)
+                    {
+                        continue;
+                    }
+                    match self.clash_rules.outcome(projectile.kind) {
+                        ClashOutcome::BothDestroyed => {
+                            fireball.extinguish();
+                            projectile.destroy();
+                        }
+                        ClashOutcome::FireballDestroyed => fireball.extinguish(),
+                        ClashOutcome::ProjectileDestroyed => projectile.destroy(),
+                        ClashOutcome::PassThrough => {}
+                    }
+                }
+            }
+            // They also smash destructible platforms they hit
+            let mut smashed = Vec::new();
+            for projectile in self.projectiles.iter_mut().filter(|p| p.is_alive()) {
+                let hit = self.platforms.iter().position(|p| {
+                    let at = p.at_time(self.level_time);
+                    p.destructible && p.is_solid_at(self.level_time) && projectile.intersects(at.x, at.y, at.width, at.height)
+                });
+                if let Some(platform) = hit {
+                    projectile.destroy();
+                    smashed.push(platform);
+                }
+            }
+            for platform in smashed {
+                self.destroy_platform(platform);
+            }
+            let (px, py, pw, ph) = (self.player.x, self.player.y, self.player.width, self.player.height);
+            let player_collider = self.player.collider;
+            let touching =
+                |p: &&mut Projectile| p.is_alive() && p.collider.interacts_with(&player_collider) && p.intersects(px, py, pw, ph);
+            for projectile in self.projectiles.iter_mut().filter(touching) {
+                if !self.player.is_invulnerable() {
+                    projectile.destroy();
+                    player_hit = true;
+                }
+            }
+            self.projectiles.retain(|p| p.is_alive());
+            self.fireballs.retain(|f| f.is_alive());
+
+            // Enemies spawned by the collisions join in after the pass
+            self.enemies.extend(spawned);
+            if player_hit {
+                self.hurt_player();
+            }
+
+            self.process_events(first_event);
+            
+            // Ease the camera towards its target
+            let player_center = (self.player.x + self.player.width / 2.0, self.player.y + self.player.height / 2.0);
+            self.camera.facing_right = self.player.facing_right;
+            self.camera.update(&[player_center], self.view_width, self.view_height, delta_time);
+            
+            // Check if player reached the goal (once it's active)
+            if self.goal_active() && self.player.intersects(self.goal.x, self.goal.y, self.goal.width, self.goal.height) {
+                self.finish_level();
+            }
+        } else if let Some(finish_time) = self.finish_time.as_mut() {
+            // Play out the goal's finishing sequence while the camera closes in
+            *finish_time += delta_time;
+            self.goal.play_finish(&mut self.player, delta_time);
+            let player_center = (self.player.x + self.player.width / 2.0, self.player.y + self.player.height / 2.0);
+            self.camera.update(&[player_center], self.view_width, self.view_height, delta_time);
+
+            // Count the time and coins into the score once the results are up
+            if *finish_time >= GOAL_SEQUENCE_TIME
+                && let Some(tally) = self.tally.as_mut()
+            {
+                let ticks = tally.update(delta_time);
+                self.score = tally.score();
+                self.best_score = self.best_score.max(self.score);
+                self.events.extend(std::iter::repeat_n(GameEvent::TallyTick, ticks as usize));
+            }
+        }
+    }
+
+    /// Move the player and enemies standing on moving platforms along with
+    /// them, by as far as each platform is about to move this step. Enemies'
+    /// patrols move too, so they keep walking the same stretch of platform.
+    fn carry_riders(&mut self, delta_time: f32) {
+        let time = self.level_time;
+        for platform in self.platforms.iter().filter(|p| p.motion.is_some() && p.is_solid_at(time)) {
+            let (dx, dy) = platform.carry_delta(time, delta_time);
+            let current = platform.at_time(time);
+
+            let player = &mut self.player;
+            if player.velocity_y >= 0.0 && current.carries(player.x, player.width, player.y + player.height) {
+                player.x += dx;
+                player.y += dy;
+            }
+            for enemy in self.enemies.iter_mut().filter(|e| e.alive && current.carries(e.x, e.width, e.y + e.height)) {
+                enemy.x += dx;
+                enemy.y += dy;
+                enemy.patrol_start += dx;
+                enemy.patrol_end += dx;
+            }
+        }
+    }
+
+    /// Everything the player collides with: platforms, intact blocks and any
+    /// doors still locked, along with where each shape came from
+    fn solids(&self) -> (Vec<Solid>, Vec<Platform>) {
+        let platforms = self
+            .platforms
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.is_solid_at(self.level_time))
+            .map(|(i, p)| (Solid::Platform(i), p.at_time(self.level_time)));
+        let blocks = self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.is_solid(self.switch_group_active(block)))
+            .map(|(i, block)| (Solid::Block(i), block.as_platform()));
+        let locked_doors = self
+            .doors
+            .iter()
+            .filter(|door| !door.is_open(&self.player.keys))
+            .map(|door| (Solid::Door, door.as_platform()));
+
+        platforms.chain(blocks).chain(locked_doors).unzip()
+    }
+
+    /// React to the events raised during this substep, from index `first` on.
+    /// Reactions may raise further events, which are handled in the same pass.
+    fn process_events(&mut self, first: usize) {
+        let mut i = first;
+        while i < self.events.len() {
+            if let GameEvent::BlockHitFromBelow { block } = self.events[i] {
+                match self.blocks[block].kind {
+                    BlockKind::Question | BlockKind::MultiCoin => {
+                        for _ in 0..self.blocks[block].dispense_coin() {
+                            self.events.push(GameEvent::CoinDispensed { block });
+                        }
+                    }
+                    BlockKind::Used | BlockKind::Spring | BlockKind::Outline { .. } => self.blocks[block].bump(),
+                    BlockKind::Switch { group } => {
+                        self.blocks[block].bump();
+                        let active = !self.active_switch_groups.remove(&group);
+                        if active {
+                            self.active_switch_groups.insert(group);
+                        }
+                        self.events.push(GameEvent::SwitchToggled { group, active });
+                    }
+                    BlockKind::Brick => {
+                        // Only big Mario is strong enough to break bricks
+                        if self.player.power == PowerUp::Small {
+                            self.blocks[block].bump();
+                            self.events.push(GameEvent::BrickBumped { block });
+                        } else {
+                            self.blocks[block].shatter();
+                            self.events.push(GameEvent::BrickBroken { block });
+                        }
+                    }
+                }
+            }
+            match self.events[i] {
+                GameEvent::CoinDispensed { block } => {
+                    self.bank_coin();
+                    let points = self.settings.scoring.coin_points();
+                    self.score += points;
+                    let block = &self.blocks[block];
+                    self.spawn_popup(block.x, block.y - 10.0, points);
+                    self.count_challenge_coin();
+                }
+                // The coin was banked and scored when first collected, so only the count comes back
+                GameEvent::DroppedCoinRecovered { .. } => self.coins_collected += 1,
+                GameEvent::BigCoinCollected { coin } => {
+                    self.big_coins_collected += 1;
+                    self.progress.find_big_coin(&self.data.name, coin);
+                    let points = self.settings.scoring.big_coin_points();
+                    self.score += points;
+                    let coin = &self.big_coins[coin];
+                    self.spawn_popup(coin.x, coin.y, points);
+                }
+                GameEvent::PickupCollected { pickup } => {
+                    let pickup = self.pickups[pickup];
+                    match pickup.loot {
+                        Loot::Coin => {
+                            self.bank_coin();
+                            let points = self.settings.scoring.coin_points();
+                            self.score += points;
+                            self.count_challenge_coin();
+                            self.spawn_popup(pickup.x, pickup.y, points);
+                        }
+                        // Power-ups only ever upgrade: a mushroom does nothing for Fire Mario
+                        Loot::PowerUp(power) => {
+                            if power == PowerUp::FireFlower || self.player.power == PowerUp::Small {
+                                self.player.set_power(power);
+                            }
+                        }
+                        Loot::SpeedShoes => self.player.speed_timer = SPEED_SHOES_TIME,
+                    }
+                }
+                GameEvent::CoinCollected { coin } => {
+                    self.bank_coin();
+                    let points = self.settings.scoring.coin_points();
+                    self.score += points;
+                    self.count_challenge_coin();
+                    let coin = &self.coins[coin];
+                    self.spawn_popup(coin.x, coin.y, points);
+                    if self.objective == Objective::CollectAllCoins && self.goal_active() {
+                        self.goal_flash = GOAL_FLASH_TIME;
+                        self.events.push(GameEvent::GoalActivated);
+                    }
+                }
+                GameEvent::ChallengeCompleted { challenge } => {
+                    let challenge = &self.challenges[challenge];
+                    let (x, y) = (challenge.x, challenge.y);
+                    match challenge.reward {
+                        ChallengeReward::ExtraLife => self.lives += 1,
+                        ChallengeReward::Points(points) => {
+                            let points = self.settings.scoring.award(points);
+                            self.score += points;
+                            self.spawn_popup(x, y - 10.0, points);
+                        }
+                    }
+                }
+                GameEvent::EnemyStomped { enemy }
+                | GameEvent::EnemyBurned { enemy }
+                | GameEvent::EnemyKnockedOut { enemy } => {
+                    let enemy = &self.enemies[enemy];
+                    let points = match self.events[i] {
+                        GameEvent::EnemyStomped { .. } => {
+                            self.stomp_combo += 1;
+                            self.settings.scoring.stomp_points(enemy.points, self.stomp_combo)
+                        }
+                        _ => self.settings.scoring.award(enemy.points),
+                    };
+                    let (x, y, size) = (enemy.x + enemy.width / 2.0, enemy.y + enemy.height / 2.0, enemy.width);
+                    // Armored enemies and mirrors go out with a bang, the rest get squashed
+                    let effect = match enemy.kind {
+                        EnemyKind::Armored | EnemyKind::Mirror => Effect::explosion(x, y, size),
+                        EnemyKind::Goomba | EnemyKind::Splitter | EnemyKind::HammerBro => Effect::squash(x, y, size),
+                    };
+                    let popup_y = enemy.y;
+                    let drop = enemy.loot.as_ref().and_then(|loot| loot.roll(&mut self.loot_rng));
+                    self.score += points;
+                    self.spawn_popup(x, popup_y, points);
+                    self.spawn_effect(effect);
+                    if let Some(loot) = drop {
+                        self.pickups.push(Pickup::centred(x, y, loot));
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Whether the switch group a block belongs to is toggled on
+    fn switch_group_active(&self, block: &Block) -> bool {
+        block.switch_group().is_some_and(|group| self.active_switch_groups.contains(&group))
+    }
+
+    /// Pick up an item in reach while grab is held, keep a carried item in
+    /// the player's hands, and throw it the way they face once grab is let go
+    fn update_held_item(&mut self, input: &InputState) {
+        let player = &mut self.player;
+        let (x, y, width, height) = (player.x, player.y, player.width, player.height);
+        match player.held {
+            Some(index) if input.grab_held => self.items[index].carry(x, y, width, height, player.facing_right),
+            Some(index) => {
+                self.items[index].throw(player.facing_right);
+                player.held = None;
+            }
+            None if input.grab_held => {
+                if let Some(index) = self.items.iter().position(|item| item.in_reach(x, y, width, height)) {
+                    self.items[index].carry(x, y, width, height, player.facing_right);
+                    player.held = Some(index);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// The item the player is carrying, if any
+    pub fn held_item(&self) -> Option<&Item> {
+        self.player.held.map(|index| &self.items[index])
+    }
+
+    /// Throw a fireball from the player's hand if they have the fire flower,
+    /// the last throw's cooldown is over and they haven't already got the
+    /// maximum number out
+    pub fn throw_fireball(&mut self) -> bool {
+        let cooling_down = self.player.fire_cooldown > 0.0;
+        if self.player.power != PowerUp::FireFlower || cooling_down || self.fireballs.len() >= MAX_FIREBALLS {
+            return false;
+        }
+        self.player.fire_cooldown = FIRE_COOLDOWN;
+
+        let x = if self.player.facing_right { self.player.x + self.player.width } else { self.player.x - FIREBALL_SIZE };
+        let fireball = Fireball::new(x, self.player.y + self.player.height / 3.0, self.player.facing_right);
+        let index = self.fireballs.acquire();
+        if let Some(slot) = self.fireballs.get_mut(index) {
+            *slot = fireball;
+        }
+        true
+    }
+
+    /// Number of fireballs currently in flight
+    pub fn fireball_count(&self) -> usize {
+        self.fireballs.len()
+    }
+
+    /// Put an enemy projectile into play
+    pub fn spawn_projectile(&mut self, projectile: Projectile) {
+        let index = self.projectiles.acquire();
+        if let Some(slot) = self.projectiles.get_mut(index) {
+            *slot = projectile;
+        }
+    }
+
+    /// Number of enemy projectiles currently in flight
+    pub fn projectile_count(&self) -> usize {
+        self.projectiles.len()
+    }
+
+    /// Count a coin towards any running challenges, queueing an event for each one it completes
+    fn count_challenge_coin(&mut self) {
+        for (i, challenge) in self.challenges.iter_mut().enumerate() {
+            if challenge.coin_collected() {
+                self.events.push(GameEvent::ChallengeCompleted { challenge: i });
+            }
+        }
+    }
+
+    /// Smash a platform: it stops being solid and breaks into debris.
+    /// Returns false if there's no such platform or it's already gone.
+    pub fn destroy_platform(&mut self, index: usize) -> bool {
+        let Some(platform) = self.platforms.get_mut(index).filter(|p| !p.destroyed) else { return false };
+        platform.destroyed = true;
+        let pieces = Debris::from_platform(&platform.at_time(self.level_time));
+        for piece in pieces {
+            let slot = self.debris.acquire();
+            if let Some(slot) = self.debris.get_mut(slot) {
+                *slot = piece;
+            }
+        }
+        self.events.push(GameEvent::PlatformDestroyed { platform: index });
+        true
+    }
+
+    /// Chunks of destroyed platforms still falling or fading
+    pub fn debris(&self) -> impl Iterator<Item = &Debris> {
+        self.debris.iter()
+    }
+
+    /// Start playing an effect; it removes itself once its animation ends
+    pub fn spawn_effect(&mut self, effect: Effect) {
+        let index = self.effects.acquire();
+        if let Some(slot) = self.effects.get_mut(index) {
+            *slot = effect;
+        }
+    }
+
+    /// Number of effects still playing
+    pub fn effect_count(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Show the points just scored floating up from a spot in the level
+    fn spawn_popup(&mut self, x: f32, y: f32, value: u32) {
+        let index = self.popups.acquire();
+        if let Some(popup) = self.popups.get_mut(index) {
+            *popup = ScorePopup { x, y, value, timer: POPUP_TIME };
+        }
+    }
+
+    /// Events raised during the most recent step
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Number of decoration tiles across all background layers
+    pub fn background_tile_count(&self) -> usize {
+        self.background.iter().map(|layer| layer.tiles.len()).sum()
+    }
+
+    /// Name of the level's background music track
+    pub fn music(&self) -> Option<&str> {
+        self.music.as_deref()
+    }
+
+    /// The level's camera
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Change what the camera focuses on, e.g. a fixed point for a cutscene
+    pub fn set_camera_target(&mut self, target: CameraTarget) {
+        self.camera.set_target(target);
+    }
+
+    /// Which control hints the player has worked through
+    pub fn tutorial(&self) -> &Tutorial {
+        &self.tutorial
+    }
+
+    /// How far right the level extends: the end of its furthest platform,
+    /// or the width of the view for levels narrower than that
+    pub fn level_width(&self) -> f32 {
+        self.platforms.iter().map(|p| p.x + p.width).fold(self.view_width, f32::max)
+    }
+
+    /// Check that the goal and every coin can be reached from the spawn point,
+    /// given how high and far the player can jump (see `reachability`)
+    pub fn reachability(&self) -> Reachability {
+        let limits = MovementLimits {
+            gravity: self.player.physics.gravity,
+            jump_velocity: self.player.physics.jump_strength,
+            spring_velocity: SPRING_MAX_LAUNCH,
+            run_speed: self.player.physics.run_speed,
+            player_width: self.player.width,
+            player_height: self.player.height,
+        };
+        let (sources, shapes) = self.solids();
+        let surfaces: Vec<Surface> = sources
+            .iter()
+            .zip(&shapes)
+            .map(|(source, shape)| Surface {
+                rect: Rect::new(shape.x, shape.y, shape.width, shape.height),
+                spring: matches!(source, Solid::Block(i) if self.blocks[*i].kind == BlockKind::Spring),
+            })
+            .collect();
+        let goal = Rect::new(self.goal.x, self.goal.y, self.goal.width, self.goal.height);
+        let coins: Vec<Rect> = self.coins.iter().map(|c| Rect::new(c.x, c.y, Coin::SIZE, Coin::SIZE)).collect();
+        reachability::analyze(&surfaces, self.spawn_point, goal, &coins, &limits)
+    }
+
+    /// Draw the level's layout (platforms, blocks, enemies, coins, goal and
+    /// player spawn) as a labelled SVG drawing
+    pub fn to_svg(&self) -> String {
+        let mut svg = SvgDocument::new(self.level_width(), self.view_height);
+        svg.rect("sky", 0.0, 0.0, self.level_width(), self.view_height, "#87ceeb");
+
+        for platform in self.platforms.iter().filter(|p| !p.destroyed) {
+            svg.rect("platform", platform.x, platform.y, platform.width, platform.height, "#8b4513");
+        }
+        for block in &self.blocks {
+            svg.rect("block", block.x, block.y, BLOCK_SIZE, BLOCK_SIZE, "#c86428");
+            svg.label(block.x, block.y - 2.0, &format!("{:?}", block.kind));
+        }
+        for enemy in &self.enemies {
+            svg.rect("enemy", enemy.x, enemy.y, enemy.width, enemy.height, "#654321");
+            svg.label(enemy.x, enemy.y - 2.0, &format!("{:?}", enemy.kind));
+        }
+        for coin in &self.coins {
+            let r = Coin::SIZE / 2.0;
+            svg.circle("coin", coin.x + r, coin.y + r, r, "#ffd700");
+        }
+        svg.rect("goal", self.goal.x, self.goal.y, self.goal.width, self.goal.height, "#00c000");
+        svg.label(self.goal.x, self.goal.y - 2.0, "Goal");
+
+        let (spawn_x, spawn_y) = self.spawn_point;
+        svg.rect("spawn", spawn_x, spawn_y, PLAYER_SIZE, PLAYER_SIZE, "#ff0000");
+        svg.label(spawn_x, spawn_y - 2.0, "Spawn");
+        svg.finish()
+    }
+
+    /// Write the level's layout to an SVG file
+    pub fn export_svg<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_svg())?;
+        Ok(())
+    }
+
+    /// Number of coins placed in the level
+    pub fn total_coins(&self) -> usize {
+        self.coins.len()
+    }
+
+    /// Whether touching the goal finishes the level. With the collect-all-coins
+    /// objective the goal only activates once every placed coin is picked up.
+    pub fn goal_active(&self) -> bool {
+        match self.objective {
+            Objective::ReachGoal => true,
+            Objective::CollectAllCoins => self.coins.iter().all(|c| c.collected),
+        }
+    }
+
+    /// Enemies in the level, including ones that have been defeated
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    /// Hurt the player: a big player shrinks and briefly can't be hurt again,
+    /// a small one loses a life. Does nothing while the player is recovering.
+    pub fn hurt_player(&mut self) {
+        if self.player.is_invulnerable() {
+            return;
+        }
+        if self.player.power == PowerUp::Small {
+            self.kill_player();
+        } else {
+            self.player.set_power(PowerUp::Small);
+            self.player.invuln_timer = HURT_INVULNERABLE_TIME;
+            self.events.push(GameEvent::PlayerHurt);
+        }
+    }
+
+    /// Send the player back to the spawn point, resetting the level's
+    /// entities first if the respawn policy asks for it
+    pub fn kill_player(&mut self) {
+        if self.respawn_policy == RespawnPolicy::Reset {
+            self.enemies = self.initial_enemies.clone();
+            for key in &mut self.keys {
+                key.collected = false;
+            }
+            self.player.keys.clear();
+            for coin in &mut self.coins {
+                coin.collected = false;
+            }
+            self.blocks = self.initial_blocks.clone();
+            for platform in &mut self.platforms {
+                platform.destroyed = false;
+            }
+            self.pickups.clear();
+            self.active_switch_groups.clear();
+            (self.score, self.coins_collected) = self.attempt_start;
+            self.bank.withdraw(std::mem::take(&mut self.coins_banked));
+        }
+        // Dying abandons any challenge in progress
+        for challenge in &mut self.challenges {
+            if let ChallengeState::Active { .. } = challenge.state {
+                challenge.state = ChallengeState::Ready;
+            }
+        }
+        // Anything the player was carrying falls where they were
+        if let Some(index) = self.player.held.take() {
+            self.items[index].let_go();
+        }
+        self.drop_coins();
+        let (x, y) = self.safe_spawn_position(self.spawn_point.0, self.spawn_point.1);
+        self.player.respawn(x, y);
+        self.events.push(GameEvent::PlayerDied);
+
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 {
+            self.game_over = true;
+            self.events.push(GameEvent::GameOver);
+            if self.settings.hardcore {
+                self.wipe_save();
+            }
+        }
+    }
+
+    /// Count a coin picked up this attempt and pay it into the bank
+    fn bank_coin(&mut self) {
+        self.coins_collected += 1;
+        self.bank.deposit(1);
+        self.coins_banked += 1;
+    }
+
+    /// Drop the set fraction of the coins collected this attempt where the
+    /// player is, replacing any left over from the last death. Coins dropped
+    /// into a pit are lost for good.
+    fn drop_coins(&mut self) {
+        self.dropped_coins.clear();
+        let count = (self.coins_collected as f32 * self.settings.death_coin_drop.clamp(0.0, 1.0)).floor() as u32;
+        if count == 0 {
+            return;
+        }
+        self.coins_collected -= count;
+        if self.player.y > self.view_height {
+            return;
+        }
+        // Fanned out in rows above where the player's feet were
+        let centre_x = self.player.x + self.player.width / 2.0 - Coin::SIZE / 2.0;
+        let bottom = self.player.y + self.player.height - Coin::SIZE;
+        for i in 0..count {
+            let (row, column) = (i / DROPPED_COINS_PER_ROW, i % DROPPED_COINS_PER_ROW);
+            let in_row = (count - row * DROPPED_COINS_PER_ROW).min(DROPPED_COINS_PER_ROW);
+            let offset = column as f32 - (in_row - 1) as f32 / 2.0;
+            self.dropped_coins.push(Coin::new(
+                centre_x + offset * DROPPED_COIN_SPACING,
+                bottom - row as f32 * DROPPED_COIN_SPACING,
+            ));
+        }
+    }
+
+    /// Whether a hardcore run has lost every life, leaving nothing to do but go back to the menu
+    pub fn run_over(&self) -> bool {
+        self.settings.hardcore && self.game_over
+    }
+
+    /// Save everything kept between sessions: the coin bank, star ratings and level progress
+    pub fn save_progress(&self) {
+        if let Err(e) = self.bank.save(&self.settings.save_files.bank) {
+            eprintln!("Couldn't save the coin bank: {}", e);
+        }
+        if let Err(e) = self.star_record.save(&self.settings.save_files.stars) {
+            eprintln!("Couldn't save the star ratings: {}", e);
+        }
+        if let Err(e) = self.progress.save(&self.settings.save_files.progress) {
+            eprintln!("Couldn't save the level progress: {}", e);
+        }
+    }
+
+    /// Forget everything kept between sessions, on disk and in memory, so
+    /// saving on the way out doesn't bring it back
+    fn wipe_save(&mut self) {
+        self.bank = CoinBank::default();
+        self.star_record = StarRecord::default();
+        self.progress = LevelProgress::default();
+        if let Err(e) = self.settings.save_files.delete() {
+            eprintln!("Couldn't delete the save: {}", e);
+        }
+    }
+
+    /// The nearest spot to (x, y) where the player fits without overlapping
+    /// anything and has ground somewhere beneath them, placed on that ground.
+    /// Searches upwards, then left and right, in widening steps, and falls back
+    /// to (x, y) itself if nothing nearby is free.
+    fn safe_spawn_position(&self, x: f32, y: f32) -> (f32, f32) {
+        let (_, shapes) = self.solids();
+        let (width, height) = (self.player.width, self.player.height);
+        let fits = |x: f32, y: f32| !shapes.iter().any(|p| p.intersects(x, y, width, height));
+        let ground_below = |x: f32, y: f32| {
+            shapes
+                .iter()
+                .filter(|p| p.x < x + width && p.x + p.width > x && p.y >= y + height)
+                .map(|p| p.y)
+                .min_by(f32::total_cmp)
+        };
+
+        for step in 0..=SAFE_SPAWN_TRIES {
+            let offset = step as f32 * SAFE_SPAWN_STEP;
+            for (x, y) in [(x, y - offset), (x - offset, y), (x + offset, y)] {
+                if !fits(x, y) {
+                    continue;
+                }
+                if let Some(ground) = ground_below(x, y) {
+                    return (x, ground - height);
+                }
+            }
+        }
+        (x, y)
+    }
+
+    /// Spend banked coins to carry on after a game over, back at the spawn
+    /// point with full lives and everything else as it was. Does nothing
+    /// unless the game is over, or in hardcore mode, which has no continues.
+    pub fn continue_game(&mut self) -> Result<(), InsufficientCoins> {
+        if !self.game_over || self.settings.hardcore {
+            return Ok(());
+        }
+        self.bank.spend(CONTINUE_COST)?;
+        self.lives = STARTING_LIVES;
+        self.game_over = false;
+        Ok(())
+    }
+
+    /// Mark the level as won and award a medal for the finishing time
+    pub fn finish_level(&mut self) {
+        self.game_won = true;
+        if self.finish_time.is_none() {
+            self.finish_time = Some(0.0);
+            // Pan from the current view onto the goal, zooming in, and hold there
+            let from = (self.camera.x + self.view_width / 2.0, self.camera.y + self.view_height / 2.0);
+            let goal = (self.goal.x + self.goal.width / 2.0, self.goal.y + self.goal.height / 2.0);
+            self.camera.set_target(CameraTarget::Path(CameraPath::new(vec![
+                CameraKeyframe::new(0.0, from.0, from.1),
+                CameraKeyframe::new(GOAL_PAN_TIME, goal.0, goal.1).zoomed(GOAL_ZOOM),
+                CameraKeyframe::new(GOAL_SEQUENCE_TIME, goal.0, goal.1).zoomed(GOAL_ZOOM),
+            ])));
+
+            let collected = self.coins.iter().filter(|c| c.collected).count();
+            let run = RunResult {
+                coins: if self.coins.is_empty() { 1.0 } else { collected as f32 / self.coins.len() as f32 },
+                time: self.level_time,
+                deaths: self.stats.deaths,
+            };
+            let stars = rating::star_rating(&run, &self.star_thresholds);
+            self.stars = Some(stars);
+            self.star_record.record(&self.data.name, stars);
+
+            // Whole seconds left under par and every coin count towards the score
+            let time_left = (self.star_thresholds.par_time - self.level_time).max(0.0).ceil() as u32;
+            let (time_points, coin_points) = (self.settings.scoring.time_bonus_points(), self.settings.scoring.coin_bonus_points());
+            self.tally = Some(Tally::new(self.score, time_left, self.coins_collected, time_points, coin_points));
+        }
+        self.best_score = self.best_score.max(self.score);
+        if self.settings.time_attack {
+            self.medal = self.medal_thresholds.and_then(|t| t.award(self.level_time));
+        }
+    }
+
+    /// How far the camera is zoomed in, and the top edge of the zoomed view on
+    /// the screen. The view stays within the screen's normal height.
+    pub fn camera_zoom(&self) -> (f32, f32) {
+        let zoom = self.camera.zoom.max(1.0);
+        let top = self.camera.y.clamp(0.0, self.view_height - self.view_height / zoom);
+        (zoom, top)
+    }
+
+    /// Draw the game straight into the window
+    pub fn draw(&self) {
+        self.draw_world(&ViewCamera::new(macroquad::prelude::Rect::new(0.0, 0.0, screen_width(), screen_height())));
+        self.draw_overlay();
+    }
+
+    /// What `draw_world` draws, in order: decoration layers and trees behind
+    /// the level, the level itself, then the player and any foreground layers
+    /// in whichever order `player_behind_foreground` asks for
+    pub fn draw_passes(&self) -> Vec<DrawPass> {
+        let layers = |foreground: bool| {
+            let matching = self.background.iter().enumerate().filter(move |(_, layer)| layer.foreground == foreground);
+            matching.map(|(i, _)| DrawPass::Decoration(i))
+        };
+        let mut passes: Vec<DrawPass> = layers(false).collect();
+        passes.push(DrawPass::Trees);
+        passes.push(DrawPass::Level);
+        if self.player_behind_foreground {
+            passes.push(DrawPass::Player);
+            passes.extend(layers(true));
+        } else {
+            passes.extend(layers(true));
+            passes.push(DrawPass::Player);
+        }
+        passes
+    }
+
+    /// Which layer a draw pass puts its pictures on
+    fn draw_layer(&self, pass: DrawPass) -> DrawLayer {
+        match pass {
+            DrawPass::Decoration(layer) if self.background[layer].foreground => DrawLayer::Foreground,
+            DrawPass::Decoration(_) | DrawPass::Trees => DrawLayer::Background,
+            DrawPass::Level | DrawPass::Player => DrawLayer::World,
+        }
+    }
+
+    /// Run `draw` for each of the `draw_passes` in turn, recording the layer
+    /// of each (which panics in debug builds if they're out of order)
+    fn run_draw_passes(&self, mut draw: impl FnMut(DrawPass)) -> DrawOrder {
+        let mut order = DrawOrder::default();
+        for pass in self.draw_passes() {
+            order.record(self.draw_layer(pass));
+            draw(pass);
+        }
+        order
+    }
+
+    /// Test hook: the layers `draw_world` draws, in order, found without drawing anything
+    pub fn capture_draw_order(&self) -> DrawOrder {
+        self.run_draw_passes(|_| {})
+    }
+
+    /// Draw the level and everything in it, as seen by the camera, through
+    /// the view `camera` has been set up with
+    pub fn draw_world(&self, camera: &ViewCamera) {
+        clear_background(SKYBLUE);
+
+        // Everything in the level goes through the camera's view to reach the screen
+        let view = ScreenTransform::new(WorldPos::new(self.camera_offset(), 0.0));
+        self.run_draw_passes(|pass| match pass {
+            DrawPass::Decoration(layer) => {
+                self.background[layer].draw(self.camera.x, self.view_width);
+            }
+            DrawPass::Trees => self.draw_trees(&view),
+            DrawPass::Level => self.draw_level(&view, camera),
+            DrawPass::Player => self.draw_player(&view, camera),
+        });
+
+        if self.settings.debug.hitboxes {
+            self.draw_hitboxes(&view);
+        }
+    }
+
+    /// Draw the trees behind the level
+    fn draw_trees(&self, view: &ScreenTransform) {
+        for tree in &self.trees {
+            let at = view.to_screen(WorldPos::new(tree.x, tree.y));
+            Tree::new(at.x, at.y, tree.height).draw();
+        }
+    }
+
+    /// Draw everything in the level but the player, trees and decoration layers
+    fn draw_level(&self, view: &ScreenTransform, camera: &ViewCamera) {
+        let alpha = self.render_alpha;
+        let screen = |x: f32, y: f32| view.to_screen(WorldPos::new(x, y));
+
+        // Draw platforms (skipping any that are off screen)
+        let platforms = self.platforms.iter().filter(|p| !p.destroyed).map(|p| p.at_time(self.level_time));
+        for platform in platforms.filter(|p| p.is_visible(self.camera.x, self.view_width)) {
+            let at = screen(platform.x, platform.y);
+            let on_screen = Platform::new(at.x, at.y, platform.width, platform.height);
+            if platform.is_solid_at(self.level_time) {
+                on_screen.draw();
+            } else {
+                // Blinked out: just a faint outline of where it will reappear
+                let p = &on_screen;
+                draw_rectangle_lines(p.x, p.y, p.width, p.height, 1.0, Color::new(0.3, 0.2, 0.1, 0.4));
+            }
+        }
+        
+        // Draw blocks
+        for block in &self.blocks {
+            let at = screen(block.x, block.y);
+            Block { x: at.x, y: at.y, ..block.clone() }.draw(self.switch_group_active(block));
+        }
+
+        // Draw doors and uncollected keys
+        for door in &self.doors {
+            let at = screen(door.x, door.y);
+            Door { x: at.x, y: at.y, ..door.clone() }.draw(door.is_open(&self.player.keys));
+        }
+        let scale = self.settings.render_scale;
+        for key in self.keys.iter().filter(|k| !k.collected) {
+            let at = screen(key.x, key.y);
+            camera.draw_scaled(centre(at.x, at.y, Key::SIZE), scale, || Key::new(key.id, at.x, at.y).draw());
+        }
+
+        // Draw enemies
+        for (i, enemy) in self.enemies.iter().enumerate().filter(|(_, e)| e.alive) {
+            let current = (enemy.x, enemy.y);
+            let previous = self.previous.enemies.get(i).copied().unwrap_or(current);
+            let (x, y) = interpolate(previous, current, alpha);
+            let (x, y) = self.snap(x, y);
+            let at = screen(x, y);
+            let anchor = bottom_centre(at.x, at.y, enemy.width, enemy.height);
+            let bounds = macroquad::prelude::Rect::new(at.x, at.y, enemy.width, enemy.height);
+            let flip = enemy.direction == EnemyDirection::Left;
+            camera.draw_scaled(anchor, scale, || {
+                let drawn = self.settings.enemy_sprites.as_ref().is_some_and(|sheet| sheet.draw(enemy.kind.tag(), self.level_time, bounds, flip));
+                if !drawn {
+                    Enemy { x: at.x, y: at.y, ..enemy.clone() }.draw();
+                }
+            });
+        }
+        
+        for coin in self.coins.iter().filter(|c| !c.collected) {
+            let at = screen(coin.x, coin.y);
+            camera.draw_scaled(centre(at.x, at.y, Coin::SIZE), scale, || Coin::new(at.x, at.y).draw());
+        }
+        for coin in self.dropped_coins.iter().filter(|c| !c.collected) {
+            let at = screen(coin.x, coin.y);
+            camera.draw_scaled(centre(at.x, at.y, Coin::SIZE), scale, || Coin::new(at.x, at.y).draw());
+        }
+        for pickup in self.pickups.iter().filter(|p| !p.collected) {
+            let at = screen(pickup.x, pickup.y);
+            camera.draw_scaled(centre(at.x, at.y, Pickup::SIZE), scale, || Pickup::draw(pickup.loot, at.x, at.y));
+        }
+        for (i, coin) in self.big_coins.iter().enumerate().filter(|(_, c)| !c.collected) {
+            let at = screen(coin.x, coin.y);
+            let found_before = self.progress.has_big_coin(&self.data.name, i);
+            camera.draw_scaled(centre(at.x, at.y, BigCoin::SIZE), scale, || BigCoin::draw(at.x, at.y, found_before));
+        }
+
+        for challenge in &self.challenges {
+            challenge.draw(view);
+        }
+
+        // Draw goal (hidden until it's active)
+        if self.goal_active() {
+            let at = screen(self.goal.x, self.goal.y);
+            let goal_copy = Goal { x: at.x, y: at.y, ..self.goal.clone() };
+            goal_copy.draw(self.goal_flash, self.finish_time);
+        }
+        
+        for fireball in self.fireballs.iter() {
+            let at = screen(fireball.x, fireball.y);
+            camera.draw_scaled(centre(at.x, at.y, FIREBALL_SIZE), scale, || Fireball { x: at.x, y: at.y, ..fireball.clone() }.draw());
+        }
+
+        for item in self.items.iter().filter(|i| i.state != ItemState::Gone) {
+            let at = screen(item.x, item.y);
+            camera.draw_scaled(bottom_centre(at.x, at.y, ITEM_SIZE, ITEM_SIZE), scale, || item.draw(view));
+        }
+
+        for projectile in self.projectiles.iter() {
+            let at = screen(projectile.x, projectile.y);
+            let anchor = centre(at.x, at.y, projectile.size());
+            camera.draw_scaled(anchor, scale, || Projectile { x: at.x, y: at.y, ..projectile.clone() }.draw());
+        }
+
+        for effect in self.effects.iter() {
+            effect.draw(view);
+        }
+        for piece in self.debris.iter() {
+            piece.draw(view);
+        }
+
+        for popup in self.popups.iter() {
+            let alpha = popup.timer / POPUP_TIME;
+            let at = screen(popup.x, popup.y);
+            draw_text(&popup.value.to_string(), at.x, at.y, 16.0, Color::new(1.0, 1.0, 1.0, alpha));
+        }
+    }
+
+    /// Draw the player and their shadow
+    fn draw_player(&self, view: &ScreenTransform, camera: &ViewCamera) {
+        let (alpha, scale) = (self.render_alpha, self.settings.render_scale);
+        let screen = |x: f32, y: f32| view.to_screen(WorldPos::new(x, y));
+
+        // Draw the player's shadow on whatever is below them
+        let (player_x, player_y) = interpolate(self.previous.player, (self.player.x, self.player.y), alpha);
+        let (player_x, player_y) = self.snap(player_x, player_y);
+        let (_, solids) = self.solids();
+        let feet_y = player_y + self.player.height;
+        if let Some(ground_y) = shadow::ground_below(player_x, self.player.width, feet_y, &solids) {
+            let at = screen(player_x + self.player.width / 2.0, ground_y);
+            shadow::draw_shadow(at.x, at.y, self.player.width, ground_y - feet_y);
+        }
+
+        // Speed shoes leave a fading trail of afterimages behind a running player
+        let at = screen(player_x, player_y);
+        if self.player.speed_timer > 0.0 {
+            for i in 1..=SPEED_TRAIL_LENGTH {
+                let behind = self.player.velocity_x * SPEED_TRAIL_GAP * i as f32;
+                let fade = 0.4 * (1.0 - i as f32 / (SPEED_TRAIL_LENGTH + 1) as f32);
+                draw_rectangle(at.x - behind, at.y, self.player.width, self.player.height, Color::new(1.0, 0.3, 0.2, fade));
+            }
+        }
+
+        // Draw player (on top of everything, unless the level puts its
+        // foreground layers in front; `draw_passes` decides the order)
+        let mut player_copy = Player::new(at.x, at.y);
+        player_copy.height = self.player.height;
+        player_copy.power = self.player.power;
+        player_copy.facing_right = self.player.facing_right;
+        player_copy.animation_state = self.player.animation_state;
+        player_copy.animation_timer = self.player.animation_timer;
+        player_copy.invuln_timer = self.player.invuln_timer;
+        let through_door = self.goal.kind == GoalKind::Door && self.finish_time.is_some_and(|t| t >= DOOR_ENTER_TIME);
+        if !through_door {
+            let bounds = macroquad::prelude::Rect::new(at.x, at.y, self.player.width, self.player.height);
+            let (tag, time) = (self.player.animation_state.tag(), self.player.animation_timer);
+            camera.draw_scaled(self.player_anchor(at.x, at.y), scale, || {
+                // A sprite sheet takes over for the animations it has
+                let drawn = self.settings.player_sprites.as_ref().is_some_and(|sheet| sheet.draw(tag, time, bounds, !self.player.facing_right));
+                if !drawn {
+                    player_copy.draw();
+                }
+            });
+        }
+    }
+
+    /// The point the player is drawn scaled about when their top-left corner
+    /// is at (x, y): the middle of their feet, so they stand on the same spot
+    fn player_anchor(&self, x: f32, y: f32) -> Vec2 {
+        bottom_centre(x, y, self.player.width, self.player.height)
+    }
+
+    /// Where `draw_player` draws the player through `camera`: their hitbox
+    /// scaled by the render scale about their feet
+    pub fn drawn_player_bounds(&self, camera: &ViewCamera) -> macroquad::prelude::Rect {
+        let bounds = macroquad::prelude::Rect::new(self.player.x, self.player.y, self.player.width, self.player.height);
+        camera.drawn_bounds(bounds, self.player_anchor(bounds.x, bounds.y), self.settings.render_scale)
+    }
+
+    /// Draw the HUD, menus and console on top of the level, unaffected by camera zoom
+    pub fn draw_overlay(&self) {
+        self.draw_ui();
+        if self.console.is_open() {
+            self.console.draw(self.view_width);
+        }
+    }
+
+    /// Left edge of the view as drawn this frame: the camera's position
+    /// between the last two physics steps, rounded to a whole pixel with
+    /// `snap_camera` (and to the low-res grid in pixel-perfect mode). The
+    /// camera itself keeps easing smoothly underneath.
+    pub fn camera_offset(&self) -> f32 {
+        let (camera_x, _) = interpolate((self.previous.camera_x, 0.0), (self.camera.x, 0.0), self.render_alpha);
+        let camera_x = if self.settings.snap_camera { camera_x.round() } else { camera_x };
+        self.snap(camera_x, 0.0).0
+    }
+
+    /// Round a drawing position to the low-res pixel grid in pixel-perfect mode
+    fn snap(&self, x: f32, y: f32) -> (f32, f32) {
+        if self.settings.pixel_perfect {
+            (snap_to_grid(x), snap_to_grid(y))
+        } else {
+            (x, y)
+        }
+    }
+
+    /// Outline every collision box: solids in white, enemies in red, the player in green
+    fn draw_hitboxes(&self, view: &ScreenTransform) {
+        let outline = |x: f32, y: f32, width: f32, height: f32, color: Color| {
+            let at = view.to_screen(WorldPos::new(x, y));
+            draw_rectangle_lines(at.x, at.y, width, height, 1.0, color);
+        };
+        let (_, solids) = self.solids();
+        for solid in &solids {
+            outline(solid.x, solid.y, solid.width, solid.height, WHITE);
+        }
+        for enemy in self.enemies.iter().filter(|e| e.alive) {
+            outline(enemy.x, enemy.y, enemy.width, enemy.height, RED);
+        }
+        let player = &self.player;
+        outline(player.x, player.y, player.width, player.height, GREEN);
+    }
+
+    /// Draw the user interface
+    fn draw_ui(&self) {
+        let stats = format!("Coins: {}  Score: {}  Best: {}  Lives: {}", self.coins_collected, self.score, self.best_score, self.lives);
+        draw_text(&stats, 10.0, 30.0, 20.0, GOLD);
+        let mut line_y = 55.0;
+        if self.objective == Objective::CollectAllCoins && !self.goal_active() {
+            let remaining = self.coins.iter().filter(|c| !c.collected).count();
+            draw_text(&format!("Collect all coins to reveal the goal ({} left)", remaining), 10.0, line_y, 20.0, WHITE);
+            line_y += 25.0;
+        }
+
+        // The objective for the first few seconds, and control hints until the player has tried each control
+        let can_fire = self.player.power == PowerUp::FireFlower;
+        for (step, alpha) in self.tutorial.visible_hints(can_fire) {
+            let hint = step.hint(self.settings.input_device, &self.settings.key_bindings, self.goal.kind);
+            draw_text(&hint, 10.0, line_y, 20.0, Color::new(1.0, 1.0, 1.0, alpha));
+            line_y += 25.0;
+        }
+        for challenge in &self.challenges {
+            if let ChallengeState::Active { time_left, collected } = challenge.state {
+                let text = format!("Challenge: {}/{} coins - {:.1}s", collected, challenge.target, time_left);
+                draw_text(&text, self.view_width - 260.0, 30.0, 20.0, YELLOW);
+            }
+        }
+
+        // Pause overlay
+        if self.state == GameState::Paused {
+            let text = "Paused";
+            let text_width = measure_text(text, None, 40, 1.0).width;
+            let x = (self.view_width - text_width) / 2.0;
+            let y = self.view_height / 2.0;
+
+            draw_rectangle(0.0, 0.0, self.view_width, self.view_height, Color::new(0.0, 0.0, 0.0, 0.4));
+            draw_text(text, x, y, 40.0, WHITE);
+            let resume = format!("Press {} to resume", action_prompt(Action::Pause, self.settings.input_device, &self.settings.key_bindings));
+            draw_text(&resume, x - 20.0, y + 30.0, 20.0, WHITE);
+            draw_text(&format!("Press {:?} to step one frame", FRAME_STEP_KEY), x - 20.0, y + 55.0, 16.0, LIGHTGRAY);
+        }
+
+        // Running timer for time attack
+        if self.settings.time_attack {
+            draw_text(&format!("Time: {:.2}", self.level_time), self.view_width - 150.0, 30.0, 24.0, WHITE);
+        }
+        
+        if let (Some(boss), Some(shown)) = (self.active_boss(), self.boss_bar) {
+            let name = boss.boss.as_deref().unwrap_or_default();
+            hud::draw_boss_health(shown, boss.max_health as f32, name, self.view_width);
+        }
+
+        if self.settings.hardcore && !self.game_over {
+            self.draw_hardcore_frame();
+        }
+
+        // Results screen
+        if self.game_over {
+            self.draw_game_over();
+        }
+
+        if self.game_won && self.finish_time.is_some_and(|t| t >= GOAL_SEQUENCE_TIME) {
+            let win_text = "Congratulations! You reached the goal!";
+            let text_width = measure_text(win_text, None, 40, 1.0).width;
+            let x = (self.view_width - text_width) / 2.0;
+            let y = self.view_height / 2.0;
+            let mut summary = self.stats.summary();
+            if let Some(tally) = self.tally {
+                let counted = [
+                    format!("Score: {}", tally.score()),
+                    format!("Time left: {}s", tally.time_left()),
+                    format!("Coins left: {}", tally.coins_left()),
+                ];
+                summary.splice(0..0, counted);
+            }
+            if !self.big_coins.is_empty() {
+                let found = self.progress.big_coins_found(&self.data.name);
+                summary.push(format!("Big coins: {}/{} (found {} ever)", self.big_coins_collected, self.big_coins.len(), found));
+            }
+            let panel_height = if self.settings.time_attack { 140.0 } else { 80.0 } + summary.len().div_ceil(2) as f32 * 20.0;
+            
+            // Background for text
+            draw_rectangle(x - 10.0, y - 30.0, text_width + 20.0, panel_height, Color::new(0.0, 0.0, 0.0, 0.7));
+            draw_text(win_text, x, y, 40.0, GOLD);
+            let restart = action_prompt(Action::Restart, self.settings.input_device, &self.settings.key_bindings);
+            let play_again = format!("Press {} to play again or ESC to quit", restart);
+
+            if self.settings.time_attack {
+                draw_text(&format!("Time: {:.2}s", self.level_time), x + 50.0, y + 30.0, 24.0, WHITE);
+                match self.medal {
+                    Some(medal) => {
+                        draw_circle(x + 260.0, y + 22.0, 10.0, medal.color());
+                        draw_text(&format!("{} medal!", medal.name()), x + 280.0, y + 30.0, 24.0, medal.color());
+                    }
+                    None => {
+                        draw_text("No medal - try again!", x + 230.0, y + 30.0, 24.0, LIGHTGRAY);
+                    }
+                }
+                draw_text(&play_again, x + 50.0, y + 60.0, 20.0, WHITE);
+            } else {
+                draw_text(&play_again, x + 50.0, y + 30.0, 20.0, WHITE);
+            }
+
+            // Stars for this run, with the best ever next to them
+            let stars_y = y + if self.settings.time_attack { 85.0 } else { 55.0 };
+            if let Some(stars) = self.stars {
+                rating::draw_stars(x + 100.0, stars_y, stars);
+                if let Some(best) = self.star_record.best(&self.data.name) {
+                    draw_text(&format!("Best: {}/3", best), x + 160.0, stars_y + 7.0, 20.0, LIGHTGRAY);
+                }
+            }
+
+            // Run stats in two columns below
+            let stats_y = stars_y + 35.0;
+            for (i, line) in summary.iter().enumerate() {
+                let column = (i % 2) as f32 * 250.0;
+                draw_text(line, x + 50.0 + column, stats_y + (i / 2) as f32 * 20.0, 18.0, LIGHTGRAY);
+            }
+        }
+    }
+
+    /// Queue sound effects for this step's events, each placed where it happened
+    fn play_event_sounds(&mut self) {
+        let player_x = self.player.x + self.player.width / 2.0;
+        let block_x = |block: usize| self.blocks.get(block).map_or(player_x, |b| b.x + BLOCK_SIZE / 2.0);
+        let enemy_x = |enemy: usize| self.enemies.get(enemy).map_or(player_x, |e| e.x + e.width / 2.0);
+        let sounds: Vec<(SoundId, Option<f32>)> = self
+            .events
+            .iter()
+            .filter_map(|event| match *event {
+                GameEvent::PlayerJumped => Some((SoundId::Jump, Some(player_x))),
+                GameEvent::CoinCollected { coin } => {
+                    Some((SoundId::Coin, Some(self.coins.get(coin).map_or(player_x, |c| c.x + Coin::SIZE / 2.0))))
+                }
+                GameEvent::CoinDispensed { block } => Some((SoundId::Coin, Some(block_x(block)))),
+                GameEvent::BlockHitFromBelow { block } => Some((SoundId::Bump, Some(block_x(block)))),
+                GameEvent::BrickBroken { block } => Some((SoundId::BrickBreak, Some(block_x(block)))),
+                GameEvent::PlatformDestroyed { platform } => {
+                    Some((SoundId::BrickBreak, Some(self.platforms.get(platform).map_or(player_x, |p| p.x + p.width / 2.0))))
+                }
+                GameEvent::SpringBounce { block, .. } => Some((SoundId::Spring, Some(block_x(block)))),
+                GameEvent::EnemyStomped { enemy }
+                | GameEvent::EnemyBurned { enemy }
+                | GameEvent::EnemyKnockedOut { enemy }
+                | GameEvent::EnemyHit { enemy } => Some((SoundId::Stomp, Some(enemy_x(enemy)))),
+                GameEvent::KeyCollected { .. } => Some((SoundId::Key, Some(player_x))),
+                GameEvent::BigCoinCollected { .. }
+                | GameEvent::PickupCollected { .. }
+                | GameEvent::DroppedCoinRecovered { .. } => Some((SoundId::Coin, Some(player_x))),
+                GameEvent::PlayerHurt => Some((SoundId::Hurt, Some(player_x))),
+                // The player has already been moved back to the spawn point
+                GameEvent::PlayerDied => Some((SoundId::Death, None)),
+                GameEvent::TallyTick => Some((SoundId::Tally, None)),
+                _ => None,
+            })
+            .collect();
+
+        self.sounds.set_listener(self.camera.x, self.view_width);
+        for (id, x) in sounds {
+            match x {
+                Some(x) => self.sounds.play_at(id, x),
+                None => self.sounds.play(id),
+            }
+        }
+    }
+
+    /// The first living boss that's on screen, if any
+    fn active_boss(&self) -> Option<&Enemy> {
+        let (left, right) = (self.camera.x, self.camera.x + self.view_width);
+        self.enemies.iter().find(|e| e.alive && e.boss.is_some() && e.x + e.width > left && e.x < right)
+    }
+
+    /// Show the boss bar while a boss is on screen, draining it towards the
+    /// boss's health so hits animate
+    fn update_boss_bar(&mut self, delta_time: f32) {
+        let health = self.active_boss().map(|boss| boss.health as f32);
+        self.boss_bar = health.map(|health| match self.boss_bar {
+            Some(shown) if shown > health => (shown - BOSS_BAR_DRAIN * delta_time).max(health),
+            _ => health,
+        });
+    }
+
+    /// Draw the game over screen offering a continue
+    fn draw_game_over(&self) {
+        let title = "GAME OVER";
+        let title_width = measure_text(title, None, 48, 1.0).width;
+        let y = self.view_height / 2.0;
+        draw_rectangle(0.0, y - 60.0, self.view_width, 130.0, Color::new(0.0, 0.0, 0.0, 0.8));
+        draw_text(title, (self.view_width - title_width) / 2.0, y - 15.0, 48.0, RED);
+
+        let jump = action_prompt(Action::Jump, self.settings.input_device, &self.settings.key_bindings);
+        if self.settings.hardcore {
+            let text = format!("Your save is gone. Press {} to return to the menu", jump);
+            let width = measure_text(&text, None, 22, 1.0).width;
+            draw_text(&text, (self.view_width - width) / 2.0, y + 20.0, 22.0, LIGHTGRAY);
+            return;
+        }
+        let restart = action_prompt(Action::Restart, self.settings.input_device, &self.settings.key_bindings);
+        let balance = self.bank.balance();
+        let (offer, color) = if balance >= CONTINUE_COST {
+            (format!("Press {} to continue for {} coins (bank: {})", jump, CONTINUE_COST, balance), GOLD)
+        } else {
+            (format!("Continuing costs {} coins, but the bank only has {}", CONTINUE_COST, balance), LIGHTGRAY)
+        };
+        for (i, (text, color)) in [(offer, color), (format!("Press {} to start over", restart), WHITE)].iter().enumerate() {
+            let width = measure_text(text, None, 22, 1.0).width;
+            draw_text(text, (self.view_width - width) / 2.0, y + 20.0 + 28.0 * i as f32, 22.0, *color);
+        }
+    }
+
+    /// Frame the screen in a red border that pulses faster the fewer lives are
+    /// left, so there's never any forgetting what's at stake
+    fn draw_hardcore_frame(&self) {
+        let rate = HARDCORE_PULSE_RATE * (1.0 + 2.0 / self.lives.max(1) as f32);
+        let pulse = 0.5 + 0.5 * (self.level_time * rate).sin();
+        let color = Color::new(0.8, 0.0, 0.0, 0.35 + 0.45 * pulse);
+        draw_rectangle_lines(0.0, 0.0, self.view_width, self.view_height, 6.0 + 4.0 * pulse, color);
+        let label = "HARDCORE";
+        let width = measure_text(label, None, 20, 1.0).width;
+        draw_text(label, (self.view_width - width) / 2.0, 22.0, 20.0, color);
+    }
+
+    /// Check if the game should quit
+    pub fn should_quit(&self) -> bool {
+        is_key_pressed(KeyCode::Escape)
+    }
+
+    /// Generate a screenshot of the current game state and save it to the specified path
+    /// 
+    /// # Arguments
+    /// * `filepath` - The path where the screenshot should be saved
+    /// * `region` - Part of the screen to keep, or `None` for all of it
+    /// 
+    /// # Returns
+    /// * `Result<(), ScreenshotError>` - Ok if successful, Err if failed
+    pub fn take_screenshot<P: AsRef<std::path::Path>>(
+        &self,
+        filepath: P,
+        region: Option<crate::screenshot::CaptureRegion>,
+    ) -> Result<(), crate::screenshot::ScreenshotError> {
+        // First render the current game state to ensure the screen has the latest frame
+        self.draw();
+        
+        // Capture and save the screenshot
+        crate::screenshot::capture_screenshot_region(filepath, region)
+    }
+}
+
+impl Default for SimpleLevel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Main game loop for the simple level
+pub async fn run_simple_level() {
+    run_level(SimpleLevel::new(), FrameSettings::default()).await;
+}
+
+/// Main game loop for an already constructed level. Returns whether to go
+/// back to the title menu rather than close the game.
+pub async fn run_level(mut game: SimpleLevel, frame_settings: FrameSettings) -> bool {
+    // Closing the window is handled below, so what's kept between sessions is saved first
+    prevent_quit();
+    let mut limiter = FrameLimiter::new(frame_settings.target_fps);
+    let mut music = MusicPlayer::default();
+    let viewport = if game.settings.pixel_perfect { Viewport::pixel_perfect() } else { Viewport::default() };
+    let mut profiler = frame_settings.profile.then(Profiler::default);
+    match CoinBank::load(&game.settings.save_files.bank) {
+        Ok(bank) => game.bank = bank,
+        Err(e) => eprintln!("Couldn't load the coin bank: {}", e),
+    }
+    match StarRecord::load(&game.settings.save_files.stars) {
+        Ok(record) => game.star_record = record,
+        Err(e) => eprintln!("Couldn't load the star ratings: {}", e),
+    }
+    match LevelProgress::load(&game.settings.save_files.progress) {
+        Ok(progress) => game.progress = progress,
+        Err(e) => eprintln!("Couldn't load the level progress: {}", e),
+    }
+    let mut display = DisplaySettings::load(DISPLAY_SETTINGS_PATH).unwrap_or_else(|e| {
+        eprintln!("Couldn't load the display settings: {}", e);
+        DisplaySettings::default()
+    });
+    for (sheet, path) in [(&mut game.settings.player_sprites, PLAYER_SPRITES_PATH), (&mut game.settings.enemy_sprites, ENEMY_SPRITES_PATH)] {
+        match SpriteSheet::load(path) {
+            Ok(loaded) => *sheet = loaded,
+            Err(e) => eprintln!("Couldn't load the sprite sheet {}: {}", path, e),
+        }
+    }
+    let mut replay_saved = false;
+    let mut screenshot_notice: Option<ScreenshotNotice> = None;
+
+    loop {
+        limiter.begin_frame();
+        let delta_time = get_frame_time();
+        
+        // Update game state
+        let update_start = Instant::now();
+        game.update(delta_time);
+        music.play(game.music());
+        music.update(delta_time);
+        // There's no audio backend to hand sound effects to yet
+        game.sounds.take_queued();
+        if is_key_pressed(FULLSCREEN_KEY) {
+            display.toggle_fullscreen();
+            display.apply();
+            if let Err(e) = display.save(DISPLAY_SETTINGS_PATH) {
+                eprintln!("Couldn't save the display settings: {}", e);
+            }
+        }
+        let update_time = update_start.elapsed();
+
+        // Export each finished run so it can be shared and watched with --replay
+        if game.game_won && !replay_saved {
+            match Replay::record(&game) {
+                Some(replay) => {
+                    if let Err(e) = replay.save(REPLAY_PATH) {
+                        eprintln!("Couldn't save the replay: {}", e);
+                    }
+                }
+                None => eprintln!("This level can't be built again or the run was too long, so no replay was saved"),
+            }
+        }
+        replay_saved = game.game_won;
+        
+        // Draw everything at the design resolution, letterboxed into the window
+        let draw_start = Instant::now();
+        let (zoom, top) = game.camera_zoom();
+        let camera = viewport.begin_zoomed(zoom, top);
+        game.draw_world(&camera);
+        viewport.begin();
+        game.draw_overlay();
+        viewport.end();
+
+        // Save the finished frame before anything is drawn over it
+        if is_key_pressed(SCREENSHOT_KEY) {
+            let result = save_timestamped_screenshot(SCREENSHOT_DIR);
+            if let Err(e) = &result {
+                eprintln!("Couldn't save the screenshot: {}", e);
+            }
+            screenshot_notice = Some(ScreenshotNotice::new(&result));
+        }
+        if let Some(notice) = &mut screenshot_notice {
+            notice.draw();
+            if !notice.update(delta_time) {
+                screenshot_notice = None;
+            }
+        }
+        if let Some(profiler) = profiler.as_mut() {
+            profiler.update.record(update_time);
+            profiler.draw.record(draw_start.elapsed());
+        }
+        
+        // However the level is left - ESC, closing the window or a lost hardcore
+        // run going back to the menu - keep what's banked this session and any profile
+        let closed = is_quit_requested();
+        if game.should_quit() || closed || game.back_to_menu {
+            game.save_progress();
+            if let Some(Err(e)) = profiler.map(|p| p.save_csv(PROFILE_PATH)) {
+                eprintln!("Couldn't save the frame profile: {}", e);
+            }
+            return game.back_to_menu && !closed;
+        }
+        
+        // Hold the target frame rate, if one is set
+        limiter.end_frame();
+
+        next_frame().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::background::{BackgroundTile, DecorationKind};
+    use crate::blocks::SPRING_LAUNCH;
+    use crate::camera::{CameraBounds, ScrollMargins};
+    use crate::effects::DEBRIS_SIZE;
+    use crate::items::ItemKind;
+    use crate::level_data::EnemySpawn;
+
+    /// The default level cut down to a flat floor `width` pixels long, with no enemies or blocks
+    fn flat_level(width: f32) -> LevelData {
+        LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, width, 20.0)],
+            enemies: Vec::new(),
+            blocks: Vec::new(),
+            ..Default::default()
+        }
+    }
+
+    /// A flat floor with a single enemy standing on it
+    fn stomp_test_level(respawn_policy: RespawnPolicy) -> SimpleLevel {
+        let data = LevelData {
+            enemies: vec![EnemySpawn::new(300.0, 384.0, 300.0, 300.0)],
+            respawn_policy,
+            ..flat_level(600.0)
+        };
+        SimpleLevel::from_data(&data)
+    }
+
+    /// Drop the player onto the enemy until it has been stomped
+    fn stomp_enemy(level: &mut SimpleLevel) {
+        level.player.respawn(298.0, 330.0);
+        for _ in 0..60 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            if !level.enemies[0].alive {
+                return;
+            }
+        }
+        panic!("enemy was never stomped");
+    }
+
+    #[test]
+    fn test_stomps_without_landing_score_a_growing_combo() {
+        let data = LevelData {
+            enemies: vec![EnemySpawn::new(300.0, 376.0, 300.0, 300.0), EnemySpawn::new(318.0, 376.0, 318.0, 318.0)],
+            ..flat_level(600.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let base = level.enemies[0].points;
+
+        // Landing on both goombas at once is two stomps in a row
+        level.player.respawn(305.0, 350.0);
+        level.player.velocity_y = 100.0;
+        for _ in 0..30 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            if level.enemies.iter().all(|e| !e.alive) {
+                break;
+            }
+        }
+        assert_eq!(level.score, base + 2 * base);
+
+        // Back on the ground the combo starts over
+        for _ in 0..120 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(level.stomp_combo, 0);
+        assert_eq!(level.settings.scoring.stomp_points(base, 1), base);
+        assert_eq!(level.settings.scoring.stomp_points(base, 9), base * 8);
+    }
+
+    #[test]
+    fn test_stomps_are_judged_by_relative_velocity() {
+        // The same descent, ending 12px into the top of a 16px enemy
+        let mut player = Player::new(298.0, 0.0);
+        player.velocity_y = 100.0;
+        let mut enemy = Enemy::new(300.0, 384.0, 300.0, 300.0);
+        player.y = enemy.y + 12.0 - player.height;
+        let dt = 1.0 / 60.0;
+
+        // A stationary enemy was already that far in: the player ran into its side
+        assert!(!enemy.is_stomped_by(&player, dt));
+        // A rising one came up under the player's feet this step
+        enemy.velocity = (0.0, -900.0);
+        assert!(enemy.is_stomped_by(&player, dt));
+        // Falling away faster than the player, it can't be landed on at all
+        enemy.velocity = (0.0, 300.0);
+        player.y = enemy.y + 2.0 - player.height;
+        assert!(!enemy.is_stomped_by(&player, dt));
+
+        // In the level, an enemy rising at the player is stomped rather than hurting them
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        let rising = level.enemies[0].clone().with_path(vec![Waypoint::new(300.0, 100.0, 900.0)]);
+        level.enemies[0] = rising;
+        level.player.respawn(298.0, 380.0 - level.player.height);
+        level.player.velocity_y = 100.0;
+        level.step(&InputState::default(), dt);
+        assert!((level.enemies[0].velocity.1 + 900.0).abs() < 1e-2);
+        assert!(!level.enemies[0].alive);
+        assert!(level.events.contains(&GameEvent::EnemyStomped { enemy: 0 }));
+        assert_eq!(level.lives, STARTING_LIVES);
+    }
+
+    #[test]
+    fn test_stomping_splitter_spawns_two_smaller_enemies() {
+        let data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 600.0, 20.0)],
+            enemies: vec![EnemySpawn {
+                name: None,
+                x: 300.0,
+                y: 376.0,
+                patrol_start: 200.0,
+                patrol_end: 400.0,
+                kind: EnemyKind::Splitter,
+                size_tier: 2,
+                path: Vec::new(),
+                boss: None,
+                loot: None,
+            }],
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        stomp_enemy(&mut level);
+
+        let alive: Vec<&Enemy> = level.enemies.iter().filter(|e| e.alive).collect();
+        assert_eq!(alive.len(), 2);
+        assert!(alive.iter().all(|e| e.kind == EnemyKind::Splitter && e.size_tier == 1));
+        assert!(alive.iter().all(|e| e.speed > level.enemies[0].speed));
+
+        // Tier 1 enemies can't split any further
+        assert!(alive[0].split().is_none());
+
+        // The halves don't catch the player on the way back up from the bounce
+        for _ in 0..10 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert!(level.player.x > 250.0, "player should not have been sent back to spawn");
+    }
+
+    #[test]
+    fn test_split_during_update_does_not_affect_other_enemies() {
+        let splitter = EnemySpawn { kind: EnemyKind::Splitter, size_tier: 2, ..EnemySpawn::new(300.0, 376.0, 300.0, 300.0) };
+        let goomba = EnemySpawn::new(318.0, 376.0, 318.0, 318.0);
+        let data = LevelData {
+            enemies: vec![splitter, goomba],
+            ..flat_level(600.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+
+        // Land on both enemies in the same step
+        level.player.respawn(305.0, 350.0);
+        level.player.velocity_y = 100.0;
+        let mut events = Vec::new();
+        for _ in 0..30 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            events.extend_from_slice(level.events());
+            if level.enemies.len() > 2 {
+                break;
+            }
+        }
+
+        // Stomping the splitter first doesn't stop the goomba being stomped too
+        assert!(events.contains(&GameEvent::EnemyStomped { enemy: 0 }));
+        assert!(events.contains(&GameEvent::EnemyStomped { enemy: 1 }));
+        assert!(level.player.velocity_y < 0.0);
+        assert!(level.player.y < 376.0, "the player shouldn't have been killed");
+
+        // The halves joined after the pass and haven't updated yet
+        assert_eq!(level.enemies.len(), 4);
+        assert!(level.enemies[2..].iter().all(|e| e.spawn_timer == SPLIT_GRACE_TIME));
+    }
+
+    #[test]
+    fn test_respawn_policy_controls_enemy_restore() {
+        let mut level = stomp_test_level(RespawnPolicy::Reset);
+        stomp_enemy(&mut level);
+        level.kill_player();
+        assert!(level.enemies[0].alive, "reset policy restores stomped enemies");
+
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        stomp_enemy(&mut level);
+        level.kill_player();
+        assert!(!level.enemies[0].alive, "persist policy keeps stomped enemies dead");
+    }
+
+    #[test]
+    fn test_reset_policy_takes_back_what_the_level_gives_again() {
+        let data = LevelData {
+            coins: vec![Coin::new(200.0, 385.0)],
+            respawn_policy: RespawnPolicy::Reset,
+            ..flat_level(800.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let points = level.settings.scoring.coin_points();
+
+        // Dying puts the coin back, and the score and count it added go with it
+        for _ in 0..3 {
+            level.player.respawn(196.0, 380.0);
+            level.step(&InputState::default(), 1.0 / 60.0);
+            assert_eq!((level.coins_collected, level.score), (1, points));
+            level.kill_player();
+            assert_eq!((level.coins_collected, level.score), (0, 0));
+        }
+    }
+
+    #[test]
+    fn test_reset_policy_coins_only_reach_the_bank_once() {
+        let data = LevelData {
+            coins: vec![Coin::new(200.0, 385.0)],
+            respawn_policy: RespawnPolicy::Reset,
+            ..flat_level(800.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.bank.deposit(7);
+
+        // Collecting the coin again after each death never adds to the bank
+        for _ in 0..3 {
+            level.player.respawn(196.0, 380.0);
+            level.step(&InputState::default(), 1.0 / 60.0);
+            assert_eq!(level.bank.balance(), 8);
+            level.kill_player();
+            assert_eq!(level.bank.balance(), 7);
+        }
+    }
+
+    #[test]
+    fn test_door_opens_only_with_matching_key() {
+        let data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 800.0, 20.0)],
+            enemies: Vec::new(),
+            keys: vec![Key::new(7, 100.0, 385.0)],
+            doors: vec![Door::new(7, 400.0, 300.0, 20.0, 100.0)],
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let walk_right = InputState { right: true, ..Default::default() };
+
+        // Without the key the door stops the player
+        level.player.respawn(300.0, 380.0);
+        for _ in 0..120 {
+            level.step(&walk_right, 1.0 / 60.0);
+        }
+        assert!(level.player.x + level.player.width <= 400.0);
+
+        // Pick up the key, then walk through the doorway
+        level.player.respawn(95.0, 380.0);
+        level.step(&InputState::default(), 1.0 / 60.0);
+        assert!(level.player.keys.contains(&7));
+
+        level.player.respawn(300.0, 380.0);
+        for _ in 0..120 {
+            level.step(&walk_right, 1.0 / 60.0);
+        }
+        assert!(level.player.x > 420.0);
+    }
+
+    #[test]
+    fn test_background_tiles_are_drawn_but_not_solid() {
+        let data = LevelData {
+            background: vec![
+                BackgroundLayer::new(1.0, vec![
+                    BackgroundTile::new(DecorationKind::Bush, 80.0, 300.0),
+                    BackgroundTile::new(DecorationKind::Hill, 60.0, 350.0),
+                ]),
+                BackgroundLayer::new(0.5, vec![BackgroundTile::new(DecorationKind::Cloud, 100.0, 100.0)]),
+            ],
+            ..flat_level(400.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        assert_eq!(level.background_tile_count(), 3);
+        let drawn: usize = level.background.iter().map(|layer| layer.visible_tiles(0.0, 800.0).count()).sum();
+        assert_eq!(drawn, 3);
+
+        // Only the floor takes part in collision
+        assert_eq!(level.solids().1.len(), 1);
+
+        // Dropped straight through the decorations, the player lands on the floor
+        level.player.respawn(100.0, 200.0);
+        for _ in 0..60 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(level.player.y, 400.0 - level.player.height);
+    }
+
+    #[test]
+    fn test_custom_scoring_config_sets_coin_value() {
+        let data = LevelData {
+            coins: vec![Coin::new(200.0, 385.0), Coin::new(600.0, 385.0)],
+            ..flat_level(800.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.settings.scoring = ScoringConfig { coin: 200, ..Default::default() };
+        let idle = InputState::default();
+
+        level.player.respawn(196.0, 380.0);
+        level.step(&idle, 1.0 / 60.0);
+        assert_eq!(level.score, 200);
+        level.player.respawn(596.0, 380.0);
+        level.step(&idle, 1.0 / 60.0);
+        assert_eq!(level.score, 400);
+    }
+
+    #[test]
+    fn test_every_goal_kind_finishes_the_level() {
+        for kind in [GoalKind::Flag, GoalKind::Door, GoalKind::Orb] {
+            let data = LevelData {
+                goal: (400.0, 300.0),
+                goal_kind: kind,
+                ..flat_level(800.0)
+            };
+            let mut level = SimpleLevel::from_data(&data);
+            let idle = InputState::default();
+            level.player.respawn(405.0, 300.0);
+            level.step(&idle, 1.0 / 60.0);
+            assert!(level.game_won, "{:?} goal didn't finish the level", kind);
+            assert_eq!(level.finish_time, Some(0.0));
+
+            // The finishing sequence keeps playing after the win
+            level.step(&idle, 1.0 / 60.0);
+            assert!(level.finish_time.unwrap() > 0.0);
+            if kind == GoalKind::Flag {
+                assert!(level.player.y > 300.0, "the player should slide down the flagpole");
+            }
+        }
+    }
+
+    #[test]
+    fn test_goal_only_counts_after_all_coins_collected() {
+        let data = LevelData {
+            goal: (400.0, 340.0),
+            coins: vec![Coin::new(200.0, 385.0), Coin::new(600.0, 385.0)],
+            objective: Objective::CollectAllCoins,
+            ..flat_level(800.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let idle = InputState::default();
+
+        // Standing in the goal before the coins are collected does nothing
+        level.player.respawn(405.0, 380.0);
+        level.step(&idle, 1.0 / 60.0);
+        assert!(!level.goal_active());
+        assert!(!level.game_won);
+
+        // Grab both coins; the second one activates the goal
+        level.player.respawn(196.0, 380.0);
+        level.step(&idle, 1.0 / 60.0);
+        assert!(!level.goal_active());
+        level.player.respawn(596.0, 380.0);
+        level.step(&idle, 1.0 / 60.0);
+        assert_eq!(level.coins_collected, level.total_coins() as u32);
+        assert!(level.events().contains(&GameEvent::GoalActivated));
+
+        level.player.respawn(405.0, 380.0);
+        level.step(&idle, 1.0 / 60.0);
+        assert!(level.game_won);
+    }
+
+    #[test]
+    fn test_take_hit_knocks_back_and_grants_invulnerability() {
+        let mut enemy = Enemy::armored(100.0, 0.0, 0.0, 200.0);
+
+        assert!(!enemy.take_hit(EnemyDirection::Left, 150.0));
+        assert!(enemy.alive);
+        assert_eq!(enemy.health, ARMORED_HEALTH - 1);
+        assert_eq!(enemy.knockback_velocity, -150.0);
+        assert_eq!(enemy.invulnerable_timer, ENEMY_INVULNERABLE_TIME);
+        assert!(!enemy.is_interactive());
+
+        // Hits during the invulnerability window are ignored
+        assert!(!enemy.take_hit(EnemyDirection::Right, 150.0));
+        assert_eq!(enemy.health, ARMORED_HEALTH - 1);
+
+        // The knockback carries it the way it was hit, then wears off with the window
+        enemy.update(0.1);
+        assert!(enemy.x < 100.0);
+        for _ in 0..10 {
+            enemy.update(0.1);
+        }
+        assert_eq!(enemy.knockback_velocity, 0.0);
+        assert!(enemy.is_interactive());
+
+        // A regular enemy still goes down in one hit
+        let mut goomba = Enemy::new(100.0, 0.0, 0.0, 200.0);
+        assert!(goomba.take_hit(EnemyDirection::Right, 150.0));
+        assert!(!goomba.alive);
+    }
+
+    #[test]
+    fn test_fixed_point_camera_target_ignores_player() {
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        level.player.respawn(50.0, 380.0);
+        level.set_camera_target(CameraTarget::FixedPoint(1400.0, 300.0));
+
+        let mut last_x = level.camera().x;
+        for _ in 0..10 {
+            level.step(&InputState { left: true, ..Default::default() }, 1.0 / 60.0);
+            assert!(level.camera().x > last_x);
+            last_x = level.camera().x;
+        }
+
+        // Given time it settles with the point in the middle of the view
+        for _ in 0..200 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert!((level.camera().x - (1400.0 - VIEW_WIDTH / 2.0)).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_interpolate_blends_previous_and_current_positions() {
+        assert_eq!(interpolate((0.0, 100.0), (10.0, 80.0), 0.0), (0.0, 100.0));
+        assert_eq!(interpolate((0.0, 100.0), (10.0, 80.0), 0.5), (5.0, 90.0));
+        assert_eq!(interpolate((0.0, 100.0), (10.0, 80.0), 1.0), (10.0, 80.0));
+        // Alpha outside the step is clamped rather than extrapolated
+        assert_eq!(interpolate((0.0, 100.0), (10.0, 80.0), 2.0), (10.0, 80.0));
+
+        // Real-time updates keep the leftover fraction of a step for drawing
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        level.advance(&InputState::default(), FIXED_DT * 2.5);
+        assert!((level.render_alpha - 0.5).abs() < 1e-3);
+        assert!((level.accumulator - FIXED_DT * 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_jump_input_moves_player_on_the_same_step() {
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        level.player.respawn(50.0, 400.0 - PLAYER_SIZE);
+        level.step(&InputState::default(), FIXED_DT);
+        assert!(level.player.on_ground);
+        let ground_y = level.player.y;
+
+        let jump = InputState { jump_pressed: true, jump_held: true, ..Default::default() };
+        level.step(&jump, FIXED_DT);
+        assert!(level.player.velocity_y < 0.0);
+        assert!(level.player.y < ground_y);
+
+        // A press on a frame too short for a physics step still jumps on the next one
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        level.player.respawn(50.0, 400.0 - PLAYER_SIZE);
+        level.step(&InputState::default(), FIXED_DT);
+        level.advance(&jump, FIXED_DT * 0.5);
+        assert_eq!(level.player.y, ground_y);
+        level.advance(&InputState::default(), FIXED_DT * 0.5);
+        assert!(level.player.velocity_y < 0.0);
+    }
+
+    #[test]
+    fn test_starting_with_fire_flower_can_throw_fireballs() {
+        let mut level = SimpleLevel::new_with(PowerUp::FireFlower);
+        assert_eq!(level.player.power, PowerUp::FireFlower);
+        assert_eq!(level.player.height, BIG_PLAYER_HEIGHT);
+
+        level.step(&InputState { fire_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        assert_eq!(level.fireball_count(), 1);
+
+        // Without the fire flower nothing comes out
+        let mut level = SimpleLevel::new_with(PowerUp::Big);
+        level.step(&InputState { fire_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        assert_eq!(level.fireball_count(), 0);
+    }
+
+    #[test]
+    fn test_chained_spring_bounce_goes_higher() {
+        let data = LevelData {
+            blocks: vec![Block::new(100.0, 380.0, BlockKind::Spring)],
+            ..flat_level(400.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.player.respawn(100.0, 300.0);
+
+        // Record the launch speed of each spring bounce
+        let mut launches = Vec::new();
+        for _ in 0..600 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            for event in level.events() {
+                if let GameEvent::SpringBounce { chain, .. } = event {
+                    launches.push((*chain, -level.player.velocity_y));
+                }
+            }
+            if launches.len() == 2 {
+                break;
+            }
+        }
+
+        assert_eq!(launches.len(), 2);
+        assert_eq!(launches[0], (1, SPRING_LAUNCH));
+        assert_eq!(launches[1].0, 2);
+        assert!(launches[1].1 > launches[0].1);
+
+        // Touching the ground ends the chain, so the next bounce is back to normal
+        let mut chain = BounceChain::default();
+        chain.bounce();
+        chain.reset();
+        assert_eq!(chain.bounce(), SPRING_LAUNCH);
+    }
+
+    #[test]
+    fn test_modern_jump_cut_leaves_spring_launches_alone() {
+        let data = LevelData {
+            blocks: vec![Block::new(100.0, 380.0, BlockKind::Spring)],
+            ..flat_level(400.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        MovementPreset::Modern.apply(&mut level.player.physics);
+        level.player.respawn(100.0, 300.0);
+
+        // The highest point reached after each of three bounces, jump never held
+        let mut peaks: Vec<f32> = Vec::new();
+        for _ in 0..900 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            if level.events().iter().any(|e| matches!(e, GameEvent::SpringBounce { .. })) {
+                assert!(-level.player.velocity_y >= SPRING_LAUNCH);
+                peaks.push(level.player.y);
+            }
+            if let Some(peak) = peaks.last_mut() {
+                *peak = peak.min(level.player.y);
+            }
+            if peaks.len() == 4 {
+                break;
+            }
+        }
+        assert_eq!(peaks.len(), 4);
+        assert!(peaks[1] < peaks[0] && peaks[2] < peaks[1], "peaks: {:?}", peaks);
+
+        // A jump of the player's own is still cut short
+        level.player.respawn(300.0, 368.0);
+        level.step(&InputState::default(), 1.0 / 60.0);
+        level.step(&InputState { jump_pressed: true, jump_held: true, ..Default::default() }, 1.0 / 60.0);
+        assert!(level.player.jumping);
+        level.step(&InputState::default(), 1.0 / 60.0);
+        let cut_speed = level.player.physics.jump_strength * JUMP_CUT;
+        assert!(-level.player.velocity_y <= cut_speed);
+    }
+
+    #[test]
+    fn test_switch_makes_outline_blocks_solid() {
+        let data = LevelData {
+            blocks: vec![
+                Block::new(100.0, 330.0, BlockKind::Switch { group: 1 }),
+                Block::new(250.0, 380.0, BlockKind::Outline { group: 1 }),
+                Block::new(270.0, 380.0, BlockKind::Outline { group: 2 }),
+            ],
+            ..flat_level(400.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let solid_blocks = |level: &SimpleLevel| {
+            level.solids().0.into_iter().filter(|s| matches!(s, Solid::Block(_))).collect::<Vec<_>>()
+        };
+        assert_eq!(solid_blocks(&level), vec![Solid::Block(0)]);
+
+        // Jump into the switch
+        level.player.respawn(100.0, 380.0);
+        level.player.on_ground = true;
+        level.step(&InputState { jump_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        let mut toggled = false;
+        for _ in 0..30 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            toggled |= level.events().contains(&GameEvent::SwitchToggled { group: 1, active: true });
+        }
+        assert!(toggled);
+
+        // Only the outline block in the switch's group became solid
+        assert_eq!(solid_blocks(&level), vec![Solid::Block(0), Solid::Block(1)]);
+    }
+
+    #[test]
+    fn test_player_resting_on_platform_stays_grounded() {
+        let platforms = [Platform::new(0.0, 100.0, 400.0, 20.0)];
+        let mut player = Player::new(50.0, 100.0 - PLAYER_SIZE);
+
+        for _ in 0..120 {
+            player.update(&platforms, &InputState::default(), 1.0 / 60.0);
+            assert!(player.on_ground);
+            assert_eq!(player.y, 100.0 - PLAYER_SIZE);
+            assert_eq!(player.animation_state, AnimationState::Idle);
+        }
+
+        // Walking off the edge leaves the ground straight away
+        player.x = 401.0;
+        player.update(&platforms, &InputState::default(), 1.0 / 60.0);
+        assert!(!player.on_ground);
+    }
+
+    #[test]
+    fn test_landing_on_a_seam_rests_on_the_higher_platform() {
+        // Two platforms meeting under the player, the right one a few pixels higher
+        let left = Platform::new(0.0, 400.0, 100.0, 20.0);
+        let right = Platform::new(100.0, 396.0, 100.0, 24.0);
+
+        for platforms in [[left.clone(), right.clone()], [right.clone(), left.clone()]] {
+            let mut player = Player::new(100.0 - PLAYER_SIZE / 2.0, 300.0);
+            for _ in 0..120 {
+                player.update(&platforms, &InputState::default(), 1.0 / 60.0);
+            }
+            assert!(player.on_ground);
+            assert_eq!(player.y, 396.0 - PLAYER_SIZE);
+
+            // And stays there rather than jittering between the two surfaces
+            for _ in 0..30 {
+                player.update(&platforms, &InputState::default(), 1.0 / 60.0);
+                assert_eq!(player.y, 396.0 - PLAYER_SIZE);
+            }
+        }
+    }
+
+    #[test]
+    fn test_coin_challenge_rewards_only_within_time_limit() {
+        let run = |time_limit: f32| {
+            let data = LevelData {
+                player_spawn: (50.0, 380.0),
+                coins: vec![Coin::new(150.0, 385.0), Coin::new(200.0, 385.0), Coin::new(250.0, 385.0)],
+                challenges: vec![CoinChallenge::new(60.0, 394.0, 3, time_limit, ChallengeReward::ExtraLife)],
+                ..flat_level(600.0)
+            };
+            let mut level = SimpleLevel::from_data(&data);
+            let right = InputState { right: true, ..Default::default() };
+            let mut events = Vec::new();
+            for _ in 0..90 {
+                level.step(&right, 1.0 / 60.0);
+                events.extend_from_slice(level.events());
+            }
+            assert!(events.contains(&GameEvent::ChallengeStarted { challenge: 0 }));
+            assert_eq!(level.coins_collected, 3);
+            (level, events)
+        };
+
+        let (level, events) = run(2.0);
+        assert_eq!(level.lives, STARTING_LIVES + 1);
+        assert_eq!(level.challenges[0].state, ChallengeState::Completed);
+        assert!(events.contains(&GameEvent::ChallengeCompleted { challenge: 0 }));
+
+        // Too slow: the timer runs out first and the challenge resets without a reward
+        let (level, events) = run(0.3);
+        assert_eq!(level.lives, STARTING_LIVES);
+        assert_eq!(level.challenges[0].state, ChallengeState::Ready);
+        assert!(events.contains(&GameEvent::ChallengeFailed { challenge: 0 }));
+        assert!(!events.contains(&GameEvent::ChallengeCompleted { challenge: 0 }));
+    }
+
+    #[test]
+    fn test_idle_animation_moves_only_the_drawing() {
+        let platforms = [Platform::new(0.0, 100.0, 400.0, 20.0)];
+        let mut player = Player::new(50.0, 100.0 - PLAYER_SIZE);
+        player.facing_right = false;
+
+        let mut offsets = Vec::new();
+        let mut blinked = false;
+        for _ in 0..240 {
+            player.update(&platforms, &InputState::default(), 1.0 / 60.0);
+            assert_eq!(player.animation_state, AnimationState::Idle);
+            assert_eq!((player.x, player.y), (50.0, 100.0 - PLAYER_SIZE));
+            offsets.push(player.bob_offset());
+            blinked |= player.is_blinking();
+        }
+
+        let lowest = offsets.iter().cloned().fold(f32::MAX, f32::min);
+        let highest = offsets.iter().cloned().fold(f32::MIN, f32::max);
+        assert!(highest - lowest > 0.5, "idle bob should vary over time");
+        assert!(highest.abs() <= 1.0 && lowest.abs() <= 1.0, "idle bob should stay subtle");
+        assert!(blinked);
+        assert!(!player.facing_right, "idle keeps the last facing");
+    }
+
+    #[test]
+    fn test_reset_restores_initial_state_but_keeps_best_score() {
+        let mut level = SimpleLevel::new();
+        let start = (level.player.x, level.player.y);
+        let coins = level.total_coins();
+
+        level.settings.time_attack = true;
+        level.player.respawn(300.0, 100.0);
+        level.score = 1200;
+        level.coins_collected = 5;
+        level.level_time = 9.0;
+        level.coins[0].collected = true;
+        level.enemies[0].alive = false;
+        level.enemies.push(level.enemies[0].clone());
+        level.finish_level();
+
+        level.step(&InputState { restart_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        assert_eq!((level.player.x, level.player.y), start);
+        assert_eq!((level.score, level.coins_collected, level.level_time), (0, 0, 0.0));
+        assert!(!level.game_won);
+        assert_eq!(level.enemies.len(), level.initial_enemies.len());
+        assert!(level.enemies.iter().all(|e| e.alive));
+        assert!(level.coins.iter().all(|c| !c.collected));
+        assert_eq!(level.total_coins(), coins);
+
+        // Stats that outlast an attempt survive
+        assert_eq!(level.best_score, 1200);
+        assert!(level.settings.time_attack);
+    }
+
+    #[test]
+    fn test_replay_recording_stops_at_its_cap_and_restarts_on_reset() {
+        let mut level = SimpleLevel::new();
+        let idle = InputState::default();
+        level.step(&idle, 1.0 / 60.0);
+        assert_eq!(level.recorded_inputs().map(<[_]>::len), Some(1));
+
+        level.recorded_inputs = Some(vec![idle; MAX_RECORDED_STEPS]);
+        level.step(&idle, 1.0 / 60.0);
+        assert!(level.recorded_inputs().is_none());
+
+        level.reset();
+        assert_eq!(level.recorded_inputs(), Some(&[][..]));
+    }
+
+    /// A simple playtesting controller: run right, jumping at gaps, walls and
+    /// the goal, and waiting for enemies to walk close enough to stomp them
+    fn playtest_input(level: &SimpleLevel) -> InputState {
+        let player = &level.player;
+        let front = player.x + player.width;
+        let feet = player.y + player.height;
+        let (_, solids) = level.solids();
+
+        let wall_ahead = solids.iter().any(|s| s.intersects(front, player.y, 12.0, player.height - 2.0));
+        let ground_ahead = solids.iter().any(|s| s.intersects(front + 8.0, feet, 4.0, 40.0));
+        let goal_ahead = (level.goal.x - front).abs() < 40.0;
+        let enemies = || level.enemies.iter().filter(|e| e.is_interactive());
+        // Closest enemy walking on the same floor just ahead
+        let enemy_gap = enemies()
+            .filter(|e| (e.y + e.height - feet).abs() < 4.0)
+            .map(|e| e.x - front)
+            .filter(|gap| (-4.0..60.0).contains(gap))
+            .fold(None, |closest: Option<f32>, gap| Some(closest.map_or(gap, |c| c.min(gap))));
+        let enemy_below = enemies().any(|e| e.y >= feet && e.x < front + 8.0 && e.x + e.width > player.x - 8.0);
+
+        if !player.on_ground {
+            // After jumping straight up to stomp, drop straight down onto the enemy,
+            // and don't drift off the end of whatever we're coming down on
+            let stomping = player.velocity_x == 0.0 && enemy_below;
+            let landing_near_edge = player.velocity_y > 0.0
+                && solids.iter().any(|s| s.intersects(player.x, feet, player.width, 60.0))
+                && !solids.iter().any(|s| s.intersects(front, feet, 4.0, 60.0));
+            return InputState { right: !stomping && !landing_near_edge, ..Default::default() };
+        }
+        match enemy_gap {
+            // Jump straight up just before it walks into us, and land on it
+            Some(gap) if gap < 14.0 => InputState { jump_pressed: true, jump_held: true, ..Default::default() },
+            Some(_) => InputState::default(),
+            None => {
+                let jump = wall_ahead || !ground_ahead || goal_ahead;
+                InputState { right: true, jump_pressed: jump, jump_held: jump, ..Default::default() }
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_level_is_completable() {
+        // Half a minute of play is plenty for a level this size
+        let mut level = SimpleLevel::new();
+        for _ in 0..60 * 30 {
+            let input = playtest_input(&level);
+            level.step(&input, FIXED_DT);
+            if level.game_won {
+                return;
+            }
+        }
+        panic!("the playtester didn't reach the goal (stopped at x = {:.0}, y = {:.0})", level.player.x, level.player.y);
+    }
+
+    #[test]
+    fn test_invulnerability_after_hit_ignores_enemy_contact() {
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        level.player.set_power(PowerUp::Big);
+        let touch_enemy = |level: &mut SimpleLevel| {
+            level.player.respawn(290.0, 400.0 - level.player.height);
+            level.step(&InputState::default(), FIXED_DT);
+            level.events().to_vec()
+        };
+
+        assert_eq!(touch_enemy(&mut level), vec![GameEvent::PlayerHurt]);
+        assert_eq!(level.player.power, PowerUp::Small);
+        assert!(level.player.is_invulnerable());
+
+        // Still touching during the i-frames does nothing more; once they
+        // run out, the small player dies
+        let mut elapsed = 0.0;
+        loop {
+            let events = touch_enemy(&mut level);
+            elapsed += FIXED_DT;
+            if !events.is_empty() {
+                assert_eq!(events, vec![GameEvent::PlayerDied]);
+                break;
+            }
+            assert_eq!(level.player.power, PowerUp::Small);
+        }
+        assert!(elapsed >= HURT_INVULNERABLE_TIME - 1e-3);
+    }
+
+    #[test]
+    fn test_reachability_reports_coin_out_of_reach() {
+        let data = LevelData {
+            player_spawn: (20.0, 300.0),
+            goal: (260.0, 350.0),
+            platforms: vec![Platform::new(0.0, 400.0, 200.0, 20.0), Platform::new(250.0, 380.0, 100.0, 20.0)],
+            coins: vec![
+                // On the floor, over the step up and far too high to jump to
+                Coin::new(100.0, 385.0),
+                Coin::new(300.0, 340.0),
+                Coin::new(100.0, 150.0),
+            ],
+            blocks: Vec::new(),
+            enemies: Vec::new(),
+            ..Default::default()
+        };
+        let report = SimpleLevel::from_data(&data).reachability();
+        assert!(report.goal_reachable);
+        assert_eq!(report.unreachable_coins, vec![2]);
+        assert!(!report.all_reachable());
+
+        assert!(SimpleLevel::new().reachability().all_reachable());
+    }
+
+    #[test]
+    fn test_export_svg_writes_a_rect_per_platform() {
+        let level = SimpleLevel::new();
+        let path = std::env::temp_dir().join(format!("rust_mario_export_{}.svg", std::process::id()));
+        level.export_svg(&path).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(svg.starts_with("<?xml"));
+        assert_eq!(svg.matches(r#"<rect class="platform""#).count(), level.platforms.len());
+        assert!(svg.contains(">Goal</text>"));
+
+        // Well formed: every element is self-closing or closed in order
+        let mut open = Vec::new();
+        for tag in svg.split('<').skip(1).map(|t| &t[..t.find('>').unwrap()]) {
+            if tag.starts_with('?') || tag.ends_with('/') {
+                continue;
+            }
+            match tag.strip_prefix('/') {
+                Some(name) => assert_eq!(open.pop(), Some(name)),
+                None => open.push(tag.split_whitespace().next().unwrap()),
+            }
+        }
+        assert!(open.is_empty());
+    }
+
+    #[test]
+    fn test_blinking_platform_is_solid_only_while_on() {
+        let blink = Blink { period: 2.0, offset: 0.0 };
+        let data = LevelData {
+            platforms: vec![
+                Platform::new(0.0, 400.0, 100.0, 20.0),
+                Platform::blinking(100.0, 400.0, 100.0, 20.0, blink),
+                Platform::blinking(200.0, 400.0, 100.0, 20.0, Blink { offset: 1.0, ..blink }),
+            ],
+            blocks: Vec::new(),
+            enemies: Vec::new(),
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let solid_at = |level: &SimpleLevel, x: f32| {
+            let (_, shapes) = level.solids();
+            shapes.iter().any(|s| s.contains_point(x, 410.0))
+        };
+
+        // On for the first half of the period, off for the second, and the
+        // offset platform does the opposite
+        level.level_time = 0.5;
+        assert!(solid_at(&level, 150.0));
+        assert!(!solid_at(&level, 250.0));
+        level.level_time = 1.5;
+        assert!(!solid_at(&level, 150.0));
+        assert!(solid_at(&level, 250.0));
+        level.level_time = 2.5;
+        assert!(solid_at(&level, 150.0));
+
+        // A player standing on it drops through once it switches off
+        level.level_time = 0.0;
+        level.player.respawn(140.0, 380.0);
+        for _ in 0..50 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(level.player.y, 380.0);
+        for _ in 0..20 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert!(level.player.y > 380.0);
+    }
+
+    #[test]
+    fn test_moving_platform_carries_enemies_standing_on_it() {
+        let motion = Motion { travel: (200.0, 0.0), period: 4.0 };
+        let data = LevelData {
+            platforms: vec![
+                Platform::new(0.0, 400.0, 100.0, 20.0),
+                Platform::moving(300.0, 300.0, 100.0, 20.0, motion),
+            ],
+            blocks: Vec::new(),
+            enemies: vec![EnemySpawn::new(460.0, 284.0, 410.0, 480.0)],
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.enemies[0].speed = 0.0;
+
+        // A quarter of the way through the trip the platform is at 400 and heading right
+        level.level_time = 1.0;
+        level.player.respawn(415.0, 280.0);
+        let dt = 1.0 / 60.0;
+        let delta = level.platforms[1].carry_delta(1.0, dt).0;
+        assert!(delta > 0.0);
+        let (enemy_x, player_x) = (level.enemies[0].x, level.player.x);
+        level.step(&InputState::default(), dt);
+
+        assert!((level.enemies[0].x - (enemy_x + delta)).abs() < 1e-3);
+        assert_eq!(level.enemies[0].patrol_start, 410.0 + delta);
+        assert!((level.player.x - (player_x + delta)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_continuing_spends_banked_coins_and_restores_lives() {
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        level.lives = 1;
+        level.kill_player();
+        assert!(level.game_over);
+        assert!(level.events.contains(&GameEvent::GameOver));
+
+        // An empty bank can't pay for a continue
+        assert_eq!(level.continue_game(), Err(InsufficientCoins { cost: CONTINUE_COST, balance: 0 }));
+        assert!(level.game_over);
+        assert_eq!(level.lives, 0);
+
+        level.bank.deposit(CONTINUE_COST + 10);
+        assert_eq!(level.continue_game(), Ok(()));
+        assert_eq!(level.bank.balance(), 10);
+        assert_eq!(level.lives, STARTING_LIVES);
+        assert!(!level.game_over);
+    }
+
+    #[test]
+    fn test_losing_a_hardcore_run_deletes_the_save() {
+        let lose_run = |hardcore: bool| {
+            let dir = std::env::temp_dir().join(format!("mario_hardcore_{}_{}", hardcore, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            let mut level = stomp_test_level(RespawnPolicy::Persist);
+            level.settings.hardcore = hardcore;
+            level.settings.save_files = SaveFiles {
+                bank: dir.join("bank.json"),
+                stars: dir.join("stars.json"),
+                progress: dir.join("progress.json"),
+            };
+            level.bank.deposit(CONTINUE_COST);
+            level.bank.save(&level.settings.save_files.bank).unwrap();
+            level.star_record.save(&level.settings.save_files.stars).unwrap();
+            level.progress.save(&level.settings.save_files.progress).unwrap();
+
+            level.lives = 1;
+            level.kill_player();
+            assert!(level.game_over);
+            let kept = [&level.settings.save_files.bank, &level.settings.save_files.stars, &level.settings.save_files.progress].map(|path| path.exists());
+            fs::remove_dir_all(&dir).unwrap();
+            (level, kept)
+        };
+
+        let (mut normal, kept) = lose_run(false);
+        assert_eq!(kept, [true; 3]);
+        assert!(!normal.run_over());
+        assert_eq!(normal.continue_game(), Ok(()));
+
+        // No continues, and jumping heads back to the menu instead
+        let (mut hardcore, kept) = lose_run(true);
+        assert_eq!(kept, [false; 3]);
+        assert_eq!(hardcore.bank.balance(), 0);
+        assert_eq!(hardcore.continue_game(), Ok(()));
+        assert!(hardcore.game_over);
+        hardcore.step(&InputState { restart_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        assert!(hardcore.game_over);
+        hardcore.step(&InputState { jump_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        assert!(hardcore.back_to_menu);
+    }
+
+    #[test]
+    fn test_fireball_and_hammer_clash_by_configured_outcome() {
+        let dt = 1.0 / 60.0;
+        let clash = |outcome: ClashOutcome| {
+            let mut level = stomp_test_level(RespawnPolicy::Persist);
+            level.enemies.clear();
+            level.clash_rules = ClashRules { hammer: outcome };
+            level.player.set_power(PowerUp::FireFlower);
+            level.player.respawn(100.0, 368.0);
+            assert!(level.throw_fireball());
+            let fireball = level.fireballs.iter().next().unwrap().clone();
+            // A hammer hanging right in the fireball's path
+            level.spawn_projectile(Projectile { x: fireball.x + 4.0, y: fireball.y, lifetime: 1.0, ..Default::default() });
+            level.step(&InputState::default(), dt);
+            (level.fireball_count(), level.projectile_count())
+        };
+
+        assert_eq!(clash(ClashOutcome::BothDestroyed), (0, 0));
+        assert_eq!(clash(ClashOutcome::FireballDestroyed), (0, 1));
+        assert_eq!(clash(ClashOutcome::ProjectileDestroyed), (1, 0));
+        assert_eq!(clash(ClashOutcome::PassThrough), (1, 1));
+    }
+
+    #[test]
+    fn test_hammer_throw_waits_for_telegraph() {
+        let dt = 1.0 / 60.0;
+        let mut enemy = Enemy::hammer_bro(100.0, 384.0, 50.0, 150.0);
+
+        // The wind-up starts once the throw interval is up, with nothing thrown yet
+        let mut elapsed = 0.0;
+        while !enemy.is_telegraphing() {
+            assert!(!enemy.ready_to_throw(dt));
+            elapsed += dt;
+        }
+        assert!(elapsed >= HAMMER_INTERVAL - 1e-3);
+
+        // Nothing fires until the telegraph timer has run out
+        let x = enemy.x;
+        let mut winding_up = 0.0;
+        while !enemy.ready_to_throw(dt) {
+            winding_up += dt;
+            enemy.update(dt);
+            assert!(winding_up < TELEGRAPH_TIME, "the throw never came");
+        }
+        assert!(winding_up + dt >= TELEGRAPH_TIME - 1e-3);
+        assert_eq!(enemy.x, x, "enemies stand still while winding up");
+        assert!(!enemy.is_telegraphing());
+    }
+
+    #[test]
+    fn test_substeps_stop_fast_player_tunnelling() {
+        let fall_onto_platform = |substeps: u32| {
+            let data = LevelData {
+                platforms: vec![Platform::new(0.0, 340.0, 200.0, 20.0)],
+                blocks: Vec::new(),
+                enemies: Vec::new(),
+                ..Default::default()
+            };
+            let mut level = SimpleLevel::from_data(&data);
+            level.settings.substeps = substeps;
+            level.player.respawn(50.0, 300.0);
+            // 100 pixels a step: further than the platform and player are thick together
+            level.player.velocity_y = 6000.0;
+            level.step(&InputState::default(), 1.0 / 60.0);
+            level.player.y
+        };
+
+        assert!(fall_onto_platform(1) > 360.0, "one substep should skip past the platform");
+        assert_eq!(fall_onto_platform(4), 340.0 - PLAYER_SIZE);
+    }
+
+    #[test]
+    fn test_grabbed_shell_is_carried_then_thrown_the_way_the_player_faces() {
+        let data = LevelData {
+            player_spawn: (230.0, 370.0),
+            enemies: vec![EnemySpawn::new(50.0, 384.0, 50.0, 50.0)],
+            items: vec![Item::new(ItemKind::Shell, 214.0, 386.0)],
+            ..flat_level(600.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        for _ in 0..30 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        level.player.facing_right = false;
+
+        // Holding grab next to the shell picks it up and carries it in front of the player
+        let grab = InputState { grab_held: true, ..Default::default() };
+        level.step(&grab, 1.0 / 60.0);
+        let held = level.held_item().expect("shell should be picked up");
+        assert_eq!(held.state, ItemState::Held);
+        assert!(held.x < level.player.x);
+        level.player.respawn(260.0, level.player.y);
+        level.step(&grab, 1.0 / 60.0);
+        assert!(level.held_item().unwrap().x < 260.0 && level.held_item().unwrap().x > 230.0, "shell follows the player");
+
+        // Letting go throws it left, where it slides into the enemy
+        level.step(&InputState::default(), 1.0 / 60.0);
+        assert!(level.held_item().is_none());
+        let shell = &level.items[0];
+        assert_eq!(shell.state, ItemState::Thrown);
+        assert!(shell.velocity_x < 0.0);
+        let mut knocked_out = false;
+        for _ in 0..60 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            knocked_out |= level.events().contains(&GameEvent::EnemyKnockedOut { enemy: 0 });
+        }
+        assert!(knocked_out);
+        assert!(!level.enemies[0].alive);
+        assert!(level.items[0].is_thrown(), "shells keep going after a hit");
+    }
+
+    #[test]
+    fn test_step_frame_runs_one_fixed_step_then_stays_paused() {
+        let mut level = stomp_test_level(RespawnPolicy::default());
+        level.step(&InputState::default(), FIXED_DT);
+        assert!(!level.step_frame(&InputState::default()), "only steps while paused");
+
+        level.step(&InputState { pause_pressed: true, ..Default::default() }, FIXED_DT);
+        let (time, y) = (level.level_time, level.player.y);
+        assert!(level.step_frame(&InputState::default()));
+        assert!((level.level_time - (time + FIXED_DT)).abs() < 1e-6);
+        assert_ne!(level.player.y, y, "the player fell for one step");
+        assert_eq!(level.state, GameState::Paused);
+
+        // Nothing moves until the next single step
+        let (time, y) = (level.level_time, level.player.y);
+        level.advance(&InputState::default(), FIXED_DT * 3.0);
+        assert_eq!((level.level_time, level.player.y), (time, y));
+    }
+
+    #[test]
+    fn test_abnormal_delta_auto_pauses() {
+        let mut level = SimpleLevel::new();
+        let input = InputState::default();
+
+        level.step(&input, 1.0 / 60.0);
+        assert_eq!(level.state, GameState::Playing);
+
+        // A multi-second stall pauses instead of simulating the huge delta
+        let (x, y) = (level.player.x, level.player.y);
+        level.step(&input, 3.0);
+        assert_eq!(level.state, GameState::Paused);
+        assert_eq!((level.player.x, level.player.y), (x, y));
+
+        // Stays paused until the player resumes
+        level.step(&input, 1.0 / 60.0);
+        assert_eq!(level.state, GameState::Paused);
+        level.step(&InputState { pause_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        assert_eq!(level.state, GameState::Playing);
+    }
+
+    #[test]
+    fn test_falling_into_a_platform_corner_grabs_the_ledge() {
+        let data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 600.0, 20.0), Platform::new(300.0, 300.0, 100.0, 100.0)],
+            blocks: Vec::new(),
+            enemies: Vec::new(),
+            ledge_grab: true,
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.player.respawn(279.0, 299.0);
+        level.player.velocity_y = 100.0;
+
+        // Head passes the top corner while pushing into the wall
+        let right = InputState { right: true, ..Default::default() };
+        level.step(&right, 1.0 / 60.0);
+        assert_eq!(level.player.ledge, Some(LedgeHang { x: 300.0, y: 300.0, on_right: true }));
+        assert_eq!((level.player.x, level.player.y), (280.0, 300.0));
+
+        // Hanging doesn't slip, and Up climbs onto the platform
+        level.step(&right, 1.0 / 60.0);
+        assert_eq!(level.player.y, 300.0);
+        level.step(&InputState { jump_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        assert!(level.player.ledge.is_none());
+        assert_eq!((level.player.x, level.player.y), (300.0, 280.0));
+        assert!(level.player.on_ground);
+    }
+
+    #[test]
+    fn test_far_enemies_update_less_often_than_near_ones() {
+        let data = LevelData {
+            enemies: vec![EnemySpawn::new(300.0, 384.0, 0.0, 1000.0), EnemySpawn::new(5000.0, 384.0, 4000.0, 6000.0)],
+            ..flat_level(6000.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let (mut near_moves, mut far_moves) = (0, 0);
+        for _ in 0..FAR_UPDATE_INTERVAL * 3 {
+            let (near, far) = (level.enemies[0].x, level.enemies[1].x);
+            level.step(&InputState::default(), 1.0 / 60.0);
+            near_moves += (level.enemies[0].x != near) as usize;
+            far_moves += (level.enemies[1].x != far) as usize;
+        }
+        assert_eq!(near_moves, FAR_UPDATE_INTERVAL * 3);
+        assert_eq!(far_moves, 3);
+
+        // Skipped time is caught up on, so both have walked just as far
+        let far = &level.enemies[1];
+        let walked = (level.enemies[0].x - 300.0, far.x + far.pending_time * far.speed - 5000.0);
+        assert!((walked.0 - walked.1).abs() < 1e-3, "{:?}", walked);
+    }
+
+    #[test]
+    fn test_far_enemy_velocity_is_measured_over_its_own_update() {
+        let data = LevelData {
+            enemies: vec![EnemySpawn::new(5000.0, 384.0, 4000.0, 6000.0)],
+            ..flat_level(6000.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        for _ in 0..FAR_UPDATE_INTERVAL * 2 {
+            let x = level.enemies[0].x;
+            level.step(&InputState::default(), 1.0 / 60.0);
+            // Catching up on skipped steps covers more ground, but no faster
+            let enemy = &level.enemies[0];
+            if enemy.x != x {
+                assert!((enemy.velocity.0.abs() - enemy.speed).abs() < 1e-2, "{:?}", enemy.velocity);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reaching_the_goal_pans_and_zooms_the_camera_onto_it() {
+        let mut level = SimpleLevel::new();
+        level.finish_level();
+        assert!(matches!(level.camera.target, CameraTarget::Path(_)));
+        assert_eq!(level.camera_zoom().0, 1.0);
+
+        let goal_x = level.goal.x + level.goal.width / 2.0;
+        let mut zooms = Vec::new();
+        for _ in 0..(GOAL_SEQUENCE_TIME * 60.0) as usize {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            zooms.push(level.camera.zoom);
+        }
+        assert!(zooms.windows(2).all(|w| w[1] >= w[0]), "zoom only ever increases");
+        assert!((level.camera.zoom - GOAL_ZOOM).abs() < 0.05);
+
+        // The goal ends up in the middle of the zoomed view
+        let view_center = level.camera.x + level.view_width / level.camera.zoom / 2.0;
+        assert!((view_center - goal_x).abs() < 5.0, "view centred on {} not {}", view_center, goal_x);
+        assert!(level.finish_time.unwrap() >= GOAL_SEQUENCE_TIME - 1e-3);
+    }
+
+    #[test]
+    fn test_fire_pressed_during_cooldown_fires_when_it_ends() {
+        let mut level = SimpleLevel::new_with(PowerUp::FireFlower);
+        let fire = InputState { fire_pressed: true, ..Default::default() };
+        let dt = 1.0 / 60.0;
+        level.step(&fire, dt);
+        assert_eq!(level.fireball_count(), 1);
+
+        // Pressed again just before the cooldown is over: nothing yet
+        let early_steps = ((FIRE_COOLDOWN - FIRE_BUFFER_TIME / 2.0) / dt) as usize;
+        for _ in 0..early_steps {
+            level.step(&InputState::default(), dt);
+        }
+        level.step(&fire, dt);
+        assert_eq!(level.fireball_count(), 1);
+        assert!(level.player.fire_cooldown > 0.0);
+
+        // ...but the buffered press throws the moment it ends
+        let mut steps = 0;
+        while level.fireball_count() < 2 {
+            level.step(&InputState::default(), dt);
+            steps += 1;
+            assert!(steps as f32 * dt <= FIRE_BUFFER_TIME, "buffered press was dropped");
+        }
+        assert!(level.player.fire_cooldown > FIRE_COOLDOWN - dt * 1.5, "threw as soon as the cooldown ended");
+
+        // A press long before the cooldown ends is forgotten
+        level.step(&fire, dt);
+        for _ in 0..30 {
+            level.step(&InputState::default(), dt);
+        }
+        assert_eq!(level.fireball_count(), 2);
+    }
+
+    #[test]
+    fn test_camera_scrolls_only_past_the_margin() {
+        let data = LevelData {
+            player_spawn: (100.0, 380.0),
+            scroll_margins: Some(ScrollMargins { behind: 0.3, ahead: 0.6 }),
+            ..flat_level(3000.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let right = InputState { right: true, ..Default::default() };
+        while level.player.x + level.player.width / 2.0 < 0.6 * VIEW_WIDTH - 5.0 {
+            level.step(&right, 1.0 / 60.0);
+            assert_eq!(level.camera.x, 0.0, "scrolled with the player at {}", level.player.x);
+        }
+        for _ in 0..30 {
+            level.step(&right, 1.0 / 60.0);
+        }
+        assert!(level.camera.x > 0.0);
+    }
+
+    #[test]
+    fn test_run_stats_count_stomps_coins_and_jumps() {
+        let mut level = stomp_test_level(RespawnPolicy::default());
+        stomp_enemy(&mut level);
+        assert_eq!(level.stats.enemies_stomped, 1);
+        assert_eq!(level.stats.coins_collected, 0);
+
+        level.coins.push(Coin::new(level.player.x + 5.0, level.player.y + 5.0));
+        level.step(&InputState::default(), 1.0 / 60.0);
+        assert_eq!(level.stats.coins_collected, 1);
+
+        for _ in 0..60 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        level.step(&InputState { jump_pressed: true, jump_held: true, ..Default::default() }, 1.0 / 60.0);
+        assert_eq!(level.stats.jumps, 1);
+        assert!(level.stats.time_played > 1.0);
+    }
+
+    #[test]
+    fn test_enemy_follows_waypoints_in_order_and_loops() {
+        let path = vec![Waypoint::new(100.0, 0.0, 100.0), Waypoint { pause: 0.5, ..Waypoint::new(100.0, 100.0, 100.0) }, Waypoint::new(0.0, 0.0, 200.0)];
+        let mut enemy = Enemy::new(0.0, 0.0, 0.0, 0.0).with_path(path.clone());
+        let mut visited = Vec::new();
+        for _ in 0..(5.5 * 60.0) as usize {
+            let heading = enemy.waypoint;
+            enemy.update(1.0 / 60.0);
+            if enemy.waypoint != heading {
+                assert_eq!((enemy.x, enemy.y), (path[heading].x, path[heading].y));
+                visited.push(heading);
+            }
+        }
+        assert_eq!(visited, vec![0, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn test_respawn_inside_a_wall_moves_to_free_ground_nearby() {
+        // A crate sits on the floor right where the spawn point is
+        let data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 600.0, 20.0), Platform::new(40.0, 300.0, 60.0, 100.0)],
+            player_spawn: (50.0, 350.0),
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.kill_player();
+
+        let player = &level.player;
+        // Four search steps left clears the crate, nearer than going over it
+        assert_eq!((player.x, player.y), (18.0, 400.0 - PLAYER_SIZE));
+        let (_, shapes) = level.solids();
+        assert!(!shapes.iter().any(|p| p.intersects(player.x, player.y, player.width, player.height)));
+        assert!(player.ground_below(&shapes).is_some());
+
+        // A spawn point in the open drops straight down onto the floor
+        level.spawn_point = (300.0, 100.0);
+        level.kill_player();
+        assert_eq!((level.player.x, level.player.y), (300.0, 400.0 - PLAYER_SIZE));
+    }
+
+    #[test]
+    fn test_camera_bounds_hold_the_view_while_the_player_walks_out() {
+        let data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 3000.0, 20.0)],
+            player_spawn: (400.0, 380.0),
+            camera_bounds: Some(CameraBounds { x: 0.0, y: 0.0, width: 1000.0, height: 600.0 }),
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let right = InputState { right: true, ..Default::default() };
+        for _ in 0..600 {
+            level.step(&right, 1.0 / 60.0);
+        }
+
+        // The player walks on well past the bounds, but the view stops at their edge
+        assert!(level.player.x > 1500.0);
+        assert_eq!(level.camera.x, 1000.0 - level.view_width);
+    }
+
+    #[test]
+    fn test_auto_jump_jumps_at_a_gap_edge() {
+        let data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 300.0, 20.0), Platform::new(360.0, 400.0, 300.0, 20.0)],
+            player_spawn: (200.0, 400.0 - PLAYER_SIZE),
+            blocks: Vec::new(),
+            enemies: Vec::new(),
+            ..Default::default()
+        };
+        let run_right = |auto_jump: bool| {
+            let mut level = SimpleLevel::from_data(&data);
+            level.settings.auto_jump = auto_jump;
+            let right = InputState { right: true, ..Default::default() };
+            let mut jumped_at = None;
+            for _ in 0..90 {
+                level.step(&right, 1.0 / 60.0);
+                if jumped_at.is_none() && level.events.contains(&GameEvent::PlayerJumped) {
+                    jumped_at = Some(level.player.x + level.player.width);
+                }
+            }
+            (jumped_at, level.player.y, level.stats.deaths)
+        };
+
+        // Without pressing jump, the player takes off at the edge and clears the gap
+        let (jumped_at, y, deaths) = run_right(true);
+        assert!(jumped_at.is_some_and(|front| (300.0..300.0 + AUTO_JUMP_LOOKAHEAD + 8.0).contains(&front)), "{:?}", jumped_at);
+        assert_eq!((y, deaths), (400.0 - PLAYER_SIZE, 0));
+
+        // With the option off they run straight into the gap
+        let (jumped_at, _, deaths) = run_right(false);
+        assert_eq!((jumped_at, deaths), (None, 1));
+    }
+
+    #[test]
+    fn test_big_coin_has_its_own_counter() {
+        let data = LevelData {
+            player_spawn: (100.0, 380.0),
+            coins: vec![Coin::new(400.0, 380.0)],
+            big_coins: vec![BigCoin::new(200.0, 376.0), BigCoin::new(500.0, 200.0)],
+            ..flat_level(600.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        let right = InputState { right: true, ..Default::default() };
+        while level.big_coins_collected == 0 && level.level_time < 2.0 {
+            level.step(&right, 1.0 / 60.0);
+        }
+
+        assert_eq!(level.big_coins_collected, 1);
+        assert_eq!(level.coins_collected, 0);
+        assert_eq!(level.score, level.settings.scoring.big_coin_points());
+        // Found coins are remembered for later attempts
+        assert!(level.progress.has_big_coin(&data.name, 0));
+        level.reset();
+        assert_eq!(level.big_coins_collected, 0);
+        assert_eq!(level.progress.big_coins_found(&data.name), 1);
+    }
+
+    #[test]
+    fn test_render_scale_only_changes_how_big_the_player_is_drawn() {
+        let mut level = stomp_test_level(RespawnPolicy::default());
+        let camera = ViewCamera::new(macroquad::prelude::Rect::new(0.0, 0.0, 800.0, 600.0));
+        let normal = level.drawn_player_bounds(&camera);
+        assert_eq!((normal.w, normal.h), (level.player.width, level.player.height));
+
+        level.settings.render_scale = 2.0;
+        let doubled = level.drawn_player_bounds(&camera);
+        assert_eq!((doubled.w, doubled.h), (normal.w * 2.0, normal.h * 2.0));
+        // Still standing on the same spot, with the hitbox untouched
+        assert_eq!(doubled.bottom(), normal.bottom());
+        assert_eq!(doubled.center().x, normal.center().x);
+        assert_eq!((level.player.width, level.player.height), (PLAYER_SIZE, PLAYER_SIZE));
+    }
+
+    #[test]
+    fn test_snapped_camera_scrolls_by_whole_pixels() {
+        let mut level = stomp_test_level(RespawnPolicy::default());
+        level.player.respawn(400.0, 380.0);
+        let right = InputState { right: true, ..Default::default() };
+        let mut offsets = Vec::new();
+        for _ in 0..30 {
+            level.step(&right, 1.0 / 60.0);
+            level.settings.snap_camera = false;
+            offsets.push(level.camera_offset());
+            level.settings.snap_camera = true;
+            let snapped = level.camera_offset();
+            assert_eq!(snapped.fract(), 0.0, "camera offset {} isn't a whole pixel", snapped);
+        }
+        // The eased camera underneath does land between pixels
+        assert!(offsets.iter().any(|x| x.fract() != 0.0));
+    }
+
+    #[test]
+    fn test_jump_tuned_by_height_and_time_peaks_there() {
+        let physics = PhysicsConfig::from_jump(96.0, 0.4);
+        assert!((physics.jump_height() - 96.0).abs() < 1e-3);
+        assert!((physics.time_to_apex() - 0.4).abs() < 1e-6);
+
+        let platforms = [Platform::new(0.0, 400.0, 600.0, 40.0)];
+        let mut player = Player::new(100.0, 380.0);
+        player.physics = physics;
+        player.update(&platforms, &InputState::default(), 1.0 / 60.0);
+        let start_y = player.y;
+        player.update(&platforms, &InputState { jump_pressed: true, jump_held: true, ..Default::default() }, 1.0 / 60.0);
+        let (mut apex_y, mut apex_time, mut time) = (player.y, 0.0, 1.0 / 60.0);
+        while !player.on_ground {
+            player.update(&platforms, &InputState { jump_held: true, ..Default::default() }, 1.0 / 60.0);
+            time += 1.0 / 60.0;
+            if player.y < apex_y {
+                (apex_y, apex_time) = (player.y, time);
+            }
+        }
+        // Fixed time steps land within a step of the exact arc
+        assert!((start_y - apex_y - 96.0).abs() < 96.0 * 0.05, "peaked {} up", start_y - apex_y);
+        assert!((apex_time - 0.4f32).abs() <= 1.0 / 60.0 + 1e-4, "peaked after {}s", apex_time);
+    }
+
+    #[test]
+    fn test_movement_presets_change_the_feel() {
+        let mut physics = PhysicsConfig::from_jump(96.0, 0.4);
+        MovementPreset::Classic.apply(&mut physics);
+        assert_eq!(physics, PhysicsConfig { gravity: physics.gravity, jump_strength: physics.jump_strength, ..Default::default() });
+        MovementPreset::Modern.apply(&mut physics);
+        assert!(physics.acceleration.is_finite() && physics.friction.is_finite());
+        assert!(physics.coyote_time > 0.0 && physics.jump_buffer > 0.0 && physics.variable_jump);
+        // The jump tuning survives a change of feel
+        assert!((physics.jump_height() - 96.0).abs() < 1e-3);
+
+        // Classic runs at full speed straight away, modern builds up to it and coasts to a stop
+        let platforms = [Platform::new(0.0, 400.0, 600.0, 40.0)];
+        let right = InputState { right: true, ..Default::default() };
+        let speeds = |preset: MovementPreset| {
+            let mut player = Player::new(100.0, 380.0);
+            preset.apply(&mut player.physics);
+            player.update(&platforms, &right, 1.0 / 60.0);
+            let started = player.velocity_x;
+            for _ in 0..60 {
+                player.update(&platforms, &right, 1.0 / 60.0);
+            }
+            player.update(&platforms, &InputState::default(), 1.0 / 60.0);
+            (started, player.velocity_x)
+        };
+        assert_eq!(speeds(MovementPreset::Classic), (PLAYER_SPEED, 0.0));
+        let (started, coasting) = speeds(MovementPreset::Modern);
+        assert!((started - 1200.0 / 60.0).abs() < 1e-3);
+        assert!((coasting - (PLAYER_SPEED - 1600.0 / 60.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_destroying_a_platform_removes_it_and_drops_debris() {
+        let ledge = Platform { destructible: true, ..Platform::new(100.0, 300.0, 120.0, 20.0) };
+        let data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 600.0, 20.0), ledge],
+            blocks: Vec::new(),
+            enemies: Vec::new(),
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.player.respawn(140.0, 280.0);
+        level.step(&InputState::default(), 1.0 / 60.0);
+        assert_eq!(level.player.y, 280.0);
+
+        assert!(level.destroy_platform(1));
+        assert!(!level.destroy_platform(1), "a platform can only be destroyed once");
+        assert!(level.events.contains(&GameEvent::PlatformDestroyed { platform: 1 }));
+        let (sources, _) = level.solids();
+        assert_eq!(sources, vec![Solid::Platform(0)]);
+        assert!(level.debris().count() >= 2);
+
+        // The player drops through the gap, and the debris lands on the floor below
+        for _ in 0..60 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(level.player.y, 380.0);
+        assert!(level.debris().count() >= 2);
+        assert!(level.debris().all(|piece| piece.y + DEBRIS_SIZE == 400.0 && piece.velocity_y == 0.0));
+
+        // Then fades away
+        for _ in 0..60 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(level.debris().count(), 0);
+    }
+
+    /// Jump straight up into a brick above the player and return the resulting events
+    fn hit_brick_from_below(power: PowerUp) -> (SimpleLevel, Vec<GameEvent>) {
+        let data = LevelData {
+            blocks: vec![Block::brick(100.0, 330.0)],
+            ..flat_level(400.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.player.set_power(power);
+        level.player.respawn(100.0, 400.0 - level.player.height);
+        level.player.on_ground = true;
+
+        let mut events = Vec::new();
+        level.step(&InputState { jump_pressed: true, ..Default::default() }, 1.0 / 60.0);
+        for _ in 0..30 {
+            events.extend_from_slice(level.events());
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        events.extend_from_slice(level.events());
+        (level, events)
+    }
+
+    #[test]
+    fn test_brick_bumps_when_small_and_breaks_when_big() {
+        let (level, events) = hit_brick_from_below(PowerUp::Small);
+        assert!(events.contains(&GameEvent::BrickBumped { block: 0 }));
+        assert!(!events.contains(&GameEvent::BrickBroken { block: 0 }));
+        assert!(level.blocks[0].is_solid(false));
+
+        let (level, events) = hit_brick_from_below(PowerUp::Big);
+        assert!(events.contains(&GameEvent::BrickBroken { block: 0 }));
+        assert!(!events.contains(&GameEvent::BrickBumped { block: 0 }));
+        assert!(!level.blocks[0].is_solid(false));
+    }
+
+    #[test]
+    fn test_defeated_enemy_drops_its_loot() {
+        let data = LevelData {
+            enemies: vec![EnemySpawn {
+                loot: Some(LootTable::always(Loot::PowerUp(PowerUp::Big))),
+                ..EnemySpawn::new(300.0, 384.0, 300.0, 300.0)
+            }],
+            ..flat_level(600.0)
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.loot_rng = SeededRng::new(7);
+        stomp_enemy(&mut level);
+
+        // A mushroom pops out where the enemy was
+        assert_eq!(level.pickups, vec![Pickup::centred(308.0, 392.0, Loot::PowerUp(PowerUp::Big))]);
+        for _ in 0..60 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert!(level.pickups[0].collected);
+        assert_eq!(level.player.power, PowerUp::Big);
+    }
+
+    #[test]
+    fn test_explosion_effect_removes_itself_when_done() {
+        let mut level = stomp_test_level(RespawnPolicy::Persist);
+        level.spawn_effect(Effect::explosion(300.0, 380.0, 16.0));
+        assert_eq!(level.effect_count(), 1);
+
+        for _ in 0..60 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(level.effect_count(), 0);
+    }
+
+    #[test]
+    fn test_results_tally_counts_time_and_coins_into_the_final_score() {
+        let mut level = SimpleLevel::new();
+        level.level_time = 10.25;
+        level.coins_collected = 4;
+        level.score = 700;
+        level.finish_level();
+        let time_left = (level.star_thresholds.par_time - 10.25).ceil() as u32;
+        let expected = 700 + time_left * level.settings.scoring.time_bonus + 4 * level.settings.scoring.coin_bonus;
+
+        // Nothing is counted during the goal sequence
+        for _ in 0..(GOAL_SEQUENCE_TIME * 60.0) as usize - 1 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(level.score, 700);
+
+        let mut ticks = 0;
+        for _ in 0..600 {
+            level.step(&InputState::default(), 1.0 / 60.0);
+            ticks += level.events.iter().filter(|e| **e == GameEvent::TallyTick).count();
+        }
+        let tally = level.tally.unwrap();
+        assert!(tally.is_finished());
+        assert_eq!((tally.time_left(), tally.coins_left()), (0, 0));
+        assert_eq!((level.score, level.best_score), (expected, expected));
+        assert_eq!(ticks, time_left as usize + 4);
+        assert_eq!(level.level_time, 10.25);
+    }
+
+    #[test]
+    fn test_results_tally_pays_out_through_the_scoring_multiplier() {
+        let mut level = SimpleLevel::new();
+        level.settings.scoring.multiplier = 0.5;
+        level.level_time = 10.25;
+        level.coins_collected = 4;
+        level.score = 700;
+        level.finish_level();
+        let time_left = (level.star_thresholds.par_time - 10.25).ceil() as u32;
+        let tally = level.tally.unwrap();
+        assert_eq!(tally.final_score(), 700 + time_left * 25 + 4 * 50);
+    }
+
+    #[test]
+    fn test_player_can_be_drawn_behind_foreground_layers() {
+        let tree_crowns = vec![BackgroundTile::new(DecorationKind::Bush, 100.0, 300.0)];
+        let data = LevelData {
+            background: vec![BackgroundLayer::new(0.5, Vec::new()), BackgroundLayer::foreground(1.0, tree_crowns)],
+            ..Default::default()
+        };
+        let level = SimpleLevel::from_data(&data);
+        use DrawPass::*;
+        assert_eq!(level.draw_passes(), vec![Decoration(0), Trees, Level, Decoration(1), Player]);
+
+        let mut level = SimpleLevel::from_data(&LevelData { player_behind_foreground: true, ..data });
+        assert_eq!(level.draw_passes(), vec![Decoration(0), Trees, Level, Player, Decoration(1)]);
+        level.reset();
+        assert!(level.player_behind_foreground);
+    }
+
+    #[test]
+    fn test_speed_shoes_raise_top_speed_until_they_wear_off() {
+        let floor = [Platform::new(0.0, 400.0, 100_000.0, 40.0)];
+        let mut player = Player::new(100.0, 380.0);
+        player.physics.acceleration = 1200.0;
+        let run = InputState { right: true, ..Default::default() };
+        let dt = 1.0 / 60.0;
+        let top_speed = |player: &mut Player, steps: usize| {
+            let mut fastest: f32 = 0.0;
+            for _ in 0..steps {
+                player.update(&floor, &run, dt);
+                fastest = fastest.max(player.velocity_x);
+            }
+            fastest
+        };
+
+        let normal = top_speed(&mut player, 60);
+        assert_eq!(normal, PLAYER_SPEED);
+
+        player.speed_timer = SPEED_SHOES_TIME;
+        let boosted = top_speed(&mut player, 60);
+        assert_eq!(boosted, PLAYER_SPEED * SPEED_SHOES_BOOST);
+
+        // Once the shoes wear off the player slows back down to the usual top speed
+        top_speed(&mut player, (SPEED_SHOES_TIME / dt) as usize);
+        assert_eq!(player.speed_timer, 0.0);
+        assert_eq!(top_speed(&mut player, 60), PLAYER_SPEED);
+    }
+
+    #[test]
+    fn test_random_enemy_starts_differ_by_seed_and_repeat_for_the_same_seed() {
+        let mut data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 1600.0, 20.0)],
+            enemies: (0..8).map(|i| EnemySpawn::new(100.0 + i as f32 * 180.0, 384.0, 50.0 + i as f32 * 180.0, 200.0 + i as f32 * 180.0)).collect(),
+            ..Default::default()
+        };
+        let starts = |level: &SimpleLevel| level.enemies.iter().map(|e| (e.direction, e.x)).collect::<Vec<_>>();
+
+        // Off by default: everyone faces right from where they were placed
+        let plain = SimpleLevel::from_data(&data);
+        assert!(plain.enemies.iter().all(|e| e.direction == EnemyDirection::Right));
+
+        data.random_enemy_starts = true;
+        let mut level = SimpleLevel::from_data(&data);
+        level.reseed(1);
+        let first = starts(&level);
+        level.reseed(2);
+        let second = starts(&level);
+        assert_ne!(
+            first.iter().map(|s| s.0).collect::<Vec<_>>(),
+            second.iter().map(|s| s.0).collect::<Vec<_>>()
+        );
+        level.reseed(1);
+        assert_eq!(starts(&level), first);
+        for (enemy, (_, x)) in level.enemies.iter().zip(&first) {
+            assert!((enemy.patrol_start..=enemy.patrol_end).contains(x));
+        }
+
+        // Restarting keeps the seed, and with it the starts
+        level.step(&InputState::default(), 1.0 / 60.0);
+        level.reset();
+        assert_eq!(starts(&level), first);
+    }
+
+    #[test]
+    fn test_dying_drops_a_share_of_coins_that_can_be_picked_up_again() {
+        let data = LevelData {
+            platforms: vec![Platform::new(0.0, 400.0, 800.0, 20.0)],
+            player_spawn: (100.0, 368.0),
+            enemies: Vec::new(),
+            ..Default::default()
+        };
+        let mut level = SimpleLevel::from_data(&data);
+        level.settings.death_coin_drop = 0.5;
+        level.coins_collected = 10;
+        level.player.respawn(500.0, 368.0);
+        level.kill_player();
+
+        assert_eq!(level.coins_collected, 5);
+        assert_eq!(level.dropped_coins.len(), 5);
+        assert!(level.dropped_coins.iter().all(|c| !c.collected && (c.x - 500.0).abs() < 60.0));
+
+        // Walk back to where they were dropped to get them again
+        let right = InputState { right: true, ..Default::default() };
+        for _ in 0..300 {
+            level.step(&right, 1.0 / 60.0);
+            if level.dropped_coins.iter().all(|c| c.collected) {
+                break;
+            }
+        }
+        assert!(level.dropped_coins.iter().all(|c| c.collected));
+        assert_eq!(level.coins_collected, 10);
+
+        // Left off, dying keeps every coin
+        level.settings.death_coin_drop = 0.0;
+        level.kill_player();
+        assert_eq!(level.coins_collected, 10);
+        assert!(level.dropped_coins.is_empty());
+    }
+
+    #[test]
+    fn test_landing_on_a_bouncy_platform_rebounds_with_the_impact() {
+        let platforms = [Platform::bouncy(0.0, 400.0, 200.0, 20.0, 0.5)];
+        let dt = 1.0 / 60.0;
+        let land = |platforms: &[Platform], fall_speed: f32| {
+            let mut player = Player::new(50.0, 400.0 - PLAYER_SIZE - 1.0);
+            player.velocity_y = fall_speed;
+            let impact = fall_speed + player.physics.gravity * dt;
+            player.update(platforms, &InputState::default(), dt);
+            (impact, player)
+        };
+
+        for fall_speed in [300.0, 600.0] {
+            let (impact, player) = land(&platforms, fall_speed);
+            assert_eq!(player.y, 400.0 - PLAYER_SIZE);
+            assert!((player.velocity_y + impact * 0.5).abs() < 1e-3, "{} from {}", player.velocity_y, impact);
+            assert!(!player.on_ground);
+        }
+
+        // Plain ground soaks up the whole fall
+        let (_, player) = land(&[Platform::new(0.0, 400.0, 200.0, 20.0)], 600.0);
+        assert_eq!(player.velocity_y, 0.0);
+        assert!(player.on_ground);
+    }
+
+    #[test]
+    fn test_console_rebinding_refuses_a_key_another_action_has() {
+        let mut level = SimpleLevel::new();
+        assert_eq!(level.run_console_command("bind fire space"), "Space is bound to both Jump and Fire, not changed");
+        assert_eq!(level.settings.key_bindings.keys(Action::Fire), &[KeyCode::X, KeyCode::LeftShift]);
+
+        assert_eq!(level.run_console_command("bind fire q"), "Fire bound to Q");
+        assert_eq!(level.settings.key_bindings.keys(Action::Fire), &[KeyCode::Q]);
+    }
+
+    #[test]
+    fn test_draw_order_keeps_trees_and_backdrops_behind_the_level() {
+        let tree_crowns = vec![BackgroundTile::new(DecorationKind::Bush, 100.0, 300.0)];
+        let data = LevelData {
+            background: vec![BackgroundLayer::new(0.5, Vec::new()), BackgroundLayer::foreground(1.0, tree_crowns)],
+            player_behind_foreground: true,
+            ..Default::default()
+        };
+        let level = SimpleLevel::from_data(&data);
+        let order = level.capture_draw_order();
+        assert!(order.is_valid());
+        use DrawLayer::*;
+        assert_eq!(order.layers(), [Background, Background, World, World, Foreground]);
+    }
+}
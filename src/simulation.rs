@@ -0,0 +1,162 @@
+//! Headless Simulation
+//!
+//! Runs a level with `SimpleLevel::step` from a scripted sequence of inputs,
+//! without opening a window. Used by the `simulate` binary so gameplay can be
+//! checked from scripts and CI.
+//!
+//! An input script has one line per step listing the held actions
+//! (`left`, `right`, `jump`), optionally prefixed with a repeat count:
+//!
+//! ```text
+//! # walk right for half a second, then jump
+//! 30 right
+//! right jump
+//! ```
+//!
+//! Blank lines and lines starting with `#` are ignored, and a line containing
+//! only `idle` (or just a count) means no input.
+
+use serde::Serialize;
+
+use crate::input::InputState;
+use crate::simple_level::SimpleLevel;
+
+/// Fixed time step used for every simulated frame
+pub const SIMULATION_DT: f32 = 1.0 / 60.0;
+
+/// Inputs for the 10-second recording of the default level
+pub const RECORDING_SCRIPT: &str = "\
+# Run onto the floating platform for its coin and drop to the low ground
+55 right
+20 right jump
+40 idle
+# Hop about down there
+20 right
+30 left jump
+15 left
+30 right jump
+40 idle
+# Miss the jump back and fall, then try again from the start
+30 left jump
+30 idle
+40 right
+30 right jump
+60 idle
+30 left jump
+130 idle
+";
+
+/// Steps in the 10-second recording
+pub const RECORDING_STEPS: usize = 600;
+
+/// The recording keeps one frame every this many steps, 10 a second
+pub const RECORDING_FRAME_INTERVAL: usize = 6;
+
+/// Whether the recording captures a frame after this step
+pub fn is_recording_frame(step: usize) -> bool {
+    step.is_multiple_of(RECORDING_FRAME_INTERVAL)
+}
+
+/// Parse an input script into one `InputState` per step
+pub fn parse_script(script: &str) -> Result<Vec<InputState>, String> {
+    let mut inputs = Vec::new();
+
+    for (line_number, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace().peekable();
+        let count = match tokens.peek().and_then(|t| t.parse::<usize>().ok()) {
+            Some(count) => {
+                tokens.next();
+                count
+            }
+            None => 1,
+        };
+
+        let mut input = InputState::default();
+        for token in tokens {
+            match token {
+                "left" => input.left = true,
+                "right" => input.right = true,
+                "jump" => input.jump_held = true,
+                "idle" => {}
+                other => return Err(format!("line {}: unknown action '{}'", line_number + 1, other)),
+            }
+        }
+        inputs.extend(std::iter::repeat_n(input, count));
+    }
+
+    // Jumps trigger on the press, so mark the first step of each held jump
+    let mut was_held = false;
+    for input in &mut inputs {
+        input.jump_pressed = input.jump_held && !was_held;
+        was_held = input.jump_held;
+    }
+
+    Ok(inputs)
+}
+
+/// State of the level at the end of a simulation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationResult {
+    pub steps: usize,
+    pub player_x: f32,
+    pub player_y: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    pub on_ground: bool,
+    pub level_time: f32,
+    pub coins_collected: u32,
+    pub score: u32,
+    pub enemies_alive: usize,
+    pub game_won: bool,
+}
+
+impl SimulationResult {
+    /// Capture the current state of a level
+    pub fn capture(level: &SimpleLevel, steps: usize) -> Self {
+        Self {
+            steps,
+            player_x: level.player.x,
+            player_y: level.player.y,
+            velocity_x: level.player.velocity_x,
+            velocity_y: level.player.velocity_y,
+            on_ground: level.player.on_ground,
+            level_time: level.level_time,
+            coins_collected: level.coins_collected,
+            score: level.score,
+            enemies_alive: level.enemies().iter().filter(|e| e.alive).count(),
+            game_won: level.game_won,
+        }
+    }
+}
+
+/// Step the level `steps` times, feeding the scripted inputs in order.
+/// Steps past the end of the script get no input.
+pub fn simulate(level: &mut SimpleLevel, inputs: &[InputState], steps: usize) -> SimulationResult {
+    for step in 0..steps {
+        let input = inputs.get(step).copied().unwrap_or_default();
+        level.step(&input, SIMULATION_DT);
+    }
+    SimulationResult::capture(level, steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script_repeats_and_marks_jump_presses() {
+        let inputs = parse_script("# comment\n2 right\n\n2 right jump\nidle\n").unwrap();
+        assert_eq!(inputs.len(), 5);
+        assert!(inputs[0].right && !inputs[0].jump_held);
+        assert!(inputs[2].jump_pressed && inputs[2].jump_held);
+        assert!(!inputs[3].jump_pressed && inputs[3].jump_held);
+        assert_eq!(inputs[4], InputState::default());
+
+        assert_eq!(parse_script("fly").unwrap_err(), "line 1: unknown action 'fly'");
+    }
+}
@@ -0,0 +1,106 @@
+//! Sound Effects
+//!
+//! Sound effects are placed in the level: each one is panned towards the side
+//! of the screen it happened on and gets quieter the further it is from the
+//! middle of the view. Like `MusicPlayer`, `SoundPlayer` only works out what
+//! to play and how; an audio backend drains `take_queued()` each frame and
+//! plays the sounds with the given volume and pan.
+
+/// How far from the middle of the view sounds fall silent, in view widths
+const SILENT_DISTANCE: f32 = 1.5;
+
+/// The sound effects the game can play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundId {
+    Jump,
+    Coin,
+    Stomp,
+    Bump,
+    BrickBreak,
+    Spring,
+    Key,
+    Hurt,
+    Death,
+    /// One step of the results tally
+    Tally,
+}
+
+/// A sound effect ready to play
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayedSound {
+    pub id: SoundId,
+    /// From 0 (silent) to 1
+    pub volume: f32,
+    /// From -1 (left speaker only) through 0 (centred) to 1 (right speaker only)
+    pub pan: f32,
+}
+
+/// Pan and volume for a sound at `world_x`, heard from the middle of a view
+/// centred on `listener_x`. Sounds at the edge of the view are panned fully
+/// to that side.
+pub fn spatialize(world_x: f32, listener_x: f32, view_width: f32) -> (f32, f32) {
+    let offset = world_x - listener_x;
+    let pan = (offset / (view_width / 2.0)).clamp(-1.0, 1.0);
+    let volume = (1.0 - offset.abs() / (view_width * SILENT_DISTANCE)).clamp(0.0, 1.0);
+    (pan, volume)
+}
+
+/// Queues sound effects, positioned relative to the camera
+#[derive(Debug, Clone)]
+pub struct SoundPlayer {
+    listener_x: f32,
+    view_width: f32,
+    queued: Vec<PlayedSound>,
+}
+
+impl SoundPlayer {
+    /// Create a player listening from the middle of a view this wide
+    pub fn new(view_width: f32) -> Self {
+        Self { listener_x: view_width / 2.0, view_width, queued: Vec::new() }
+    }
+
+    /// Move the listener to the middle of the camera's view
+    pub fn set_listener(&mut self, camera_x: f32, view_width: f32) {
+        self.listener_x = camera_x + view_width / 2.0;
+        self.view_width = view_width;
+    }
+
+    /// Play a sound at full volume in both speakers
+    pub fn play(&mut self, id: SoundId) {
+        self.queued.push(PlayedSound { id, volume: 1.0, pan: 0.0 });
+    }
+
+    /// Play a sound that happened at `world_x` in the level. Sounds too far
+    /// away to hear are skipped.
+    pub fn play_at(&mut self, id: SoundId, world_x: f32) {
+        let (pan, volume) = spatialize(world_x, self.listener_x, self.view_width);
+        if volume > 0.0 {
+            self.queued.push(PlayedSound { id, volume, pan });
+        }
+    }
+
+    /// Hand over every sound queued since the last call
+    pub fn take_queued(&mut self) -> Vec<PlayedSound> {
+        std::mem::take(&mut self.queued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sounds_pan_and_fade_with_position() {
+        // A camera at x 1000 with an 800 wide view is listening from x 1400
+        let mut sounds = SoundPlayer::new(800.0);
+        sounds.set_listener(1000.0, 800.0);
+        sounds.play_at(SoundId::Coin, 1000.0);
+        sounds.play_at(SoundId::Coin, 1400.0);
+        sounds.play_at(SoundId::Coin, 1600.0);
+        sounds.play_at(SoundId::Coin, 5000.0);
+
+        let heard: Vec<(f32, f32)> = sounds.take_queued().iter().map(|s| (s.pan, s.volume)).collect();
+        assert_eq!(heard, vec![(-1.0, 1.0 - 400.0 / 1200.0), (0.0, 1.0), (0.5, 1.0 - 200.0 / 1200.0)]);
+        assert!(sounds.take_queued().is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+//! Run Statistics
+//!
+//! Tallies of what the player got up to during an attempt at a level, shown
+//! on the results screen. Counts are kept up to date from the level's game
+//! events; distance and time are added by the level as it steps.
+
+use crate::events::GameEvent;
+
+/// Pixels counted as a metre when showing the distance travelled
+const PIXELS_PER_METRE: f32 = 16.0;
+
+/// What happened during one run of a level
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunStats {
+    pub jumps: u32,
+    pub enemies_stomped: u32,
+    pub coins_collected: u32,
+    /// Pixels the player has moved horizontally, either way
+    pub distance: f32,
+    /// Seconds of play, not counting pauses
+    pub time_played: f32,
+    pub deaths: u32,
+}
+
+impl RunStats {
+    /// Count an event towards the stats it affects
+    pub fn record(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PlayerJumped => self.jumps += 1,
+            GameEvent::EnemyStomped { .. } => self.enemies_stomped += 1,
+            GameEvent::CoinCollected { .. } | GameEvent::CoinDispensed { .. } => self.coins_collected += 1,
+            GameEvent::PlayerDied => self.deaths += 1,
+            _ => {}
+        }
+    }
+
+    /// One line per stat, for the results screen
+    pub fn summary(&self) -> Vec<String> {
+        vec![
+            format!("Jumps: {}", self.jumps),
+            format!("Enemies stomped: {}", self.enemies_stomped),
+            format!("Coins: {}", self.coins_collected),
+            format!("Distance: {:.0}m", self.distance / PIXELS_PER_METRE),
+            format!("Time played: {:.1}s", self.time_played),
+            format!("Deaths: {}", self.deaths),
+        ]
+    }
+}
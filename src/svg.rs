@@ -0,0 +1,65 @@
+//! SVG Export
+//!
+//! A minimal SVG document builder used to export level layouts as scalable
+//! drawings for documentation and design review.
+
+use std::fmt::Write;
+
+/// Escape text for use in XML content or attribute values
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Builds an SVG document one shape at a time
+#[derive(Debug, Clone)]
+pub struct SvgDocument {
+    width: f32,
+    height: f32,
+    body: String,
+}
+
+impl SvgDocument {
+    /// Start an empty drawing of the given size
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { width, height, body: String::new() }
+    }
+
+    /// Add a rectangle with a class and a fill color
+    pub fn rect(&mut self, class: &str, x: f32, y: f32, width: f32, height: f32, fill: &str) {
+        let _ = writeln!(
+            self.body,
+            r#"  <rect class="{}" x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            escape(class), x, y, width, height, escape(fill)
+        );
+    }
+
+    /// Add a circle with a class and a fill color
+    pub fn circle(&mut self, class: &str, cx: f32, cy: f32, r: f32, fill: &str) {
+        let _ = writeln!(
+            self.body,
+            r#"  <circle class="{}" cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            escape(class), cx, cy, r, escape(fill)
+        );
+    }
+
+    /// Add a text label with its baseline at `(x, y)`
+    pub fn label(&mut self, x: f32, y: f32, text: &str) {
+        let _ = writeln!(
+            self.body,
+            r#"  <text class="label" x="{}" y="{}" font-family="sans-serif" font-size="10">{}</text>"#,
+            x, y, escape(text)
+        );
+    }
+
+    /// The finished document
+    pub fn finish(&self) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+             {body}</svg>\n",
+            w = self.width,
+            h = self.height,
+            body = self.body,
+        )
+    }
+}
@@ -0,0 +1,107 @@
+//! Results Tally
+//!
+//! Once the goal sequence is over, the time left on the clock and the coins
+//! collected count down one at a time into the score, classic style: first
+//! every leftover second, then every coin, each tick adding its bonus. The
+//! bonuses come from the level's `ScoringConfig`. The final score is worked
+//! out up front; the tally only animates towards it.
+
+/// Seconds between ticks
+const TICK_INTERVAL: f32 = 0.04;
+
+/// Leftover time and coins counting down into the score
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tally {
+    time_left: u32,
+    coins_left: u32,
+    score: u32,
+    final_score: u32,
+    /// Points added for each second and each coin
+    time_points: u32,
+    coin_points: u32,
+    tick_timer: f32,
+}
+
+impl Tally {
+    /// A tally about to count `time_left` seconds and `coins` coins into
+    /// `score`, at `time_points` a second and `coin_points` a coin
+    pub fn new(score: u32, time_left: u32, coins: u32, time_points: u32, coin_points: u32) -> Self {
+        let final_score = score + time_left * time_points + coins * coin_points;
+        Self { time_left, coins_left: coins, score, final_score, time_points, coin_points, tick_timer: 0.0 }
+    }
+
+    /// Seconds still to be counted
+    pub fn time_left(&self) -> u32 {
+        self.time_left
+    }
+
+    /// Coins still to be counted
+    pub fn coins_left(&self) -> u32 {
+        self.coins_left
+    }
+
+    /// The score so far
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// The score once everything has been counted
+    pub fn final_score(&self) -> u32 {
+        self.final_score
+    }
+
+    /// Whether everything has been counted
+    pub fn is_finished(&self) -> bool {
+        self.time_left == 0 && self.coins_left == 0
+    }
+
+    /// Count on by however many ticks fit in the time, returning how many there were
+    pub fn update(&mut self, delta_time: f32) -> u32 {
+        self.tick_timer += delta_time;
+        let mut ticks = 0;
+        while self.tick_timer >= TICK_INTERVAL && self.tick() {
+            self.tick_timer -= TICK_INTERVAL;
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// Count one second, or one coin once the time is done
+    fn tick(&mut self) -> bool {
+        if self.time_left > 0 {
+            self.time_left -= 1;
+            self.score += self.time_points;
+        } else if self.coins_left > 0 {
+            self.coins_left -= 1;
+            self.score += self.coin_points;
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally_counts_time_then_coins_into_the_score() {
+        let mut tally = Tally::new(1000, 12, 3, 50, 100);
+        assert_eq!(tally.final_score(), 1000 + 12 * 50 + 3 * 100);
+
+        // Time goes first, one second a tick
+        assert_eq!(tally.update(TICK_INTERVAL * 2.5), 2);
+        assert_eq!((tally.time_left(), tally.coins_left(), tally.score()), (10, 3, 1000 + 2 * 50));
+
+        let mut ticks = 2;
+        for _ in 0..100 {
+            ticks += tally.update(1.0 / 60.0);
+        }
+        assert!(tally.is_finished());
+        assert_eq!(ticks, 15);
+        assert_eq!((tally.time_left(), tally.coins_left()), (0, 0));
+        assert_eq!(tally.score(), tally.final_score());
+        assert_eq!(tally.update(1.0), 0);
+    }
+}
@@ -0,0 +1,73 @@
+//! Time Attack Medals
+//!
+//! In time attack mode the level timer is shown on screen and, when the goal
+//! is reached, the finishing time is compared against per-level thresholds to
+//! award a bronze, silver or gold medal.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Medal awarded for finishing a level fast enough
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Medal {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+impl Medal {
+    /// Display name of the medal
+    pub fn name(&self) -> &'static str {
+        match self {
+            Medal::Gold => "Gold",
+            Medal::Silver => "Silver",
+            Medal::Bronze => "Bronze",
+        }
+    }
+
+    /// Color used when drawing the medal
+    pub fn color(&self) -> Color {
+        match self {
+            Medal::Gold => GOLD,
+            Medal::Silver => LIGHTGRAY,
+            Medal::Bronze => Color::new(0.8, 0.5, 0.2, 1.0),
+        }
+    }
+}
+
+/// Finishing times (in seconds) needed for each medal, stored with the level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MedalThresholds {
+    pub gold: f32,
+    pub silver: f32,
+    pub bronze: f32,
+}
+
+impl MedalThresholds {
+    /// Get the medal earned for a finishing time, if any
+    pub fn award(&self, time: f32) -> Option<Medal> {
+        if time <= self.gold {
+            Some(Medal::Gold)
+        } else if time <= self.silver {
+            Some(Medal::Silver)
+        } else if time <= self.bronze {
+            Some(Medal::Bronze)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gold_threshold_boundary() {
+        let thresholds = MedalThresholds { gold: 10.0, silver: 15.0, bronze: 25.0 };
+
+        assert_eq!(thresholds.award(9.5), Some(Medal::Gold));
+        assert_eq!(thresholds.award(10.01), Some(Medal::Silver));
+        assert_eq!(thresholds.award(30.0), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! Contextual Tutorial
+//!
+//! Control hints are shown only until the player has used the control they
+//! describe, then fade out, so experienced players get an uncluttered screen.
+//! The level's objective is shown the same way for the first few seconds.
+
+use crate::input::{Action, InputState, KeyBindings};
+use crate::prompts::{action_prompt, movement_prompt, InputDevice};
+use crate::simple_level::GoalKind;
+
+/// Seconds a hint takes to fade out once its step is done
+const FADE_TIME: f32 = 1.0;
+/// Seconds of play the objective stays up for, as there's no control to try for it
+const GOAL_TIME: f32 = 5.0;
+
+/// Something the tutorial teaches: the objective, then each control
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialStep {
+    Goal,
+    Move,
+    Jump,
+    Fire,
+}
+
+impl TutorialStep {
+    /// Every step, in the order the hints are listed
+    pub const ALL: [TutorialStep; 4] = [TutorialStep::Goal, TutorialStep::Move, TutorialStep::Jump, TutorialStep::Fire];
+
+    /// The hint shown until the step is done, naming the controls for the
+    /// active device and what finishes this level's kind of goal
+    pub fn hint(&self, device: InputDevice, bindings: &KeyBindings, goal: GoalKind) -> String {
+        match self {
+            TutorialStep::Goal => {
+                let objective = match goal {
+                    GoalKind::Flag => "Reach the green flag",
+                    GoalKind::Door => "Walk through the door",
+                    GoalKind::Orb => "Touch the orb",
+                };
+                format!("{} to win! ESC to quit", objective)
+            }
+            TutorialStep::Move => format!("Use {} to move", movement_prompt(device, bindings)),
+            TutorialStep::Jump => format!("Press {} to jump", action_prompt(Action::Jump, device, bindings)),
+            TutorialStep::Fire => format!("Press {} to throw a fireball", action_prompt(Action::Fire, device, bindings)),
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Which tutorial steps the player has completed
+#[derive(Debug, Clone, Default)]
+pub struct Tutorial {
+    /// Seconds since each step was completed, or `None` while it's still to do
+    completed: [Option<f32>; TutorialStep::ALL.len()],
+    /// Seconds played so far, which completes the goal step
+    played: f32,
+}
+
+impl Tutorial {
+    /// Mark steps complete from the player's input and advance the fades
+    pub fn update(&mut self, input: &InputState, delta_time: f32) {
+        for fade in self.completed.iter_mut().flatten() {
+            *fade += delta_time;
+        }
+        self.played += delta_time;
+        if self.played >= GOAL_TIME {
+            self.complete(TutorialStep::Goal);
+        }
+        if input.left || input.right {
+            self.complete(TutorialStep::Move);
+        }
+        if input.jump_pressed {
+            self.complete(TutorialStep::Jump);
+        }
+        if input.fire_pressed {
+            self.complete(TutorialStep::Fire);
+        }
+    }
+
+    /// Mark a step as done, starting its hint's fade
+    pub fn complete(&mut self, step: TutorialStep) {
+        self.completed[step.index()].get_or_insert(0.0);
+    }
+
+    /// Whether the player has done this step
+    pub fn is_complete(&self, step: TutorialStep) -> bool {
+        self.completed[step.index()].is_some()
+    }
+
+    /// Opacity of a step's hint: fully shown until completed, then fading to nothing
+    pub fn hint_alpha(&self, step: TutorialStep) -> f32 {
+        match self.completed[step.index()] {
+            None => 1.0,
+            Some(elapsed) => (1.0 - elapsed / FADE_TIME).max(0.0),
+        }
+    }
+
+    /// Hints still on screen with their opacity. `can_fire` hides the fire
+    /// hint from players without the fire flower.
+    pub fn visible_hints(&self, can_fire: bool) -> Vec<(TutorialStep, f32)> {
+        TutorialStep::ALL
+            .into_iter()
+            .filter(|step| can_fire || *step != TutorialStep::Fire)
+            .map(|step| (step, self.hint_alpha(step)))
+            .filter(|(_, alpha)| *alpha > 0.0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jumping_completes_the_jump_step() {
+        let mut tutorial = Tutorial::default();
+        let idle = InputState::default();
+        tutorial.update(&idle, 0.5);
+        assert!(tutorial.visible_hints(false).iter().any(|(step, _)| *step == TutorialStep::Jump));
+
+        let jump = InputState { jump_pressed: true, jump_held: true, ..Default::default() };
+        tutorial.update(&jump, 1.0 / 60.0);
+        assert!(tutorial.is_complete(TutorialStep::Jump));
+        assert!(!tutorial.is_complete(TutorialStep::Move));
+
+        // The hint fades out rather than vanishing at once
+        tutorial.update(&idle, FADE_TIME / 2.0);
+        assert!(tutorial.hint_alpha(TutorialStep::Jump) > 0.0);
+        tutorial.update(&idle, FADE_TIME);
+        let hints = tutorial.visible_hints(false);
+        assert!(hints.iter().all(|(step, _)| *step != TutorialStep::Jump));
+        assert_eq!(hints, vec![(TutorialStep::Goal, 1.0), (TutorialStep::Move, 1.0)]);
+    }
+
+    #[test]
+    fn test_goal_hint_names_the_kind_of_goal() {
+        let hint = |goal| TutorialStep::Goal.hint(InputDevice::Keyboard, &KeyBindings::default(), goal);
+        assert!(hint(GoalKind::Flag).contains("flag"));
+        assert!(hint(GoalKind::Door).contains("door"));
+        assert!(hint(GoalKind::Orb).contains("orb"));
+    }
+
+    #[test]
+    fn test_goal_fades_out_after_a_few_seconds() {
+        let mut tutorial = Tutorial::default();
+        let idle = InputState::default();
+        tutorial.update(&idle, GOAL_TIME / 2.0);
+        assert_eq!(tutorial.hint_alpha(TutorialStep::Goal), 1.0);
+        tutorial.update(&idle, GOAL_TIME / 2.0);
+        assert!(tutorial.is_complete(TutorialStep::Goal));
+        tutorial.update(&idle, FADE_TIME);
+        assert!(tutorial.visible_hints(false).iter().all(|(step, _)| *step != TutorialStep::Goal));
+    }
+}
@@ -0,0 +1,151 @@
+//! Tweens and Easing
+//!
+//! Easing curves shape how an animation moves between two values: each takes
+//! progress from 0 to 1 and returns how far along the value should be, from
+//! 0 at the start to 1 at the end (overshooting in between for `ease_out_back`).
+//! A `Tween` runs one of them over a duration, for anything that slides,
+//! scales or fades from one value to another.
+
+use macroquad::prelude::*;
+
+/// An easing curve, taking progress from 0 to 1
+pub type Easing = fn(f32) -> f32;
+
+/// Overshoot used by `ease_out_back`
+const BACK_OVERSHOOT: f32 = 1.70158;
+
+/// The value `t` of the way from `from` to `to`
+pub fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// Steady movement the whole way
+pub fn linear(t: f32) -> f32 {
+    t
+}
+
+/// Starts slowly and speeds up
+pub fn ease_in(t: f32) -> f32 {
+    t * t
+}
+
+/// Starts quickly and slows down to a stop
+pub fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t) * (1.0 - t)
+}
+
+/// Starts and stops gently, following half a cosine wave
+pub fn ease_in_out(t: f32) -> f32 {
+    (1.0 - (std::f32::consts::PI * t).cos()) / 2.0
+}
+
+/// Shoots a little past the end, then settles back onto it
+pub fn ease_out_back(t: f32) -> f32 {
+    let u = t - 1.0;
+    1.0 + (BACK_OVERSHOOT + 1.0) * u * u * u + BACK_OVERSHOOT * u * u
+}
+
+/// Values a tween can move between
+pub trait Lerp: Copy {
+    /// The value `t` of the way from `self` to `to`
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        lerp(self, to, t)
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Color::new(lerp(self.r, to.r, t), lerp(self.g, to.g, t), lerp(self.b, to.b, t), lerp(self.a, to.a, t))
+    }
+}
+
+/// A value moving from one end to the other over a set time
+#[derive(Debug, Clone, Copy)]
+pub struct Tween<T: Lerp> {
+    pub from: T,
+    pub to: T,
+    /// Seconds the whole move takes
+    pub duration: f32,
+    elapsed: f32,
+    easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// A tween at its start, moving along the easing curve
+    pub fn new(from: T, to: T, duration: f32, easing: Easing) -> Self {
+        Self { from, to, duration, elapsed: 0.0, easing }
+    }
+
+    /// Move the tween on, stopping at the end
+    pub fn advance(&mut self, delta_time: f32) {
+        self.elapsed = (self.elapsed + delta_time).min(self.duration);
+    }
+
+    /// How much of the duration has passed, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 { 1.0 } else { self.elapsed / self.duration }
+    }
+
+    /// Whether the tween has reached its end
+    pub fn is_finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    /// The current value, shaped by the easing curve
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, (self.easing)(self.progress()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn test_easing_curves_at_start_middle_and_end() {
+        let cases: [(Easing, f32); 5] = [
+            (linear, 0.5),
+            (ease_in, 0.25),
+            (ease_out, 0.75),
+            (ease_in_out, 0.5),
+            (ease_out_back, 1.0876975),
+        ];
+        for (easing, middle) in cases {
+            assert_near(easing(0.0), 0.0);
+            assert_near(easing(0.5), middle);
+            assert_near(easing(1.0), 1.0);
+        }
+        assert_near(lerp(10.0, 20.0, 0.0), 10.0);
+        assert_near(lerp(10.0, 20.0, 0.5), 15.0);
+        assert_near(lerp(10.0, 20.0, 1.0), 20.0);
+    }
+
+    #[test]
+    fn test_tween_advances_to_its_end_and_stops() {
+        let mut tween = Tween::new(vec2(0.0, 100.0), vec2(40.0, 0.0), 2.0, ease_in);
+        assert_eq!(tween.value(), vec2(0.0, 100.0));
+
+        tween.advance(1.0);
+        assert_near(tween.progress(), 0.5);
+        assert_eq!(tween.value(), vec2(10.0, 75.0));
+        assert!(!tween.is_finished());
+
+        tween.advance(5.0);
+        assert!(tween.is_finished());
+        assert_eq!(tween.value(), vec2(40.0, 0.0));
+    }
+}
@@ -0,0 +1,359 @@
+//! Letterboxed Viewport
+//!
+//! The game is drawn at a fixed virtual resolution into an offscreen render
+//! target, which is then scaled up as far as it fits in the window and
+//! centred, with black bars filling the rest. This keeps the 4:3 design
+//! undistorted whatever shape the window is. `Letterbox` holds the layout
+//! maths, so it can map window coordinates back into the virtual screen.
+//!
+//! In pixel-perfect mode the virtual screen is instead rasterized at a low
+//! retro resolution and scaled up by a whole number with nearest-neighbour
+//! filtering, so every game pixel becomes the same crisp square block.
+//!
+//! A `ViewCamera` remembers which area the virtual screen is showing, so
+//! `ViewCamera::draw_scaled` can magnify whatever a draw call produces about
+//! an anchor point by briefly narrowing the camera onto a smaller area, for
+//! drawing entities bigger than their hitboxes.
+//!
+//! F11 switches between windowed and fullscreen. The letterbox is worked out
+//! from the window size every frame, so it follows the change on its own;
+//! `DisplaySettings` remembers the choice for next time.
+
+use std::cell::OnceCell;
+use std::fs;
+use std::path::Path;
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Width the game is designed for and drawn at
+pub const VIRTUAL_WIDTH: f32 = 800.0;
+/// Height the game is designed for and drawn at
+pub const VIRTUAL_HEIGHT: f32 = 600.0;
+/// Width of the low-resolution screen used in pixel-perfect mode
+pub const PIXEL_WIDTH: f32 = 320.0;
+/// Height of the low-resolution screen used in pixel-perfect mode
+pub const PIXEL_HEIGHT: f32 = 240.0;
+/// Size of one low-resolution pixel in virtual coordinates
+pub const PIXEL_GRID: f32 = VIRTUAL_WIDTH / PIXEL_WIDTH;
+/// Where the display settings are kept between sessions
+pub const DISPLAY_SETTINGS_PATH: &str = "display_settings.json";
+/// Toggles between windowed and fullscreen
+pub const FULLSCREEN_KEY: KeyCode = KeyCode::F11;
+
+/// Largest whole number of window pixels per low-resolution pixel that fits
+/// the window, but never less than one
+pub fn integer_scale(window_width: f32, window_height: f32, pixel_width: f32, pixel_height: f32) -> f32 {
+    (window_width / pixel_width).min(window_height / pixel_height).floor().max(1.0)
+}
+
+/// Round a virtual coordinate to the nearest low-resolution pixel
+pub fn snap_to_grid(value: f32) -> f32 {
+    (value / PIXEL_GRID).round() * PIXEL_GRID
+}
+
+/// Where the scaled virtual screen sits inside the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Letterbox {
+    /// Window pixels per virtual pixel
+    pub scale: f32,
+    /// Left edge of the picture in the window (width of the left bar)
+    pub offset_x: f32,
+    /// Top edge of the picture in the window (height of the top bar)
+    pub offset_y: f32,
+}
+
+impl Letterbox {
+    /// Fit a virtual screen into a window as large as possible without distorting it
+    pub fn fit(window_width: f32, window_height: f32, virtual_width: f32, virtual_height: f32) -> Self {
+        let scale = (window_width / virtual_width).min(window_height / virtual_height);
+        Self {
+            scale,
+            offset_x: (window_width - virtual_width * scale) / 2.0,
+            offset_y: (window_height - virtual_height * scale) / 2.0,
+        }
+    }
+
+    /// Fit a virtual screen rasterized at `pixel_width` x `pixel_height` into
+    /// a window, scaled by a whole number of window pixels per low-res pixel
+    pub fn fit_integer(
+        window_width: f32,
+        window_height: f32,
+        virtual_width: f32,
+        pixel_width: f32,
+        pixel_height: f32,
+    ) -> Self {
+        let factor = integer_scale(window_width, window_height, pixel_width, pixel_height);
+        Self {
+            scale: factor * pixel_width / virtual_width,
+            offset_x: (window_width - pixel_width * factor) / 2.0,
+            offset_y: (window_height - pixel_height * factor) / 2.0,
+        }
+    }
+
+    /// Map a window position into virtual coordinates. Positions over the
+    /// black bars fall outside 0..virtual size.
+    pub fn screen_to_virtual(&self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.offset_x) / self.scale, (y - self.offset_y) / self.scale)
+    }
+
+    /// Map a virtual position to where it appears in the window
+    pub fn virtual_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        (x * self.scale + self.offset_x, y * self.scale + self.offset_y)
+    }
+}
+
+/// Offscreen target the game draws into before it is letterboxed onto the window
+pub struct Viewport {
+    /// Created on first use, so the layout can be worked out without a window
+    target: OnceCell<RenderTarget>,
+    width: f32,
+    height: f32,
+    /// Resolution the target is rasterized at, if lower than the virtual one
+    /// and scaled up by whole numbers
+    pixels: Option<(f32, f32)>,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::new(VIRTUAL_WIDTH, VIRTUAL_HEIGHT)
+    }
+}
+
+impl Viewport {
+    /// Create a viewport with the given virtual resolution
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { target: OnceCell::new(), width, height, pixels: None }
+    }
+
+    /// Create a pixel-perfect viewport: the virtual screen is drawn at the
+    /// low retro resolution and scaled up by whole numbers
+    pub fn pixel_perfect() -> Self {
+        Self {
+            target: OnceCell::new(),
+            width: VIRTUAL_WIDTH,
+            height: VIRTUAL_HEIGHT,
+            pixels: Some((PIXEL_WIDTH, PIXEL_HEIGHT)),
+        }
+    }
+
+    /// The offscreen target, at the low resolution in pixel-perfect mode
+    fn target(&self) -> &RenderTarget {
+        self.target.get_or_init(|| {
+            let (width, height) = self.pixels.unwrap_or((self.width, self.height));
+            let target = render_target(width as u32, height as u32);
+            target.texture.set_filter(FilterMode::Nearest);
+            target
+        })
+    }
+
+    /// Layout of the virtual screen in the current window
+    pub fn letterbox(&self) -> Letterbox {
+        self.letterbox_in(screen_width(), screen_height())
+    }
+
+    /// Layout of the virtual screen in a window of the given size
+    pub fn letterbox_in(&self, window_width: f32, window_height: f32) -> Letterbox {
+        match self.pixels {
+            Some((pixel_width, pixel_height)) => {
+                Letterbox::fit_integer(window_width, window_height, self.width, pixel_width, pixel_height)
+            }
+            None => Letterbox::fit(window_width, window_height, self.width, self.height),
+        }
+    }
+
+    /// Mouse position in virtual coordinates
+    pub fn mouse_position(&self) -> (f32, f32) {
+        let (x, y) = mouse_position();
+        self.letterbox().screen_to_virtual(x, y)
+    }
+
+    /// Redirect drawing into the virtual screen
+    pub fn begin(&self) -> ViewCamera {
+        self.begin_zoomed(1.0, 0.0)
+    }
+
+    /// Redirect drawing into the virtual screen, magnified `zoom` times so
+    /// it shows the screen's left edge from `top` down
+    pub fn begin_zoomed(&self, zoom: f32, top: f32) -> ViewCamera {
+        let area = Rect::new(0.0, top, self.width / zoom, self.height / zoom);
+        let camera = ViewCamera { area, target: Some(self.target().clone()) };
+        camera.show(area);
+        camera
+    }
+
+    /// Draw the virtual screen into the window, centred between black bars
+    pub fn end(&self) {
+        set_default_camera();
+        clear_background(BLACK);
+        let letterbox = self.letterbox();
+        draw_texture_ex(
+            &self.target().texture,
+            letterbox.offset_x,
+            letterbox.offset_y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(self.width * letterbox.scale, self.height * letterbox.scale)),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// The area drawing is currently shown through, and where it ends up
+#[derive(Clone)]
+pub struct ViewCamera {
+    area: Rect,
+    target: Option<RenderTarget>,
+}
+
+impl ViewCamera {
+    /// A camera showing `area` straight in the window
+    pub fn new(area: Rect) -> Self {
+        Self { area, target: None }
+    }
+
+    /// Show `area` through the whole picture
+    fn show(&self, area: Rect) {
+        let mut camera = Camera2D::from_display_rect(area);
+        camera.render_target = self.target.clone();
+        set_camera(&camera);
+    }
+
+    /// The smaller area that, shown through the whole picture, magnifies
+    /// everything `scale` times about `anchor`, which stays where it is
+    pub fn scaled_area(&self, anchor: Vec2, scale: f32) -> Rect {
+        let corner = anchor + (self.area.point() - anchor) / scale;
+        Rect::new(corner.x, corner.y, self.area.w / scale, self.area.h / scale)
+    }
+
+    /// Where a box drawn inside `draw_scaled` appears, in this camera's coordinates
+    pub fn drawn_bounds(&self, bounds: Rect, anchor: Vec2, scale: f32) -> Rect {
+        let scaled = self.scaled_area(anchor, scale);
+        let factor = self.area.w / scaled.w;
+        let corner = self.area.point() + (bounds.point() - scaled.point()) * factor;
+        Rect::new(corner.x, corner.y, bounds.w * factor, bounds.h * factor)
+    }
+
+    /// Run `draw` with everything it draws scaled by `scale` about `anchor`
+    pub fn draw_scaled(&self, anchor: Vec2, scale: f32, draw: impl FnOnce()) {
+        if scale == 1.0 {
+            return draw();
+        }
+        self.show(self.scaled_area(anchor, scale));
+        draw();
+        self.show(self.area);
+    }
+}
+
+/// Display choices remembered between sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplaySettings {
+    pub fullscreen: bool,
+}
+
+impl DisplaySettings {
+    /// Switch between windowed and fullscreen, returning whether it's now fullscreen.
+    /// Only the setting changes; `apply` passes it on to the window.
+    pub fn toggle_fullscreen(&mut self) -> bool {
+        self.fullscreen = !self.fullscreen;
+        self.fullscreen
+    }
+
+    /// Put the window into the chosen mode. Going back to a window restores
+    /// the design resolution.
+    pub fn apply(&self) {
+        set_fullscreen(self.fullscreen);
+        if !self.fullscreen {
+            request_new_screen_size(VIRTUAL_WIDTH, VIRTUAL_HEIGHT);
+        }
+    }
+
+    /// Load the settings from a JSON file, using the defaults if there isn't one yet
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Box<dyn std::error::Error>> {
+        match fs::read_to_string(filepath) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Save the settings to a JSON file
+    pub fn save<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(filepath, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaled_camera_magnifies_about_the_anchor() {
+        let camera = ViewCamera::new(Rect::new(0.0, 100.0, 400.0, 300.0));
+        let anchor = vec2(200.0, 250.0);
+        let scaled = camera.scaled_area(anchor, 2.0);
+        assert_eq!(scaled, Rect::new(100.0, 175.0, 200.0, 150.0));
+        // The anchor sits at the same place in both areas
+        let fraction = |area: Rect, p: Vec2| (p - area.point()) / area.size();
+        assert_eq!(fraction(scaled, anchor), fraction(camera.area, anchor));
+
+        // A box standing on the anchor comes out twice the size, still standing there
+        let bounds = Rect::new(184.0, 218.0, 32.0, 32.0);
+        assert_eq!(camera.drawn_bounds(bounds, anchor, 2.0), Rect::new(168.0, 186.0, 64.0, 64.0));
+        assert_eq!(camera.drawn_bounds(bounds, anchor, 1.0), bounds);
+    }
+
+    #[test]
+    fn test_screen_to_virtual_through_letterbox() {
+        // A wide window gets bars on the left and right
+        let pillarbox = Letterbox::fit(1600.0, 600.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT);
+        assert_eq!(pillarbox, Letterbox { scale: 1.0, offset_x: 400.0, offset_y: 0.0 });
+        assert_eq!(pillarbox.screen_to_virtual(400.0, 0.0), (0.0, 0.0));
+        assert_eq!(pillarbox.screen_to_virtual(800.0, 300.0), (400.0, 300.0));
+        assert!(pillarbox.screen_to_virtual(100.0, 300.0).0 < 0.0);
+
+        // A tall window gets bars above and below, and scales down
+        let letterbox = Letterbox::fit(400.0, 800.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT);
+        assert_eq!(letterbox, Letterbox { scale: 0.5, offset_x: 0.0, offset_y: 250.0 });
+        assert_eq!(letterbox.screen_to_virtual(200.0, 400.0), (400.0, 300.0));
+        assert_eq!(letterbox.virtual_to_screen(800.0, 600.0), (400.0, 550.0));
+    }
+
+    #[test]
+    fn test_integer_scale_fits_whole_multiples() {
+        assert_eq!(integer_scale(1280.0, 960.0, PIXEL_WIDTH, PIXEL_HEIGHT), 4.0);
+        // Not quite room for 4x vertically, so it drops to 3x
+        assert_eq!(integer_scale(1920.0, 950.0, PIXEL_WIDTH, PIXEL_HEIGHT), 3.0);
+        // Windows smaller than the low-res screen still get 1x
+        assert_eq!(integer_scale(200.0, 100.0, PIXEL_WIDTH, PIXEL_HEIGHT), 1.0);
+
+        // The 3x picture is centred, and maps back to the full virtual screen
+        let letterbox = Letterbox::fit_integer(1920.0, 950.0, VIRTUAL_WIDTH, PIXEL_WIDTH, PIXEL_HEIGHT);
+        assert_eq!((letterbox.offset_x, letterbox.offset_y), (480.0, 115.0));
+        let (x, y) = letterbox.screen_to_virtual(480.0 + 960.0, 115.0 + 720.0);
+        assert!((x - VIRTUAL_WIDTH).abs() < 1e-3 && (y - VIRTUAL_HEIGHT).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_fullscreen_toggle_refits_the_letterbox() {
+        let mut settings = DisplaySettings::default();
+        assert!(settings.toggle_fullscreen());
+
+        // A 1080p screen scales the 4:3 picture to full height, with bars at the sides
+        let viewport = Viewport::default();
+        let fullscreen = viewport.letterbox_in(1920.0, 1080.0);
+        assert_eq!(fullscreen, Letterbox { scale: 1.8, offset_x: 240.0, offset_y: 0.0 });
+        assert_eq!(fullscreen.screen_to_virtual(1680.0, 1080.0), (VIRTUAL_WIDTH, VIRTUAL_HEIGHT));
+        // Pixel-perfect mode fits 4x, leaving bars all round
+        let pixels = Viewport::pixel_perfect().letterbox_in(1920.0, 1080.0);
+        assert_eq!((pixels.offset_x, pixels.offset_y), (320.0, 60.0));
+
+        // And back in the window the picture fills it exactly again
+        assert!(!settings.toggle_fullscreen());
+        let windowed = viewport.letterbox_in(VIRTUAL_WIDTH, VIRTUAL_HEIGHT);
+        assert_eq!(windowed, Letterbox { scale: 1.0, offset_x: 0.0, offset_y: 0.0 });
+    }
+}
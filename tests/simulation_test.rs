@@ -0,0 +1,41 @@
+//! Headless simulation test
+//!
+//! Runs the `simulate` binary with a known input script and checks that the
+//! player ends up in the expected place.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use rust_mario::simple_level::SimpleLevel;
+use rust_mario::simulation::{parse_script, simulate};
+
+/// Walk right for one second from the spawn point
+const WALK_RIGHT_SCRIPT: &str = "# walk right for a second\n60 right\n";
+
+#[test]
+fn test_simulate_binary_reports_final_position() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_simulate"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start simulate");
+    child.stdin.take().unwrap().write_all(WALK_RIGHT_SCRIPT.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).expect("output is JSON");
+    assert_eq!(result["steps"], 60);
+    // Spawned at x = 50 and walking at 200 px/s for a second
+    assert!((result["player_x"].as_f64().unwrap() - 250.0).abs() < 0.01);
+    // Dropped from the spawn onto the floating platform at x = 200, whose top is at y = 300
+    assert!((result["player_y"].as_f64().unwrap() - 280.0).abs() < 0.01);
+    assert_eq!(result["on_ground"], true);
+}
+
+#[test]
+fn test_simulation_is_deterministic() {
+    let inputs = parse_script("60 right\nright jump\n30 right\n").unwrap();
+    let first = simulate(&mut SimpleLevel::new(), &inputs, 120);
+    let second = simulate(&mut SimpleLevel::new(), &inputs, 120);
+    assert_eq!(first, second);
+}